@@ -3,24 +3,54 @@
 in vec3 vColor;
 out vec4 f_color;
 
-const int MAX_SPHERES = 100;
-const int MAX_TRIANGLES = 100;
 const float MIN_DIST = 0.005;
 const int MAX_DEPTH = 150;
 
-const vec3 BG_CLR = vec3(0.6196, 0.6118, 0.6549);
+// the bvh is split in half at every level (see src/bvh.rs), so it is never deeper than this
+const int BVH_STACK_SIZE = 64;
+
+// the most bounces the side panel allows, see maxBounces
+const int MAX_BOUNCES = 8;
+// reflected and refracted rays start this far from the surface, on the side they go to
+const float BOUNCE_BIAS = 0.02;
+// rays that would give less than this of the pixel color are not traced
+const float MIN_BOUNCE_WEIGHT = 0.01;
+
+// surfaces closer than SELECTION_DIST to the selected object are tinted with SELECTION_CLR
+const vec3 SELECTION_CLR = vec3(1.0, 0.6, 0.1);
+const float SELECTION_DIST = 0.02;
 
 uniform int numOfSpheres;
 uniform int numOfTriangles;
 uniform int numOfBoxes;
 uniform int numOfMengerSponges;
-uniform vec3 lightPos;
-
-// 0 : Normal
-// 1 : Intersect
-// 2 : Blend
-uniform int renderMode;
-uniform float smoothness;
+uniform int numOfMeshes;
+uniform int numOfTori;
+uniform int numOfCapsules;
+uniform int numOfCylinders;
+uniform int numOfCones;
+
+// the bvh is used when useBvh is set and there are nodes, otherwise every object is looked at
+uniform int numOfBvhNodes;
+uniform bool useBvh;
+
+// the objects in objectOrder, the position of the first one that is not in the bvh.
+// it and every object after it in list order are applied one at a time
+uniform int numOfObjects;
+uniform int orderedStart;
+
+// 0 : Sphere
+// 1 : Cube
+// 2 : Triangle
+// 3 : Menger sponge
+// 4 : Mesh
+// 5 : Torus
+// 6 : Capsule
+// 7 : Cylinder
+// 8 : Cone
+// -1 when nothing is selected
+uniform int selectedType;
+uniform int selectedIndex;
 
 uniform vec3 cameraPos;
 uniform vec4 cameraRotationQuaternion;
@@ -28,231 +58,343 @@ uniform float cameraFOV;
 
 uniform vec2 u_resolution;
 
-struct Sphere {
-    vec3 pos;
-    vec3 color;
-    float radius;
-};
+// how many times rays are reflected and refracted, at most MAX_BOUNCES
+uniform int maxBounces;
 
+#include "sdf.glsl"
+#include "texture.glsl"
+#include "sky.glsl"
 
-layout(std140) buffer sphere_array {
-    float number_of_objects;
-    vec4 positions[128];
-    vec4 colors[128];
-    vec4 radius[128];
+// the objects are stored in shader storage buffers that are as long as the scene needs
+// the structs have to match the ones in src/gpu_storage.rs, every field is a vec4 to keep the layouts the same
+struct GpuSphere {
+    vec4 pos;
+    vec4 color;
+    vec4 radius;
+    vec4 rotation;
+    vec4 scale;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
 };
 
-layout(std140) buffer triangle_array {
-    vec4 v1[128];
-    vec4 v2[128];
-    vec4 v3[128];
-    vec4 norm[128];
-    vec4 color_triangles[128];
+struct GpuTriangle {
+    vec4 v1;
+    vec4 v2;
+    vec4 v3;
+    vec4 norm;
+    vec4 color;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
 };
 
-layout(std140) buffer cube_array {
-    vec4 pos_cubes[128];
-    vec4 dim_cubes[128];
-    vec4 color_cubes[128];
+struct GpuCube {
+    vec4 pos;
+    vec4 dim;
+    vec4 color;
+    vec4 rotation;
+    vec4 scale;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
 };
 
-layout(std140) buffer menger_sponge_array {
-    vec4 pos_menger_sponges[128];
-    vec4 iterations_menger_sponges[128];
-    vec4 color_menger_sponges[128];
+struct GpuMengerSponge {
+    vec4 pos;
+    vec4 iterations;
+    vec4 color;
+    vec4 rotation;
+    vec4 scale;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
 };
 
-Sphere getSphereFromIndex(int id){
-    Sphere s; 
-    s.radius = radius[id].x;
-    s.pos = vec3(positions[id].x, positions[id].y, positions[id].z);
-    s.color = vec3(colors[id].x, colors[id].y, colors[id].z);
-    return s;
-}
+// triangles is (first triangle in meshTriangles, number of triangles)
+struct GpuMesh {
+    vec4 pos;
+    vec4 scale;
+    vec4 color;
+    ivec4 triangles;
+    vec4 rotation;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
+    vec4 bounds_min;
+    vec4 bounds_max;
+};
 
-struct Triangle {
-    vec3 v1;
-    vec3 v2;
-    vec3 v3;
-    vec3 norm;
-    vec3 color;
+layout(std430) buffer sphere_array {
+    GpuSphere spheres[];
 };
 
-struct Cube {
-    vec3 pos;
-    vec3 dim;
-    vec3 color;
+layout(std430) buffer triangle_array {
+    GpuTriangle triangles[];
 };
 
-struct MengerSponge {
-    vec3 pos;
-    float iterations;
-    vec3 color;
+layout(std430) buffer cube_array {
+    GpuCube cubes[];
 };
 
-Triangle newTriangle(vec3 a, vec3 b, vec3 c, vec3 clr) {
-    vec3 ba = b - a;
-    vec3 ac = a - c;
-    vec3 nor = normalize(cross( ba, ac ));
-    
-    return Triangle(a, b, c, nor, clr);
-}
+layout(std430) buffer menger_sponge_array {
+    GpuMengerSponge mengerSponges[];
+};
 
+// size is (radius, thickness)
+struct GpuTorus {
+    vec4 pos;
+    vec4 size;
+    vec4 color;
+    vec4 rotation;
+    vec4 scale;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
+};
 
-struct PaddedSphere {
+// size is (half height, radius)
+struct GpuCapsule {
     vec4 pos;
+    vec4 size;
     vec4 color;
-    vec4 radius;
+    vec4 rotation;
+    vec4 scale;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
 };
 
-struct PaddedTriangle {
-    vec4 v1;
-    vec4 v2;
-    vec4 v3;
+// size is (half height, radius)
+struct GpuCylinder {
+    vec4 pos;
+    vec4 size;
     vec4 color;
+    vec4 rotation;
+    vec4 scale;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
 };
 
+// size is (half height, bottom radius, top radius)
+struct GpuCone {
+    vec4 pos;
+    vec4 size;
+    vec4 color;
+    vec4 rotation;
+    vec4 scale;
+    vec4 combine;
+    ivec4 material;
+    vec4 texture;
+};
 
-struct Ray {
-    vec3 pos;
-    vec3 dir;
+layout(std430) buffer torus_array {
+    GpuTorus tori[];
 };
 
-// from old demo code, 
-/// SSBO 
-layout(std430, binding = 10) buffer spheres_
-{
-    PaddedSphere paddedSpheres[MAX_SPHERES];
+layout(std430) buffer capsule_array {
+    GpuCapsule capsules[];
 };
 
-/// SSBO 
-layout(std430, binding = 11) buffer triangles_
-{
-    PaddedTriangle paddedTriangles[MAX_TRIANGLES];
+layout(std430) buffer cylinder_array {
+    GpuCylinder cylinders[];
 };
 
+layout(std430) buffer cone_array {
+    GpuCone cones[];
+};
+
+layout(std430) buffer mesh_array {
+    GpuMesh meshes[];
+};
 
+// the triangles of every mesh, relative to the position of the mesh
+layout(std430) buffer mesh_triangle_array {
+    GpuTriangle meshTriangles[];
+};
 
-float dist3(vec3 pos1, vec3 pos2) {
-    float dx = pos1.x - pos2.x;
-    float dy = pos1.y - pos2.y;
-    float dz = pos1.z - pos2.z;
+// the library of materials, the objects have the index of theirs in material.x
+// texture is (pattern, image, scale, sharpness), see ObjectTexture
+struct GpuMaterial {
+    vec4 optics;
+    vec4 surface;
+};
 
-    return sqrt(dx*dx + dy*dy + dz*dz);
+layout(std430) buffer material_array {
+    GpuMaterial materials[];
+};
+
+// the root is the first node, data is (type of the object or -1 for a node with children, index of the object, left child, right child)
+struct BvhNode {
+    vec4 bounds_min;
+    vec4 bounds_max;
+    ivec4 data;
+};
+
+layout(std430) buffer bvh_nodes {
+    BvhNode bvhNodes[];
+};
+
+// every object in list order, id is (type of the object, index of the object, 0, 0)
+struct ObjectId {
+    ivec4 id;
+};
+
+layout(std430) buffer object_order {
+    ObjectId objectOrder[];
+};
+
+Sphere getSphereFromIndex(int id){
+    Sphere s; 
+    s.radius = spheres[id].radius.x;
+    s.pos = spheres[id].pos.xyz;
+    s.color = spheres[id].color.xyz;
+    return s;
 }
 
+struct Ray {
+    vec3 pos;
+    vec3 dir;
+};
+
+Transform getTransform(vec4 rotation, vec4 scale) {
+    Transform t;
+    t.rotation = rotation;
+    t.scale = scale.xyz;
+    return t;
+}
 
 Sphere getSphere(int index) {
     Sphere s; 
-    s.radius = radius[index].x;
-    s.pos = vec3(positions[index].x, positions[index].y, positions[index].z);
-    s.color = vec3(colors[index].x, colors[index].y, colors[index].z);
+    s.radius = spheres[index].radius.x;
+    s.pos = spheres[index].pos.xyz;
+    s.color = spheres[index].color.xyz;
+    s.transform = getTransform(spheres[index].rotation, spheres[index].scale);
     return s;
 }
 
 Triangle getTriangle(int index) {
     Triangle t; 
-    t.v1 = vec3(v1[index].x, v1[index].y, v1[index].z);
-    t.v2 = vec3(v2[index].x, v2[index].y, v2[index].z);
-    t.v1 = vec3(v3[index].x, v3[index].y, v3[index].z);
-    t.norm = vec3(norm[index].x, norm[index].y, norm[index].z);
-    t.color = vec3(color_triangles[index].x, color_triangles[index].y, color_triangles[index].z);
+    t.v1 = triangles[index].v1.xyz;
+    t.v2 = triangles[index].v2.xyz;
+    t.v3 = triangles[index].v3.xyz;
+    t.norm = triangles[index].norm.xyz;
+    t.color = triangles[index].color.xyz;
     return t;
 }
 
 Cube getCube(int index) {
     Cube c; 
-    c.pos = vec3(pos_cubes[index].x, pos_cubes[index].y, pos_cubes[index].z);
-    c.dim = vec3(dim_cubes[index].x, dim_cubes[index].y, dim_cubes[index].z);
-    c.color = vec3(color_cubes[index].x, color_cubes[index].y, color_cubes[index].z);
+    c.pos = cubes[index].pos.xyz;
+    c.dim = cubes[index].dim.xyz;
+    c.color = cubes[index].color.xyz;
+    c.transform = getTransform(cubes[index].rotation, cubes[index].scale);
 
     return c;
 }
 
-MengerSponge getMengerSponge(int index) {
-    MengerSponge m;
-    m.pos = vec3(pos_menger_sponges[index].x, pos_menger_sponges[index].y, pos_menger_sponges[index].z);
-    m.iterations = iterations_menger_sponges[index].x;
-    m.color = vec3(color_menger_sponges[index].x, color_menger_sponges[index].y, color_menger_sponges[index].z);
-
-    return m;
+Triangle getMeshTriangle(int index) {
+    Triangle t;
+    t.v1 = meshTriangles[index].v1.xyz;
+    t.v2 = meshTriangles[index].v2.xyz;
+    t.v3 = meshTriangles[index].v3.xyz;
+    t.norm = meshTriangles[index].norm.xyz;
+    t.color = meshTriangles[index].color.xyz;
+    return t;
 }
 
-// from https://www.youtube.com/watch?v=Cp5WWtMoeKg
-float smoothMin(float dstA, float dstB, float k) {
-    float h = max(k - abs(dstA - dstB), 0.0) / k;
-    return min(dstA, dstB) - h*h*h*k/6.0;
-}
+Mesh getMesh(int index) {
+    Mesh m;
+    m.pos = meshes[index].pos.xyz;
+    m.color = meshes[index].color.xyz;
+    m.firstTriangle = meshes[index].triangles.x;
+    m.numTriangles = meshes[index].triangles.y;
+    m.transform = getTransform(meshes[index].rotation, meshes[index].scale);
+    m.boundsMin = meshes[index].bounds_min.xyz;
+    m.boundsMax = meshes[index].bounds_max.xyz;
 
-float sphereDist(Sphere sphere, vec3 pos) {
-    return dist3(sphere.pos, pos) - sphere.radius;
+    return m;
 }
 
-float dot2(vec3 v ) { return dot(v,v); }
+Torus getTorus(int index) {
+    Torus t;
+    t.pos = tori[index].pos.xyz;
+    t.radius = tori[index].size.x;
+    t.thickness = tori[index].size.y;
+    t.color = tori[index].color.xyz;
+    t.transform = getTransform(tori[index].rotation, tori[index].scale);
 
-// from https://iquilezles.org/articles/distfunctions/
-float triangleDist(Triangle triangle, vec3 pos) {
-    vec3 a = triangle.v1;
-    vec3 b = triangle.v2;
-    vec3 c = triangle.v3;
-    vec3 p = pos;
+    return t;
+}
 
-    vec3 ba = b - a; vec3 pa = p - a;
-    vec3 cb = c - b; vec3 pb = p - b;
-    vec3 ac = a - c; vec3 pc = p - c;
-    vec3 nor = triangle.norm;
+Capsule getCapsule(int index) {
+    Capsule c;
+    c.pos = capsules[index].pos.xyz;
+    c.halfHeight = capsules[index].size.x;
+    c.radius = capsules[index].size.y;
+    c.color = capsules[index].color.xyz;
+    c.transform = getTransform(capsules[index].rotation, capsules[index].scale);
 
-    return sqrt(
-    (sign(dot(cross(ba,nor),pa)) +
-        sign(dot(cross(cb,nor),pb)) +
-        sign(dot(cross(ac,nor),pc))<2.0)
-        ?
-        min( min(
-        dot2(ba*clamp(dot(ba,pa)/dot2(ba),0.0,1.0)-pa),
-        dot2(cb*clamp(dot(cb,pb)/dot2(cb),0.0,1.0)-pb) ),
-        dot2(ac*clamp(dot(ac,pc)/dot2(ac),0.0,1.0)-pc) )
-        :
-        dot(nor,pa)*dot(nor,pa)/dot2(nor) );
+    return c;
 }
 
+Cylinder getCylinder(int index) {
+    Cylinder c;
+    c.pos = cylinders[index].pos.xyz;
+    c.halfHeight = cylinders[index].size.x;
+    c.radius = cylinders[index].size.y;
+    c.color = cylinders[index].color.xyz;
+    c.transform = getTransform(cylinders[index].rotation, cylinders[index].scale);
 
-// from https://iquilezles.org/articles/distfunctions/
-float cubeDist(Cube box, vec3 pos)
-{
-    vec3 p = pos - box.pos;
-    vec3 q = abs(p) - box.dim;
-    return length(max(q,0.0)) + min(max(q.x,max(q.y,q.z)),0.0);
+    return c;
 }
 
-vec3 getDir(vec2 uv) {
-    return vec3(uv.xy, 1.0 / tan(radians(cameraFOV / 2.0)));
-}
+Cone getCone(int index) {
+    Cone c;
+    c.pos = cones[index].pos.xyz;
+    c.halfHeight = cones[index].size.x;
+    c.bottomRadius = cones[index].size.y;
+    c.topRadius = cones[index].size.z;
+    c.color = cones[index].color.xyz;
+    c.transform = getTransform(cones[index].rotation, cones[index].scale);
 
-vec3 getSphereNormal(Sphere sphere, vec3 pos) {
-    return normalize((sphere.pos - pos));
+    return c;
 }
 
-vec3 getLightVec(vec3 pos) {
-    return normalize((pos - lightPos));
-}
+MengerSponge getMengerSponge(int index) {
+    MengerSponge m;
+    m.pos = mengerSponges[index].pos.xyz;
+    m.iterations = mengerSponges[index].iterations.x;
+    m.color = mengerSponges[index].color.xyz;
+    m.transform = getTransform(mengerSponges[index].rotation, mengerSponges[index].scale);
 
-float getLightCoefSphere(Ray ray, Sphere sphere) {
-    return dot(getLightVec(ray.pos), getSphereNormal(sphere, ray.pos));
+    return m;
 }
 
-float getLightCoefTriangle(Ray ray, Triangle triangle) {
-    return dot(getLightVec(ray.pos), triangle.norm);
-}
+// the same as MESH_BOUNDS_MARGIN in src/sdf.rs. further than this from the box around a mesh the distance to the box is used,
+// closer the triangles are needed for the normals, the shadows and the smooth operations
+const float MESH_BOUNDS_MARGIN = 1.0;
 
-vec3 intersectXZPlane(Ray ray) {
-    // Check if the ray is parallel to the x-z plane
-    if (abs(ray.dir.y) < 1e-6) {
-        return vec3(0.0, 0.0, 0.0);  // Ray is parallel to the plane
+float meshDist(Mesh mesh, vec3 pos) {
+    vec3 localPos = pos - mesh.pos;
+
+    // the box is around the triangles, so the distance to it is never more than the distance to the mesh
+    float boundsDist = length(max(max(mesh.boundsMin - localPos, localPos - mesh.boundsMax), 0.0));
+    if (boundsDist > MESH_BOUNDS_MARGIN) {
+        return boundsDist;
     }
 
-    float t = -ray.pos.y / ray.dir.y;
+    float dst = 10000000.0;
 
-    return ray.pos + t * ray.dir;
+    for (int i = mesh.firstTriangle; i < mesh.firstTriangle + mesh.numTriangles; i++) {
+        dst = min(dst, triangleDist(getMeshTriangle(i), localPos));
+    }
+    return dst;
+}
+
+vec3 getDir(vec2 uv) {
+    return vec3(uv.xy, 1.0 / tan(radians(cameraFOV / 2.0)));
 }
 
 float distToInt(float number) {
@@ -268,258 +410,251 @@ vec3 vecPow(vec3 v, int x) {
     );
 }
 
-float sigmoid(float x, float k) {
-    return 1.0 / (1 + exp(-k*x));
+vec4 objectDistColor(int type, int index, vec3 pos) {
+    if (type == 0) {
+        Sphere sphere = getSphere(index);
+        vec3 p = untransform(sphere.transform, sphere.pos, pos);
+        return vec4(sphereDist(sphere, p) * distScale(sphere.transform), sphere.color);
+    } else if (type == 1) {
+        Cube box = getCube(index);
+        vec3 p = untransform(box.transform, box.pos, pos);
+        return vec4(cubeDist(box, p) * distScale(box.transform), box.color);
+    } else if (type == 2) {
+        Triangle t = getTriangle(index);
+        return vec4(triangleDist(t, pos), t.color);
+    } else if (type == 3) {
+        MengerSponge ms = getMengerSponge(index);
+        vec3 p = untransform(ms.transform, ms.pos, pos);
+        return vec4(sdMengerSponge(p - ms.pos, int(ms.iterations)) * distScale(ms.transform), ms.color);
+    } else if (type == 4) {
+        Mesh mesh = getMesh(index);
+        vec3 p = untransform(mesh.transform, mesh.pos, pos);
+        return vec4(meshDist(mesh, p) * distScale(mesh.transform), mesh.color);
+    } else if (type == 5) {
+        Torus torus = getTorus(index);
+        vec3 p = untransform(torus.transform, torus.pos, pos);
+        return vec4(torusDist(torus, p) * distScale(torus.transform), torus.color);
+    } else if (type == 6) {
+        Capsule capsule = getCapsule(index);
+        vec3 p = untransform(capsule.transform, capsule.pos, pos);
+        return vec4(capsuleDist(capsule, p) * distScale(capsule.transform), capsule.color);
+    } else if (type == 7) {
+        Cylinder cylinder = getCylinder(index);
+        vec3 p = untransform(cylinder.transform, cylinder.pos, pos);
+        return vec4(cylinderDist(cylinder, p) * distScale(cylinder.transform), cylinder.color);
+    } else if (type == 8) {
+        Cone cone = getCone(index);
+        vec3 p = untransform(cone.transform, cone.pos, pos);
+        return vec4(coneDist(cone, p) * distScale(cone.transform), cone.color);
+    }
+    return vec4(10000000.0, vec3(0.0));
+}
+
+// (operation, blend radius) of an object, the operations are
+// 0 : Union
+// 1 : Subtraction
+// 2 : Intersection
+// 3 : Smooth union
+// 4 : Smooth subtraction
+// 5 : Smooth intersection
+vec2 getCombine(int type, int index) {
+    if (type == 0) {
+        return spheres[index].combine.xy;
+    } else if (type == 1) {
+        return cubes[index].combine.xy;
+    } else if (type == 2) {
+        return triangles[index].combine.xy;
+    } else if (type == 3) {
+        return mengerSponges[index].combine.xy;
+    } else if (type == 4) {
+        return meshes[index].combine.xy;
+    } else if (type == 5) {
+        return tori[index].combine.xy;
+    } else if (type == 6) {
+        return capsules[index].combine.xy;
+    } else if (type == 7) {
+        return cylinders[index].combine.xy;
+    } else if (type == 8) {
+        return cones[index].combine.xy;
+    }
+    return vec2(0.0);
+}
+
+Material libraryMaterial(int index) {
+    return Material(materials[index].optics, materials[index].surface);
+}
+
+Material getMaterial(int type, int index) {
+    if (type == 0) {
+        return libraryMaterial(spheres[index].material.x);
+    } else if (type == 1) {
+        return libraryMaterial(cubes[index].material.x);
+    } else if (type == 2) {
+        return libraryMaterial(triangles[index].material.x);
+    } else if (type == 3) {
+        return libraryMaterial(mengerSponges[index].material.x);
+    } else if (type == 4) {
+        return libraryMaterial(meshes[index].material.x);
+    } else if (type == 5) {
+        return libraryMaterial(tori[index].material.x);
+    } else if (type == 6) {
+        return libraryMaterial(capsules[index].material.x);
+    } else if (type == 7) {
+        return libraryMaterial(cylinders[index].material.x);
+    } else if (type == 8) {
+        return libraryMaterial(cones[index].material.x);
+    }
+    return DEFAULT_MATERIAL;
+}
+
+ObjectTexture getObjectTexture(int type, int index) {
+    if (type == 0) {
+        return ObjectTexture(spheres[index].texture, spheres[index].pos.xyz, getTransform(spheres[index].rotation, spheres[index].scale));
+    } else if (type == 1) {
+        return ObjectTexture(cubes[index].texture, cubes[index].pos.xyz, getTransform(cubes[index].rotation, cubes[index].scale));
+    } else if (type == 2) {
+        // triangles are textured in world space
+        return ObjectTexture(triangles[index].texture, vec3(0.0), IDENTITY_TRANSFORM);
+    } else if (type == 3) {
+        return ObjectTexture(mengerSponges[index].texture, mengerSponges[index].pos.xyz, getTransform(mengerSponges[index].rotation, mengerSponges[index].scale));
+    } else if (type == 4) {
+        return ObjectTexture(meshes[index].texture, meshes[index].pos.xyz, getTransform(meshes[index].rotation, meshes[index].scale));
+    } else if (type == 5) {
+        return ObjectTexture(tori[index].texture, tori[index].pos.xyz, getTransform(tori[index].rotation, tori[index].scale));
+    } else if (type == 6) {
+        return ObjectTexture(capsules[index].texture, capsules[index].pos.xyz, getTransform(capsules[index].rotation, capsules[index].scale));
+    } else if (type == 7) {
+        return ObjectTexture(cylinders[index].texture, cylinders[index].pos.xyz, getTransform(cylinders[index].rotation, cylinders[index].scale));
+    } else if (type == 8) {
+        return ObjectTexture(cones[index].texture, cones[index].pos.xyz, getTransform(cones[index].rotation, cones[index].scale));
+    }
+    return ObjectTexture(vec4(0.0), vec3(0.0), IDENTITY_TRANSFORM);
+}
+
+int getNumOf(int type) {
+    if (type == 0) {
+        return numOfSpheres;
+    } else if (type == 1) {
+        return numOfBoxes;
+    } else if (type == 2) {
+        return numOfTriangles;
+    } else if (type == 3) {
+        return numOfMengerSponges;
+    } else if (type == 4) {
+        return numOfMeshes;
+    } else if (type == 5) {
+        return numOfTori;
+    } else if (type == 6) {
+        return numOfCapsules;
+    } else if (type == 7) {
+        return numOfCylinders;
+    } else if (type == 8) {
+        return numOfCones;
+    }
+    return 0;
 }
 
-vec3 floorColor(float x, float z) {
-    vec3 clr;
-
-    if (int(floor(x) + floor(z)) % 2 == 0) {
-        clr = vec3(0.8);
-    } else {
-        clr = vec3(0.6);
+// adds one object to the distance, type uses the same numbers as selectedType.
+// norm is the normal of the surface for the textures, zero while marching
+void addObject(int type, int index, vec3 pos, vec3 norm, inout float dst, inout vec3 clr, inout Material mat) {
+    vec4 dstClr = objectDistColor(type, index, pos);
+    vec3 color = dstClr.yzw;
+    // not reading the texture of every object at every step of the march
+    if (norm != vec3(0.0)) {
+        color = applyTexture(color, getObjectTexture(type, index), pos, norm);
     }
-
-    clr = mix(clr, BG_CLR, sigmoid(dist3(vec3(0.0, 0.0, 0.0), vec3(x, 0, z)), 0.05));
-
-    return clr;
+    combine(getCombine(type, index), dstClr.x, color, getMaterial(type, index), dst, clr, mat);
 }
 
-
-vec2 calculateColorBlending(float dist2, float dist1){
-
-    float coeff = dist1 / dist2;
-
-    float c1 = 2/(coeff + 1);
-    float c2 = -c1 + 2;
-
-    return vec2(c1, c2);
+// every object from the position start on, in list order
+void addObjectsFrom(int start, vec3 pos, vec3 norm, inout float dst, inout vec3 clr, inout Material mat) {
+    for (int i = start; i < numOfObjects; i++) {
+        ivec4 id = objectOrder[i].id;
+        // the objects of a kind that did not fit on the gpu are left out
+        if (id.y < getNumOf(id.x)) {
+            addObject(id.x, id.y, pos, norm, dst, clr, mat);
+        }
+    }
 }
 
-vec4 Blend(float a, float b, vec3 colA, vec3 colB, float k )
-{
-    float h = clamp( 0.5+0.5*(b-a)/k, 0.0, 1.0 );
-    float blendDst = (a-b) * h + b - k*h*(1.0-h);
-    vec3 blendCol = (colA-colB) * h + colB;
-    return vec4(blendCol, blendDst);
+float aabbDist(BvhNode node, vec3 pos) {
+    return length(max(max(node.bounds_min.xyz - pos, pos - node.bounds_max.xyz), 0.0));
 }
 
+// goes through the bvh with the closest child first, and skips the nodes that are further away than the current distance.
+// the boxes of smooth unions are grown by their blend radius, so a skipped object can not change the distance or the color
+void bvhMinDist(vec3 pos, vec3 norm, inout float dst, inout vec3 clr, inout Material mat) {
+    int stack[BVH_STACK_SIZE];
+    int top = 0;
+    stack[top++] = 0;
 
-float cross(vec3 pos, float side_length, vec3 offset){
-
-    float inf = 100000;
-    float dist_a = cubeDist(Cube(offset, vec3(inf, side_length, side_length), vec3(1.0)), pos);
-    float dist_b = cubeDist(Cube(offset, vec3(side_length, inf, side_length), vec3(1.0)), pos);
-    float dist_c = cubeDist(Cube(offset, vec3(side_length, side_length, inf), vec3(1.0)), pos);
+    while (top > 0) {
+        BvhNode node = bvhNodes[stack[--top]];
+        if (aabbDist(node, pos) >= dst) {
+            continue;
+        }
 
-    return min(dist_a, min(dist_b, dist_c));
+        if (node.data.x >= 0) {
+            addObject(node.data.x, node.data.y, pos, norm, dst, clr, mat);
+        } else if (aabbDist(bvhNodes[node.data.z], pos) < aabbDist(bvhNodes[node.data.w], pos)) {
+            // the child that is visited first is pushed last
+            stack[top++] = node.data.w;
+            stack[top++] = node.data.z;
+        } else {
+            stack[top++] = node.data.z;
+            stack[top++] = node.data.w;
+        }
+    }
 }
 
+// sceneDist begin
+// the objects from the side panel, small scenes get a version with every object unrolled in its place, see shader_gen.rs
+vec4 sceneDist(vec3 pos, vec3 norm, out Material material) {
+    float dst = 10000000.0;
+    vec3 clr = vec3(0.0);
+    material = NO_MATERIAL;
 
+    // the objects that are not in the bvh are applied in list order afterwards
+    if (useBvh && numOfBvhNodes > 0) {
+        bvhMinDist(pos, norm, dst, clr, material);
+        addObjectsFrom(orderedStart, pos, norm, dst, clr, material);
+    } else {
+        addObjectsFrom(0, pos, norm, dst, clr, material);
+    }
 
-
-float mengel_fractal_iteration_1(vec3 pos){
-    float distBoundingBox = cubeDist(Cube(vec3(-0.0, -0.0, -0.0), vec3(1.0), vec3(1.0)), pos);
-
-    float cross_dist = cross(pos, 1.0/3.0, vec3(0.0));
-
-    float subtraction = max(distBoundingBox, -cross_dist);
-    return subtraction;
+    return vec4(dst, clr);
 }
+// sceneDist end
 
-
-float mengerSponge2(vec3 pos){
-
-    pos = pos - vec3(0.0, 1.0, 0.0);
-    float distBoundingBox = cubeDist(Cube(vec3(-0.0, -0.0, -0.0), vec3(1.0), vec3(1.0)), pos / 1) * 1;// spans [-1, 1]
-
-    float cubeWidth = 2.0;
-    float boxedWorldDimen = cubeWidth / 3.0;
-
-    float translation = -boxedWorldDimen / 2.0;
-    vec3 ray = pos - translation;
-
-    vec3 repeatedPos = mod(ray, boxedWorldDimen);
-    repeatedPos += translation;
-    repeatedPos *= 3.0;
-    //float dist = cubeDist(Cube(vec3(0.0, 0.0, 0.0), vec3(1.0), vec3(1.0)), repeatedPos / 0.9) * 0.9;
-    float dist = cross(repeatedPos*3.0, 1.0, vec3(0.0)) / 3.0;
-    dist /= 3.0;
-    float sub = max(mengel_fractal_iteration_1(pos), -dist);
-    return sub;
+// the material and the textures are only needed where a ray hits, everywhere else they are left out
+vec4 sceneDist(vec3 pos) {
+    Material material;
+    return sceneDist(pos, vec3(0.0), material);
 }
 
-//https://iquilezles.org/articles/menger/
-float sdMengerSponge(vec3 rayPos, int numIterations) {
-  const float cubeWidth = 2.0;
-  const float oneThird = 1.0 / 3.0;
-  float spongeCube = cubeDist(Cube(vec3(-0.0, -0.0, -0.0), vec3(1.0), vec3(1.0)), rayPos / 1) * 1;
-  float mengerSpongeDist = spongeCube;
-  
-  float scale = 1.0;
-  for(int i = 0; i < numIterations; ++i) {
-    float boxedWidth = cubeWidth / scale;
-    
-    float translation = -boxedWidth / 2.0;
-    vec3 ray = rayPos - translation;
-    vec3 repeatedPos = mod(ray, boxedWidth);
-    repeatedPos += translation;
-    
-
-    repeatedPos *= scale;
-    
-    float crossesDist = cross(repeatedPos / oneThird, 1.0, vec3(0.0)) * oneThird;
-    
-    crossesDist /= scale;
-    
-    mengerSpongeDist = max(mengerSpongeDist, -crossesDist);
-    
-    scale *= 3.0;
-  }
-  return mengerSpongeDist;
+// objectsDist begin
+// the node graph editor replaces everything up to objectsDist end with the compiled graph
+vec4 objectsDist(vec3 pos) {
+    return sceneDist(pos);
 }
 
+vec4 objectsDist(vec3 pos, vec3 norm, out Material material) {
+    return sceneDist(pos, norm, material);
+}
+// objectsDist end
 
+#include "ground.glsl"
 
-vec4 minDist(vec3 pos) {
-    vec3 clr;
-    float dst;
-
-    if (renderMode == 0) {
-        dst = 10000000.0;
-
-        for (int i = 0; i < numOfSpheres; i++) {
-            Sphere sphere = getSphere(i);
-            float new_dst = sphereDist(sphere, pos);
-
-            if (new_dst < dst) {
-                dst = new_dst;
-                clr = sphere.color;
-            }
-        }
-
-        for (int i = 0; i < numOfBoxes; i++) {
-            Cube box = getCube(i);
-
-            float new_dst = cubeDist(box, pos);
-
-            if (new_dst < dst) {
-                dst = new_dst;
-                clr = box.color;
-            }
-        }
-    } else if (renderMode == 1) {
-
-        // some tests for boolean operators
-        dst = 0.0;
-
-        Sphere sphere = getSphere(0);
-        Cube box = getCube(0);
-        {
-            dst = max(-sphereDist(sphere, pos), cubeDist(box, pos));
-            dst = max(dst, sphereDist(getSphere(1), pos));
-
-        }
-        clr = box.color;
-    } else if (renderMode == 2) {
-        dst = 10000000.0;
-        
-        float previous_shortest_non_smooth_dist = 10000000.0;
-        vec3 color_previous_shortest_object = vec3(1.0);
-
-        for (int i = 0; i < numOfBoxes; i++) {
-            Cube box = getCube(i);
-
-            float new_dst = cubeDist(box, pos);
-            float s_dst = smoothMin(dst, new_dst, smoothness);
-
-            // for color blending
-            vec2 color_coeffs = calculateColorBlending(previous_shortest_non_smooth_dist, new_dst);
-            clr = Blend(previous_shortest_non_smooth_dist, new_dst, clr, box.color, 0.5).xyz;
-            previous_shortest_non_smooth_dist = Blend(previous_shortest_non_smooth_dist, new_dst, color_previous_shortest_object, box.color, 0.5).w;
-
-            if (s_dst < dst) {
-                dst = s_dst;
-            }
-            dst = s_dst;
-        }  
-
-        
-        for (int i = 0; i < numOfSpheres; i++) {
-            Sphere sphere = getSphere(i);
-
-            float new_dst = sphereDist(sphere, pos);
-            float s_dst = smoothMin(dst, new_dst, smoothness);
-
-            // for color blending
-            vec2 color_coeffs = calculateColorBlending(previous_shortest_non_smooth_dist, new_dst);
-            clr = Blend(previous_shortest_non_smooth_dist, new_dst, clr, sphere.color, 0.5).xyz;
-            previous_shortest_non_smooth_dist = Blend(previous_shortest_non_smooth_dist, new_dst, color_previous_shortest_object, sphere.color, 0.5).w;
-
-            dst = s_dst;
-            color_previous_shortest_object = sphere.color;
-
-            color_previous_shortest_object = sphere.color;
-        }    
-    } else if (renderMode == 3) {
-        dst = 10000000.0;
-        
-        float previous_shortest_non_smooth_dist = 10000000.0;
-        vec3 color_previous_shortest_object = vec3(1.0);
-
-        for (int i = 0; i < numOfBoxes; i++) {
-            Cube box = getCube(i);
-
-            float new_dst = cubeDist(box, pos);
-            float s_dst = smoothMin(dst, new_dst, smoothness);
-
-            // for color blending
-            vec2 color_coeffs = calculateColorBlending(previous_shortest_non_smooth_dist, new_dst);
-            clr = Blend(previous_shortest_non_smooth_dist, new_dst, clr, box.color, 0.5).xyz;
-            previous_shortest_non_smooth_dist = Blend(previous_shortest_non_smooth_dist, new_dst, color_previous_shortest_object, box.color, 0.5).w;
-
-            if (s_dst < dst) {
-                dst = s_dst;
-            }
-            dst = s_dst;
-        }  
-
-        
-        for (int i = 0; i < numOfSpheres; i++) {
-            Sphere sphere = getSphere(i);
-
-            float new_dst = sphereDist(sphere, pos);
-            float s_dst = smoothMin(dst, new_dst, smoothness);
-
-            // for color blending
-            vec2 color_coeffs = calculateColorBlending(previous_shortest_non_smooth_dist, new_dst);
-            clr = Blend(previous_shortest_non_smooth_dist, new_dst, clr, sphere.color, 0.5).xyz;
-            previous_shortest_non_smooth_dist = Blend(previous_shortest_non_smooth_dist, new_dst, color_previous_shortest_object, sphere.color, 0.5).w;
-
-            dst = s_dst;
-            color_previous_shortest_object = sphere.color;
-
-            color_previous_shortest_object = sphere.color;
-        }
-
-        for(int i = 0; i < numOfMengerSponges; i++){
-            MengerSponge ms = getMengerSponge(i);
-
-            float new_dist = sdMengerSponge(pos - ms.pos, int(ms.iterations));
-
-            float smooth_dist = smoothMin(new_dist, dst, smoothness);
+// distance to a single object, type uses the same numbers as selectedType
+float objectDist(int type, int index, vec3 pos) {
+    return objectDistColor(type, index, pos).x;
+}
 
-            if(smooth_dist < dst){
-                dst = smooth_dist;
-                vec2 color_coeffs = calculateColorBlending(previous_shortest_non_smooth_dist, new_dist);
-                clr = Blend(previous_shortest_non_smooth_dist, new_dist, clr, ms.color, 0.5).xyz; //* (cnoise(pos*10) + 0.5);
-                previous_shortest_non_smooth_dist = Blend(previous_shortest_non_smooth_dist, new_dist, color_previous_shortest_object, ms.color, 0.5).w;
-            }
-        }
+vec3 highlightSelected(vec3 clr, vec3 pos) {
+    if (selectedType >= 0 && abs(objectDist(selectedType, selectedIndex, pos)) < SELECTION_DIST) {
+        return mix(clr, SELECTION_CLR, 0.5);
     }
-
-    return vec4(dst, clr);
+    return clr;
 }
 
 vec3 approxNorm(vec3 pos, float dst) {
@@ -530,63 +665,117 @@ vec3 approxNorm(vec3 pos, float dst) {
     return normalize(vec3(dx, dy, dz));
 }
 
-vec3 shade(vec3 clr, vec3 norm, vec3 pos) {
-    vec3 lightDir = normalize(getLightVec(pos));
 
-    vec3 c1 = clr * (1.0 + dot(lightDir, norm)) / 2.0;
-    vec3 c2 = clr *  max(dot(lightDir, norm), 0.0);
+#include "lighting.glsl"
 
-    return mix(c1, c2, 0.0);
-}
+// marches the ray until it is closer than MIN_DIST to a surface, the ray is left at the surface.
+// inside is for rays going through an object, where the distance is negative. false when the ray does not hit anything
+bool marchToSurface(inout Ray ray, int depth, bool inside, out float dst) {
+    for (; depth > 0; depth--) {
+        dst = minDist(ray.pos).x;
 
+        float stepDist = inside ? -dst : dst;
 
-vec3 _march(Ray ray, int depth) {
-    float dst = 1000000.0;
-    vec3 clr = vec3(0);
-    vec3 p;
+        if (stepDist <= MIN_DIST) {
+            return true;
+        }
 
-    while (dst > MIN_DIST) {
-        p = ray.pos; 
+        ray = Ray(ray.pos + ray.dir * stepDist, ray.dir);
+    }
+    return false;
+}
 
-        vec4 drgb = minDist(p);
+// a ray that is waiting to be traced, weight is how much of the pixel color it gives
+struct BounceRay {
+    Ray ray;
+    vec3 weight;
+    int bounces;
+    bool inside;
+};
 
-        dst = drgb.x;
-        clr = drgb.yzw;
+// follows the ray and the rays that are reflected and refracted by the surfaces it hits, at most maxBounces times.
+// the light that would go to a ray that is not traced shows the color of the surface instead
+vec3 _march(Ray ray, int depth) {
+    vec3 result = vec3(0.0);
+    int bounceLimit = min(maxBounces, MAX_BOUNCES);
+
+    // every hit can add a reflected and a refracted ray, but only the ones that bounce again make the stack grow
+    BounceRay stack[MAX_BOUNCES + 1];
+    int top = 0;
+    stack[top++] = BounceRay(ray, vec3(1.0), 0, false);
+
+    while (top > 0) {
+        BounceRay current = stack[--top];
+        vec3 start = current.ray.pos;
+
+        float dst;
+        if (!marchToSurface(current.ray, depth, current.inside, dst)) {
+            result += current.weight * skyColor(current.ray.dir);
+            continue;
+        }
 
-        if (ray.pos.y < 0) {
-            vec3 intersect = intersectXZPlane(ray);
+        vec3 p = current.ray.pos;
+        vec3 dir = current.ray.dir;
+        // approxNorm points into the surface, facing is the normal on the side the ray came from
+        vec3 norm = approxNorm(p, dst);
+        vec3 facing = current.inside ? norm : -norm;
+
+        // a ray going through an object leaves it here, or is reflected back in when it can not get out
+        if (current.inside) {
+            // only the index of refraction is needed, so the textures are left out
+            vec3 exitClr;
+            vec3 refracted = refract(dir, facing, surfaceAt(p, vec3(0.0), exitClr).optics.z);
+            if (refracted != vec3(0.0)) {
+                stack[top++] = BounceRay(Ray(p - facing * BOUNCE_BIAS, refracted), current.weight, current.bounces, false);
+            } else if (current.bounces < bounceLimit) {
+                stack[top++] = BounceRay(Ray(p + facing * BOUNCE_BIAS, reflect(dir, facing)), current.weight, current.bounces + 1, true);
+            }
+            continue;
+        }
 
-            float density = 5.0;
+        float ao = ambientOcclusion(p, norm);
+        if (aoDebug) {
+            result += current.weight * ao;
+            continue;
+        }
 
-            return floorColor(density * intersect.x, density * intersect.z);
-        } 
-        
-        if (depth <= 0) {
-            if (ray.dir.y < 0) {
-                vec3 intersect = intersectXZPlane(ray);
+        // far away the ground fades into the sky, the rays it reflects and refracts fade with it
+        float fade = groundFade(p, length(p - start));
+        if (fade > 0.0) {
+            result += current.weight * fade * skyColor(dir);
+            current.weight *= 1.0 - fade;
+            if (fade >= 1.0) {
+                continue;
+            }
+        }
 
-                float density = 5.0;
+        vec3 clr;
+        Material material = surfaceAt(p, norm, clr);
+        clr = highlightSelected(clr, p);
+        float reflected = material.optics.x;
+        float transmitted = (1.0 - material.optics.x) * material.optics.y;
+        float surface = 1.0 - reflected - transmitted;
+
+        if (current.bounces < bounceLimit) {
+            vec3 reflectedWeight = current.weight * reflected;
+            if (max(reflectedWeight.r, max(reflectedWeight.g, reflectedWeight.b)) > MIN_BOUNCE_WEIGHT) {
+                stack[top++] = BounceRay(Ray(p + facing * BOUNCE_BIAS, reflect(dir, facing)), reflectedWeight, current.bounces + 1, false);
+            }
 
-                return floorColor(density * intersect.x, density * intersect.z);
-            } 
-            return BG_CLR;
+            // colored objects tint the light that goes through them
+            vec3 transmittedWeight = current.weight * transmitted * clr;
+            vec3 refracted = refract(dir, facing, 1.0 / material.optics.z);
+            if (max(transmittedWeight.r, max(transmittedWeight.g, transmittedWeight.b)) > MIN_BOUNCE_WEIGHT && refracted != vec3(0.0)) {
+                stack[top++] = BounceRay(Ray(p - facing * BOUNCE_BIAS, refracted), transmittedWeight, current.bounces + 1, true);
+            }
+        } else {
+            surface = 1.0;
         }
 
-        ray = Ray(ray.pos + ray.dir * dst, ray.dir);
-        depth -= 1;
+        result += current.weight * surface * shade(clr, material, norm, p, dir) * ao;
     }
 
-    return shade(clr, approxNorm(p, dst), p);
-}
-
-// Quaternion Multiplication
-vec4 qMul(vec4 r, vec4 s) {
-    float x = r.x * s.x - r.y * s.y - r.z * s.z - r.w * s.w;
-    float y = r.x * s.y + r.y * s.x - r.z * s.w + r.w * s.z;
-    float z = r.x * s.z + r.y * s.w + r.z * s.x - r.w * s.y;
-    float w = r.x * s.w - r.y * s.z + r.z * s.y + r.w * s.x;
-
-    return vec4(x, y, z, w);
+    return result;
 }
 
 // Descirbed in https://danceswithcode.net/engineeringnotes/quaternions/quaternions.html
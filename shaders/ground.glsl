@@ -0,0 +1,87 @@
+// the ground plane under the objects, see src/ground.rs which does the same on the cpu
+// included after objectsDist, it is added to the objects with a union so it is lit, shadowed and reflected like an object
+
+uniform bool groundEnabled;
+// points up out of the ground and is normalized, the plane goes through groundNormal * groundHeight
+uniform vec3 groundNormal;
+uniform float groundHeight;
+// the patterns go from groundSecondColor to groundColor, images are multiplied with groundColor
+uniform vec3 groundColor;
+uniform vec3 groundSecondColor;
+// the index of the material in the library
+uniform int groundMaterial;
+// (pattern, image), the same numbers as in ObjectTexture
+uniform ivec2 groundPattern;
+// the width of a tile of the checker, the other patterns repeat over the same distance
+uniform float groundTileSize;
+// how far from where a ray starts the ground has faded into the sky, 0 does not fade
+uniform float groundFadeDistance;
+
+// negative below the ground
+float groundDist(vec3 pos) {
+    return dot(pos, groundNormal) - groundHeight;
+}
+
+// the position along the ground in tiles, x and z for flat ground.
+// the cross of the menger sponge in sdf.glsl hides the builtin one, so the cross products are written out
+vec2 groundUv(vec3 pos) {
+    vec3 n = groundNormal;
+    // n cross z, or n cross x when n is close to z
+    vec3 u = normalize(abs(n.z) < 0.999 ? vec3(n.y, -n.x, 0.0) : vec3(0.0, n.z, -n.y));
+    // u cross n
+    vec3 v = u.yzx * n.zxy - u.zxy * n.yzx;
+    return vec2(dot(pos, u), dot(pos, v)) / groundTileSize;
+}
+
+// the color with the pattern at pos
+vec3 groundColorAt(vec3 pos) {
+    vec2 uv = groundUv(pos);
+    if (groundPattern.x == 5) {
+        return groundColor * imageColor(groundPattern.y, uv);
+    }
+    return mix(groundSecondColor, groundColor, patternValue(groundPattern.x, uv));
+}
+
+// a union with everything before it, like an object after the last one
+void addGround(vec3 pos, vec3 norm, inout float dst, inout vec3 clr, inout Material mat) {
+    if (!groundEnabled) {
+        return;
+    }
+
+    // the pattern is only needed where a ray hits, like the textures
+    vec3 color = norm == vec3(0.0) ? groundColor : groundColorAt(pos);
+    combine(vec2(0.0), groundDist(pos), color, libraryMaterial(groundMaterial), dst, clr, mat);
+}
+
+// the objects and the ground, everything is marched, shadowed and shaded with these two
+vec4 minDist(vec3 pos) {
+    Material material;
+    vec4 dstClr = objectsDist(pos, vec3(0.0), material);
+    float dst = dstClr.x;
+    vec3 clr = dstClr.yzw;
+    addGround(pos, vec3(0.0), dst, clr, material);
+    return vec4(dst, clr);
+}
+
+// the material and the color with the textures of the surface at pos, norm is the normal there
+Material surfaceAt(vec3 pos, vec3 norm, out vec3 clr) {
+    Material material;
+    vec4 dstClr = objectsDist(pos, norm, material);
+    float dst = dstClr.x;
+    clr = dstClr.yzw;
+    addGround(pos, norm, dst, clr, material);
+    return material;
+}
+
+// whether the surface at pos is the ground and not an object
+bool onGround(vec3 pos) {
+    return groundEnabled && groundDist(pos) < objectsDist(pos).x;
+}
+
+// how much of the sky is seen instead of the surface at pos, dist is how far the ray went to get there. 0 for objects
+float groundFade(vec3 pos, float dist) {
+    if (groundFadeDistance <= 0.0 || !onGround(pos)) {
+        return 0.0;
+    }
+    return smoothstep(0.0, groundFadeDistance, dist);
+}
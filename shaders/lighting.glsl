@@ -0,0 +1,175 @@
+// the lights of the scene and their shadows, see src/light.rs
+// included after minDist, which the shadow rays are marched with
+
+// kind is the same as LightKind: 0 directional, 1 point, 2 spot
+// data is (intensity, range, cosine of the spot angle, cosine of the angle where the spot has its full intensity)
+struct GpuLight {
+    vec4 pos;
+    vec4 dir;
+    vec4 color;
+    vec4 data;
+    ivec4 kind;
+};
+
+layout(std430) buffer light_array {
+    GpuLight lights[];
+};
+
+uniform int numOfLights;
+
+uniform bool useShadows;
+// how wide the penumbra is, 0 gives hard shadows
+uniform float shadowSoftness;
+
+const int MAX_SHADOW_STEPS = 64;
+// shadow rays start this far from the surface, so that they do not hit the surface they start on
+const float SHADOW_BIAS = 0.01;
+// how far shadow rays towards directional lights go
+const float MAX_SHADOW_DIST = 50.0;
+
+// 0 when ambient occlusion is turned off
+uniform int aoSteps;
+uniform float aoStrength;
+// shows only the ambient occlusion of the surfaces
+uniform bool aoDebug;
+
+// how far along the normal ambient occlusion looks for other surfaces
+const float AO_DIST = 0.3;
+
+// a perfectly smooth surface would have its highlight in a single point
+const float MIN_ROUGHNESS = 0.02;
+// how much light non metals reflect straight back with a specular of 1, the default 0.5 gives the usual 4%
+const float MAX_F0 = 0.08;
+
+// how much of its color a light gives at pos, before the angle to the surface is taken into account
+vec3 lightColor(GpuLight light, vec3 pos) {
+    int kind = light.kind.x;
+    float intensity = light.data.x;
+    float range = light.data.y;
+
+    if (kind == 0) {
+        return light.color.xyz * intensity;
+    }
+
+    if (range > 0.0) {
+        float d = length(pos - light.pos.xyz) / range;
+        intensity /= 1.0 + d * d;
+    }
+
+    if (kind == 2) {
+        float cosAngle = dot(normalize(pos - light.pos.xyz), light.dir.xyz);
+        intensity *= smoothstep(light.data.z, light.data.w, cosAngle);
+    }
+
+    return light.color.xyz * intensity;
+}
+
+// the direction from the light to pos
+vec3 lightDir(GpuLight light, vec3 pos) {
+    if (light.kind.x == 0) {
+        return light.dir.xyz;
+    }
+    return normalize(pos - light.pos.xyz);
+}
+
+// how much of the light gets from pos to maxDist along dir, 0 when something is in the way, soft at the edges of the shadow
+// from https://iquilezles.org/articles/rmshadows/
+float softShadow(vec3 pos, vec3 dir, float maxDist) {
+    float k = 1.0 / max(shadowSoftness, 0.001);
+    float res = 1.0;
+    float t = SHADOW_BIAS;
+
+    for (int i = 0; i < MAX_SHADOW_STEPS && t < maxDist; i++) {
+        float h = minDist(pos + dir * t).x;
+        if (h < MIN_DIST) {
+            return 0.0;
+        }
+        res = min(res, k * h / t);
+        t += h;
+    }
+    return clamp(res, 0.0, 1.0);
+}
+
+float lightVisibility(GpuLight light, vec3 pos) {
+    if (!useShadows) {
+        return 1.0;
+    }
+    if (light.kind.x == 0) {
+        return softShadow(pos, -light.dir.xyz, MAX_SHADOW_DIST);
+    }
+    return softShadow(pos, normalize(light.pos.xyz - pos), length(light.pos.xyz - pos));
+}
+
+// the highlight of the cook-torrance brdf with the ggx distribution, from https://learnopengl.com/PBR/Theory
+// n is the normal out of the surface, v points towards the viewer and l towards the light
+vec3 specularBrdf(vec3 n, vec3 v, vec3 l, float roughness, vec3 f0) {
+    vec3 h = normalize(v + l);
+    float nh = max(dot(n, h), 0.0);
+    float nv = max(dot(n, v), 0.0001);
+    float nl = max(dot(n, l), 0.0001);
+
+    // the distribution uses the square of the roughness, which looks more even when the roughness is changed
+    float alpha = max(roughness, MIN_ROUGHNESS) * max(roughness, MIN_ROUGHNESS);
+    float a2 = alpha * alpha;
+    float denom = nh * nh * (a2 - 1.0) + 1.0;
+    float distribution = a2 / (PI * denom * denom);
+
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    float geometry = nv / (nv * (1.0 - k) + k) * nl / (nl * (1.0 - k) + k);
+
+    vec3 fresnel = f0 + (1.0 - f0) * pow(1.0 - max(dot(h, v), 0.0), 5.0);
+
+    return distribution * geometry * fresnel / (4.0 * nv * nl);
+}
+
+// norm points into the surface, see approxNorm, and dir is the direction of the ray that hit it
+// a shadow only takes away the light on the side facing the light, so the side facing away looks the same with and without shadows
+vec3 shade(vec3 clr, Material material, vec3 norm, vec3 pos, vec3 dir) {
+    float roughness = material.surface.x;
+    float metallic = material.surface.y;
+    float specular = material.surface.z;
+    float emissive = material.surface.w;
+
+    // metals have colored highlights and no diffuse light
+    vec3 f0 = mix(vec3(MAX_F0 * specular), clr, metallic);
+    vec3 diffuse = clr * (1.0 - metallic);
+
+    // the sky lights the surface from every direction, norm points into it
+    vec3 result = clr * emissive + diffuse * skyLight(-norm) * skyAmbient;
+    vec3 start = pos - norm * SHADOW_BIAS;
+
+    for (int i = 0; i < numOfLights; i++) {
+        vec3 lightClr = lightColor(lights[i], pos);
+        if (lightClr == vec3(0.0)) {
+            continue;
+        }
+
+        vec3 l = lightDir(lights[i], pos);
+        float d = dot(l, norm);
+        float visibility = d > 0.0 ? lightVisibility(lights[i], start) : 1.0;
+
+        result += diffuse * (1.0 + min(d, 0.0) + max(d, 0.0) * visibility) / 2.0 * lightClr;
+        if (d > 0.0) {
+            result += specularBrdf(-norm, -dir, -l, roughness, f0) * d * visibility * lightClr;
+        }
+    }
+
+    return result;
+}
+
+// 1 in the open and less where other surfaces are close, norm points into the surface
+// at every step the distance to the scene is compared with the distance to pos, in the open they are the same
+float ambientOcclusion(vec3 pos, vec3 norm) {
+    if (aoSteps == 0) {
+        return 1.0;
+    }
+
+    float occlusion = 0.0;
+    for (int i = 0; i < aoSteps; i++) {
+        float h = AO_DIST * float(i + 1) / float(aoSteps);
+        float d = minDist(pos - norm * h).x;
+        occlusion += clamp((h - d) / h, 0.0, 1.0);
+    }
+
+    return clamp(1.0 - aoStrength * occlusion / float(aoSteps), 0.0, 1.0);
+}
@@ -0,0 +1,369 @@
+// the shapes, their distance functions and how objects are combined, shared by the generic sceneDist, the generated one and the node graph
+// src/sdf.rs has the same functions for the cpu
+
+// the same as MIN_BLEND_RADIUS in src/sdf.rs, Blend divides by the radius
+const float MIN_BLEND_RADIUS = 0.0001;
+
+const float PI = 3.14159265;
+
+// see src/material.rs, optics is (reflectivity, transparency, index of refraction) and surface is (roughness, metallic, specular, emissive)
+struct Material {
+    vec4 optics;
+    vec4 surface;
+};
+
+// the same as Material::DEFAULT and Material::ZERO
+const Material DEFAULT_MATERIAL = Material(vec4(0.0, 0.0, 1.5, 0.0), vec4(0.5, 0.0, 0.5, 0.0));
+const Material NO_MATERIAL = Material(vec4(0.0), vec4(0.0));
+
+Material mixMaterial(Material a, Material b, float h) {
+    return Material(mix(a.optics, b.optics, h), mix(a.surface, b.surface, h));
+}
+
+// rotation and scale of an object around its pos, see Transform in src/shapes.rs
+struct Transform {
+    vec4 rotation;
+    vec3 scale;
+};
+
+const Transform IDENTITY_TRANSFORM = Transform(vec4(1.0, 0.0, 0.0, 0.0), vec3(1.0));
+
+struct Sphere {
+    vec3 pos;
+    vec3 color;
+    float radius;
+    Transform transform;
+};
+
+struct Triangle {
+    vec3 v1;
+    vec3 v2;
+    vec3 v3;
+    vec3 norm;
+    vec3 color;
+};
+
+struct Cube {
+    vec3 pos;
+    vec3 dim;
+    vec3 color;
+    Transform transform;
+};
+
+struct MengerSponge {
+    vec3 pos;
+    float iterations;
+    vec3 color;
+    Transform transform;
+};
+
+struct Torus {
+    vec3 pos;
+    float radius;
+    float thickness;
+    vec3 color;
+    Transform transform;
+};
+
+struct Capsule {
+    vec3 pos;
+    float halfHeight;
+    float radius;
+    vec3 color;
+    Transform transform;
+};
+
+struct Cylinder {
+    vec3 pos;
+    float halfHeight;
+    float radius;
+    vec3 color;
+    Transform transform;
+};
+
+struct Cone {
+    vec3 pos;
+    float halfHeight;
+    float bottomRadius;
+    float topRadius;
+    vec3 color;
+    Transform transform;
+};
+
+struct Mesh {
+    vec3 pos;
+    vec3 color;
+    int firstTriangle;
+    int numTriangles;
+    Transform transform;
+    // the box around the triangles, relative to pos
+    vec3 boundsMin;
+    vec3 boundsMax;
+};
+
+Triangle newTriangle(vec3 a, vec3 b, vec3 c, vec3 clr) {
+    vec3 ba = b - a;
+    vec3 ac = a - c;
+    vec3 nor = normalize(cross( ba, ac ));
+    
+    return Triangle(a, b, c, nor, clr);
+}
+
+float dist3(vec3 pos1, vec3 pos2) {
+    float dx = pos1.x - pos2.x;
+    float dy = pos1.y - pos2.y;
+    float dz = pos1.z - pos2.z;
+
+    return sqrt(dx*dx + dy*dy + dz*dz);
+}
+
+
+// Quaternion Multiplication
+vec4 qMul(vec4 r, vec4 s) {
+    float x = r.x * s.x - r.y * s.y - r.z * s.z - r.w * s.w;
+    float y = r.x * s.y + r.y * s.x - r.z * s.w + r.w * s.z;
+    float z = r.x * s.z + r.y * s.w + r.z * s.x - r.w * s.y;
+    float w = r.x * s.w - r.y * s.z + r.z * s.y + r.w * s.x;
+
+    return vec4(x, y, z, w);
+}
+
+// moves pos so that the distance function of the object without its transform can be used, center is the pos of the object
+vec3 untransform(Transform t, vec3 center, vec3 pos) {
+    vec4 p = vec4(0.0, pos - center);
+    vec4 q = t.rotation;
+    vec4 q_inv = vec4(q.x, -q.y, -q.z, -q.w);
+
+    vec3 local = qMul(qMul(q_inv, p), q).yzw;
+    return center + local / t.scale;
+}
+
+// distances at the untransformed point are multiplied with this, so that they are never too long with a non uniform scale
+float distScale(Transform t) {
+    vec3 s = abs(t.scale);
+    return min(s.x, min(s.y, s.z));
+}
+
+float sphereDist(Sphere sphere, vec3 pos) {
+    return dist3(sphere.pos, pos) - sphere.radius;
+}
+
+float dot2(vec3 v ) { return dot(v,v); }
+
+// from https://iquilezles.org/articles/distfunctions/
+float triangleDist(Triangle triangle, vec3 pos) {
+    vec3 a = triangle.v1;
+    vec3 b = triangle.v2;
+    vec3 c = triangle.v3;
+    vec3 p = pos;
+
+    vec3 ba = b - a; vec3 pa = p - a;
+    vec3 cb = c - b; vec3 pb = p - b;
+    vec3 ac = a - c; vec3 pc = p - c;
+    vec3 nor = triangle.norm;
+
+    return sqrt(
+    (sign(dot(cross(ba,nor),pa)) +
+        sign(dot(cross(cb,nor),pb)) +
+        sign(dot(cross(ac,nor),pc))<2.0)
+        ?
+        min( min(
+        dot2(ba*clamp(dot(ba,pa)/dot2(ba),0.0,1.0)-pa),
+        dot2(cb*clamp(dot(cb,pb)/dot2(cb),0.0,1.0)-pb) ),
+        dot2(ac*clamp(dot(ac,pc)/dot2(ac),0.0,1.0)-pc) )
+        :
+        dot(nor,pa)*dot(nor,pa)/dot2(nor) );
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+float cubeDist(Cube box, vec3 pos)
+{
+    vec3 p = pos - box.pos;
+    vec3 q = abs(p) - box.dim;
+    return length(max(q,0.0)) + min(max(q.x,max(q.y,q.z)),0.0);
+}
+
+// the torus, capsule, cylinder and cone are all around the y axis
+// from https://iquilezles.org/articles/distfunctions/
+float torusDist(Torus torus, vec3 pos) {
+    vec3 p = pos - torus.pos;
+    vec2 q = vec2(length(p.xz) - torus.radius, p.y);
+    return length(q) - torus.thickness;
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+float capsuleDist(Capsule capsule, vec3 pos) {
+    vec3 p = pos - capsule.pos;
+    p.y -= clamp(p.y, -capsule.halfHeight, capsule.halfHeight);
+    return length(p) - capsule.radius;
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+float cylinderDist(Cylinder cylinder, vec3 pos) {
+    vec3 p = pos - cylinder.pos;
+    vec2 d = abs(vec2(length(p.xz), p.y)) - vec2(cylinder.radius, cylinder.halfHeight);
+    return min(max(d.x, d.y), 0.0) + length(max(d, 0.0));
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+float coneDist(Cone cone, vec3 pos) {
+    vec3 p = pos - cone.pos;
+    float h = cone.halfHeight;
+    float r1 = cone.bottomRadius;
+    float r2 = cone.topRadius;
+
+    vec2 q = vec2(length(p.xz), p.y);
+    vec2 k1 = vec2(r2, h);
+    vec2 k2 = vec2(r2 - r1, 2.0 * h);
+    vec2 ca = vec2(q.x - min(q.x, (q.y < 0.0) ? r1 : r2), abs(q.y) - h);
+    vec2 cb = q - k1 + k2 * clamp(dot(k1 - q, k2) / dot(k2, k2), 0.0, 1.0);
+    float s = (cb.x < 0.0 && ca.y < 0.0) ? -1.0 : 1.0;
+    return s * sqrt(min(dot(ca, ca), dot(cb, cb)));
+}
+
+vec2 calculateColorBlending(float dist2, float dist1){
+
+    float coeff = dist1 / dist2;
+
+    float c1 = 2/(coeff + 1);
+    float c2 = -c1 + 2;
+
+    return vec2(c1, c2);
+}
+
+// how much of a is in the smooth minimum of a and b, the materials are blended with it like the colors
+float blendFactor(float a, float b, float k) {
+    return clamp(0.5 + 0.5 * (b - a) / k, 0.0, 1.0);
+}
+
+vec4 Blend(float a, float b, vec3 colA, vec3 colB, float k )
+{
+    float h = blendFactor(a, b, k);
+    float blendDst = (a-b) * h + b - k*h*(1.0-h);
+    vec3 blendCol = (colA-colB) * h + colB;
+    return vec4(blendCol, blendDst);
+}
+
+
+float cross(vec3 pos, float side_length, vec3 offset){
+
+    float inf = 100000;
+    float dist_a = cubeDist(Cube(offset, vec3(inf, side_length, side_length), vec3(1.0), IDENTITY_TRANSFORM), pos);
+    float dist_b = cubeDist(Cube(offset, vec3(side_length, inf, side_length), vec3(1.0), IDENTITY_TRANSFORM), pos);
+    float dist_c = cubeDist(Cube(offset, vec3(side_length, side_length, inf), vec3(1.0), IDENTITY_TRANSFORM), pos);
+
+    return min(dist_a, min(dist_b, dist_c));
+}
+
+
+
+
+float mengel_fractal_iteration_1(vec3 pos){
+    float distBoundingBox = cubeDist(Cube(vec3(-0.0, -0.0, -0.0), vec3(1.0), vec3(1.0), IDENTITY_TRANSFORM), pos);
+
+    float cross_dist = cross(pos, 1.0/3.0, vec3(0.0));
+
+    float subtraction = max(distBoundingBox, -cross_dist);
+    return subtraction;
+}
+
+
+float mengerSponge2(vec3 pos){
+
+    pos = pos - vec3(0.0, 1.0, 0.0);
+    float distBoundingBox = cubeDist(Cube(vec3(-0.0, -0.0, -0.0), vec3(1.0), vec3(1.0), IDENTITY_TRANSFORM), pos / 1) * 1;// spans [-1, 1]
+
+    float cubeWidth = 2.0;
+    float boxedWorldDimen = cubeWidth / 3.0;
+
+    float translation = -boxedWorldDimen / 2.0;
+    vec3 ray = pos - translation;
+
+    vec3 repeatedPos = mod(ray, boxedWorldDimen);
+    repeatedPos += translation;
+    repeatedPos *= 3.0;
+    //float dist = cubeDist(Cube(vec3(0.0, 0.0, 0.0), vec3(1.0), vec3(1.0)), repeatedPos / 0.9) * 0.9;
+    float dist = cross(repeatedPos*3.0, 1.0, vec3(0.0)) / 3.0;
+    dist /= 3.0;
+    float sub = max(mengel_fractal_iteration_1(pos), -dist);
+    return sub;
+}
+
+//https://iquilezles.org/articles/menger/
+float sdMengerSponge(vec3 rayPos, int numIterations) {
+  const float cubeWidth = 2.0;
+  const float oneThird = 1.0 / 3.0;
+  float spongeCube = cubeDist(Cube(vec3(-0.0, -0.0, -0.0), vec3(1.0), vec3(1.0), IDENTITY_TRANSFORM), rayPos / 1) * 1;
+  float mengerSpongeDist = spongeCube;
+  
+  float scale = 1.0;
+  for(int i = 0; i < numIterations; ++i) {
+    float boxedWidth = cubeWidth / scale;
+    
+    float translation = -boxedWidth / 2.0;
+    vec3 ray = rayPos - translation;
+    vec3 repeatedPos = mod(ray, boxedWidth);
+    repeatedPos += translation;
+    
+
+    repeatedPos *= scale;
+    
+    float crossesDist = cross(repeatedPos / oneThird, 1.0, vec3(0.0)) * oneThird;
+    
+    crossesDist /= scale;
+    
+    mengerSpongeDist = max(mengerSpongeDist, -crossesDist);
+    
+    scale *= 3.0;
+  }
+  return mengerSpongeDist;
+}
+
+// applies the operation of an object to everything before it, the material follows the color
+void combine(vec2 operation, float new_dst, vec3 color, Material material, inout float dst, inout vec3 clr, inout Material mat) {
+    int op = int(operation.x);
+    float k = max(operation.y, MIN_BLEND_RADIUS);
+
+    if (op == 0) {
+        if (new_dst < dst) {
+            dst = new_dst;
+            clr = color;
+            mat = material;
+        }
+    } else if (op == 1) {
+        // the cut surface gets the color of the object that cuts
+        if (-new_dst > dst) {
+            dst = -new_dst;
+            clr = color;
+            mat = material;
+        }
+    } else if (op == 2) {
+        if (new_dst > dst) {
+            dst = new_dst;
+            clr = color;
+            mat = material;
+        }
+    } else if (op == 3) {
+        mat = mixMaterial(material, mat, blendFactor(dst, new_dst, k));
+        vec4 blended = Blend(dst, new_dst, clr, color, k);
+        dst = blended.w;
+        clr = blended.xyz;
+    } else if (op == 4) {
+        // the smooth maximums are the smooth minimum of the negated distances
+        mat = mixMaterial(material, mat, blendFactor(-dst, new_dst, k));
+        vec4 blended = Blend(-dst, new_dst, clr, color, k);
+        dst = -blended.w;
+        clr = blended.xyz;
+    } else if (op == 5) {
+        mat = mixMaterial(material, mat, blendFactor(-dst, -new_dst, k));
+        vec4 blended = Blend(-dst, -new_dst, clr, color, k);
+        dst = -blended.w;
+        clr = blended.xyz;
+    }
+}
+
+// for the node graph, which does not keep track of materials
+void combine(vec2 operation, float new_dst, vec3 color, inout float dst, inout vec3 clr) {
+    Material mat = DEFAULT_MATERIAL;
+    combine(operation, new_dst, color, DEFAULT_MATERIAL, dst, clr, mat);
+}
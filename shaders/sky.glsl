@@ -0,0 +1,80 @@
+// what is seen where rays leave the scene, see src/sky.rs which does the same on the cpu
+// the sky is also seen in reflections, and lights the surfaces from every direction with the ambient light
+
+// kind is the same as SkyKind: 0 gradient, 1 cubemap, 2 equirectangular
+uniform int skyKind;
+// the colors of the gradient straight up, at the horizon and straight down
+uniform vec3 skyZenith;
+uniform vec3 skyHorizon;
+uniform vec3 skyGround;
+uniform float skyIntensity;
+// how much the sky lights the surfaces, on top of the lights
+uniform float skyAmbient;
+// the faces of the cubemap are the layers of skyCubemap, the faces and the panorama are filtered linearly like on the cpu
+uniform sampler2DArray skyCubemap;
+uniform sampler2D skyPanorama;
+
+// the nine coefficients of the spherical harmonics of the sky up to the second band, see SkyIrradiance.
+// they are the columns of the matrices, glium can not set arrays of uniforms
+uniform mat3 skyIrradiance0;
+uniform mat3 skyIrradiance1;
+uniform mat3 skyIrradiance2;
+
+// the face that dir points at and where on it, the same as in opengl
+int cubemapFace(vec3 dir, out vec2 st) {
+    vec3 a = abs(dir);
+    int face;
+    vec2 sc;
+    float ma;
+
+    if (a.x >= a.y && a.x >= a.z) {
+        face = dir.x > 0.0 ? 0 : 1;
+        sc = vec2(dir.x > 0.0 ? -dir.z : dir.z, -dir.y);
+        ma = a.x;
+    } else if (a.y >= a.z) {
+        face = dir.y > 0.0 ? 2 : 3;
+        sc = vec2(dir.x, dir.y > 0.0 ? dir.z : -dir.z);
+        ma = a.y;
+    } else {
+        face = dir.z > 0.0 ? 4 : 5;
+        sc = vec2(dir.z > 0.0 ? dir.x : -dir.x, -dir.y);
+        ma = a.z;
+    }
+
+    st = (sc / ma + 1.0) / 2.0;
+    return face;
+}
+
+// the longitude goes right from +z towards +x, the latitude goes down from +y
+vec2 panoramaSt(vec3 dir) {
+    return vec2(0.5 + atan(dir.x, dir.z) / (2.0 * PI), acos(clamp(dir.y, -1.0, 1.0)) / PI);
+}
+
+// the color of the sky in the direction dir
+vec3 skyColor(vec3 dir) {
+    vec3 clr;
+
+    if (skyKind == 1) {
+        vec2 st;
+        int face = cubemapFace(dir, st);
+        clr = textureLod(skyCubemap, vec3(st, float(face)), 0.0).rgb;
+    } else if (skyKind == 2) {
+        clr = textureLod(skyPanorama, panoramaSt(dir), 0.0).rgb;
+    } else if (dir.y >= 0.0) {
+        clr = mix(skyHorizon, skyZenith, dir.y);
+    } else {
+        clr = mix(skyHorizon, skyGround, -dir.y);
+    }
+
+    return clr * skyIntensity;
+}
+
+// the light that a surface facing norm gets from the whole sky, divided by pi so that a sky of one color gives that color
+vec3 skyLight(vec3 norm) {
+    float x = norm.x;
+    float y = norm.y;
+    float z = norm.z;
+
+    vec3 light = skyIrradiance0 * vec3(1.0, y, z) + skyIrradiance1 * vec3(x, x * y, y * z) + skyIrradiance2 * vec3(3.0 * z * z - 1.0, x * z, x * x - y * y);
+    return max(light, vec3(0.0));
+}
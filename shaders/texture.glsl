@@ -0,0 +1,113 @@
+// textures of the objects, see src/texture.rs which does the same on the cpu
+// distance fields have no uv coordinates, so the texture is projected along the three axes of the object
+// and the projections are blended by the normal of the surface (triplanar mapping)
+
+// data is (pattern, image, scale, sharpness), the pattern is the same as Pattern:
+// 0 none, 1 checker, 2 noise, 3 stripes, 4 marble, 5 image
+// center and transform are the ones of the object, so that the texture moves, rotates and scales with it
+struct ObjectTexture {
+    vec4 data;
+    vec3 center;
+    Transform transform;
+};
+
+// every image is a layer, they all have the same size. the top row of an image is at t = 0
+uniform sampler2DArray textureImages;
+// objects can refer to an image that is not on the gpu when the array could not be created
+uniform int numOfTextureImages;
+
+// the patterns go from this much of the color of the object to all of it
+const float PATTERN_DARK = 0.3;
+const int NOISE_OCTAVES = 4;
+// how much the noise bends the stripes of marble
+const float MARBLE_TURBULENCE = 2.0;
+
+// bilinear between the four closest pixels, v goes up and the rows of the image go down.
+// the image is sampled at its full size, the derivatives for a mipmap are not defined in the loops of the marcher
+vec3 imageColor(int index, vec2 uv) {
+    if (index < 0 || index >= numOfTextureImages) {
+        return vec3(1.0);
+    }
+    return textureLod(textureImages, vec3(uv.x, -uv.y, float(index)), 0.0).rgb;
+}
+
+// integer math so that the cpu and the gpu get the same noise
+float hash(int x, int y) {
+    uint h = (uint(x) * 1597334677u) ^ (uint(y) * 3812015801u);
+    h ^= h >> 16;
+    h *= 2246822519u;
+    h ^= h >> 13;
+    // 24 bits fit in a float exactly
+    return float(h >> 8) / 16777216.0;
+}
+
+// smoothly interpolated random values at the integer points
+float valueNoise(vec2 uv) {
+    vec2 i = floor(uv);
+    vec2 f = uv - i;
+    vec2 u = f * f * (3.0 - 2.0 * f);
+    int x = int(i.x);
+    int y = int(i.y);
+
+    return mix(mix(hash(x, y), hash(x + 1, y), u.x), mix(hash(x, y + 1), hash(x + 1, y + 1), u.x), u.y);
+}
+
+// octaves of noise that get smaller and weaker, from 0 to 1
+float fbm(vec2 uv) {
+    float value = 0.0;
+    float amplitude = 0.5;
+    float total = 0.0;
+
+    for (int i = 0; i < NOISE_OCTAVES; i++) {
+        value += amplitude * valueNoise(uv);
+        total += amplitude;
+        uv *= 2.0;
+        amplitude *= 0.5;
+    }
+    return value / total;
+}
+
+// from 0 to 1
+float patternValue(int pattern, vec2 uv) {
+    if (pattern == 1) {
+        return mod(floor(uv.x) + floor(uv.y), 2.0);
+    } else if (pattern == 2) {
+        return fbm(uv);
+    } else if (pattern == 3) {
+        return step(0.5, fract(uv.x));
+    } else if (pattern == 4) {
+        return 0.5 + 0.5 * sin((uv.x + MARBLE_TURBULENCE * fbm(uv)) * PI);
+    }
+    return 1.0;
+}
+
+vec3 textureSample(vec4 data, vec2 uv) {
+    int pattern = int(data.x);
+    if (pattern == 5) {
+        return imageColor(int(data.y), uv);
+    }
+    return vec3(mix(PATTERN_DARK, 1.0, patternValue(pattern, uv)));
+}
+
+// the color of an object with its texture at pos, norm is the normal of the surface.
+// the distance functions get a zero norm while marching, where the texture is not needed
+vec3 applyTexture(vec3 clr, ObjectTexture tex, vec3 pos, vec3 norm) {
+    if (tex.data.x == 0.0 || norm == vec3(0.0)) {
+        return clr;
+    }
+
+    vec3 p = (untransform(tex.transform, tex.center, pos) - tex.center) * tex.data.z;
+
+    // normals are scaled the opposite way of positions
+    vec4 q = tex.transform.rotation;
+    vec3 n = qMul(qMul(vec4(q.x, -q.yzw), vec4(0.0, norm)), q).yzw;
+    n = normalize(n * tex.transform.scale);
+
+    vec3 w = pow(abs(n), vec3(tex.data.w));
+
+    vec3 x = textureSample(tex.data, p.zy);
+    vec3 y = textureSample(tex.data, p.xz);
+    vec3 z = textureSample(tex.data, p.xy);
+
+    return clr * (w.x * x + w.y * y + w.z * z) / (w.x + w.y + w.z);
+}
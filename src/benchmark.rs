@@ -0,0 +1,58 @@
+// `--benchmark <frames>` renders the same frames with the bvh turned off and on and prints how long they took
+
+use std::time::{Duration, Instant};
+
+use crate::object_handler::ObjectHandeler;
+use crate::shapes::{Combine, MengerSponge, Operation, Sphere, DEFAULT_BLEND_RADIUS};
+
+// fills the scene with a grid of spheres above a grid of menger sponges that are all blended together, used when no scene file is given
+pub fn create_scene(object_handeler : &mut ObjectHandeler) {
+    let blend = Combine::new(Operation::SmoothUnion, DEFAULT_BLEND_RADIUS);
+
+    let mut spheres = Vec::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            let color = [x as f32 / 16.0, 0.5, z as f32 / 16.0];
+            spheres.push(Sphere { combine : blend, ..Sphere::new([x as f32 - 7.5, 0.5, z as f32], color, 0.3) });
+        }
+    }
+
+    let mut menger_sponges = Vec::new();
+    for x in 0..8 {
+        for z in 0..8 {
+            menger_sponges.push(MengerSponge { combine : blend, ..MengerSponge::new([x as f32 * 2.0 - 7.0, -1.5, z as f32 * 2.0 + 1.0], 4.0, [0.8078, 0.1647, 0.3569]) });
+        }
+    }
+
+    object_handeler.add_spheres_from(spheres);
+    object_handeler.add_menger_sponges_from(menger_sponges);
+}
+
+// calls draw_frame for every frame, first going through every object and then with the bvh.
+// draw_frame should not return before the gpu is done with the frame
+pub fn run(object_handeler : &mut ObjectHandeler, frames : u32, mut draw_frame : impl FnMut(&mut ObjectHandeler)) {
+    let use_bvh = object_handeler.get_use_bvh();
+
+    let linear = time_frames(object_handeler, frames, false, &mut draw_frame);
+    let bvh = time_frames(object_handeler, frames, true, &mut draw_frame);
+
+    object_handeler.set_use_bvh(use_bvh);
+
+    let objects = object_handeler.object_ids().count();
+    println!("{} frames, {} objects", frames, objects);
+    println!("without bvh: {:.2} ms per frame", linear.as_secs_f64() * 1000.0);
+    println!("with bvh:    {:.2} ms per frame", bvh.as_secs_f64() * 1000.0);
+    println!("speedup:     {:.2}x", linear.as_secs_f64() / bvh.as_secs_f64());
+}
+
+// the average time of a frame, after one frame that is not counted so that uploading the scene is not included
+fn time_frames(object_handeler : &mut ObjectHandeler, frames : u32, use_bvh : bool, draw_frame : &mut impl FnMut(&mut ObjectHandeler)) -> Duration {
+    object_handeler.set_use_bvh(use_bvh);
+    draw_frame(object_handeler);
+
+    let start = Instant::now();
+    for _ in 0..frames {
+        draw_frame(object_handeler);
+    }
+    start.elapsed() / frames.max(1)
+}
@@ -0,0 +1,212 @@
+// bounding volume hierarchy over the objects in the scene, so that the distance to the scene can be found without
+// looking at every object. the traversal is in sdf.rs (bvh_min_dist) and in the shader (bvhMinDist)
+
+use crate::object_handler::{ObjectHandeler, ObjectId, ObjectKind};
+use crate::shapes::{Operation, Transform, Triangle};
+use crate::vec_util::{length, vec_add};
+
+// axis aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min : [f32; 3],
+    pub max : [f32; 3],
+}
+
+impl Aabb {
+
+    pub fn new(min : [f32; 3], max : [f32; 3]) -> Aabb {
+        Aabb { min, max }
+    }
+
+    // the box from center - half_size to center + half_size
+    pub fn around(center : [f32; 3], half_size : [f32; 3]) -> Aabb {
+        let half_size = [half_size[0].abs(), half_size[1].abs(), half_size[2].abs()];
+        Aabb::new(vec_add(center, half_size, -1.0), vec_add(center, half_size, 1.0))
+    }
+
+    pub fn union(&self, other : &Aabb) -> Aabb {
+        Aabb::new(
+            [self.min[0].min(other.min[0]), self.min[1].min(other.min[1]), self.min[2].min(other.min[2])],
+            [self.max[0].max(other.max[0]), self.max[1].max(other.max[1]), self.max[2].max(other.max[2])],
+        )
+    }
+
+    // the smallest box around the vertices of the triangles, there has to be at least one triangle
+    pub fn around_triangles(triangles : &[Triangle]) -> Aabb {
+        let first = Aabb::new(triangles[0].v1, triangles[0].v1);
+
+        triangles.iter().fold(first, |bounds, t| {
+            bounds.union(&Aabb::new(t.v1, t.v1)).union(&Aabb::new(t.v2, t.v2)).union(&Aabb::new(t.v3, t.v3))
+        })
+    }
+
+    // a box around this box after the transform of an object at center is applied to it.
+    // with a non uniform scale the distance function of the object is shorter than the real distance,
+    // so it can be less than the distance to this box and the bvh then skips objects that the full loop would look at.
+    // the skipped objects are still further away than the step that is taken, so the ray can not go through them
+    pub fn transformed(&self, transform : &Transform, center : [f32; 3]) -> Aabb {
+        let first = transform.apply(center, self.min);
+
+        (1..8).fold(Aabb::new(first, first), |bounds, i| {
+            let corner = [
+                if i & 1 == 0 { self.min[0] } else { self.max[0] },
+                if i & 2 == 0 { self.min[1] } else { self.max[1] },
+                if i & 4 == 0 { self.min[2] } else { self.max[2] },
+            ];
+            let moved = transform.apply(center, corner);
+            bounds.union(&Aabb::new(moved, moved))
+        })
+    }
+
+    pub fn grown(&self, amount : f32) -> Aabb {
+        Aabb::new(vec_add(self.min, [amount; 3], -1.0), vec_add(self.max, [amount; 3], 1.0))
+    }
+
+    pub fn center(&self) -> [f32; 3] {
+        [(self.min[0] + self.max[0]) / 2.0, (self.min[1] + self.max[1]) / 2.0, (self.min[2] + self.max[2]) / 2.0]
+    }
+
+    // distance from pos to the box, zero inside it. This is never more than the distance to anything inside the box
+    pub fn dist(&self, pos : [f32; 3]) -> f32 {
+        let mut outside = [0.0; 3];
+        for i in 0..3 {
+            outside[i] = f32::max(f32::max(self.min[i] - pos[i], pos[i] - self.max[i]), 0.0);
+        }
+        length(outside)
+    }
+}
+
+// a box around everything that the distance function of the object can reach
+pub fn object_bounds(object_handeler : &ObjectHandeler, id : ObjectId) -> Aabb {
+    match id.kind {
+        ObjectKind::Sphere => {
+            let sphere = &object_handeler.get_spheres()[id.index];
+            Aabb::around(sphere.pos, [sphere.radius; 3]).transformed(&sphere.transform, sphere.pos)
+        },
+        ObjectKind::Cube => {
+            let cube = &object_handeler.get_cubes()[id.index];
+            Aabb::around(cube.pos, cube.dim).transformed(&cube.transform, cube.pos)
+        },
+        ObjectKind::Triangle => Aabb::around_triangles(&object_handeler.get_triangles()[id.index..id.index + 1]),
+        // sdMengerSponge is cut out of a cube with sides of length 2
+        ObjectKind::MengerSponge => {
+            let menger_sponge = &object_handeler.get_menger_sponges()[id.index];
+            Aabb::around(menger_sponge.pos, [1.0; 3]).transformed(&menger_sponge.transform, menger_sponge.pos)
+        },
+        ObjectKind::Mesh => {
+            // the box around the triangles is relative to the mesh pos
+            let mesh = &object_handeler.get_meshes()[id.index];
+            let local = Aabb::new(mesh.bounds_min, mesh.bounds_max);
+            Aabb::new(vec_add(local.min, mesh.pos, 1.0), vec_add(local.max, mesh.pos, 1.0)).transformed(&mesh.transform, mesh.pos)
+        },
+        ObjectKind::Torus => {
+            let torus = &object_handeler.get_tori()[id.index];
+            let outer = torus.radius.abs() + torus.thickness;
+            Aabb::around(torus.pos, [outer, torus.thickness, outer]).transformed(&torus.transform, torus.pos)
+        },
+        ObjectKind::Capsule => {
+            let capsule = &object_handeler.get_capsules()[id.index];
+            Aabb::around(capsule.pos, [capsule.radius, capsule.half_height.abs() + capsule.radius, capsule.radius]).transformed(&capsule.transform, capsule.pos)
+        },
+        ObjectKind::Cylinder => {
+            let cylinder = &object_handeler.get_cylinders()[id.index];
+            Aabb::around(cylinder.pos, [cylinder.radius, cylinder.half_height, cylinder.radius]).transformed(&cylinder.transform, cylinder.pos)
+        },
+        ObjectKind::Cone => {
+            let cone = &object_handeler.get_cones()[id.index];
+            let radius = f32::max(cone.bottom_radius.abs(), cone.top_radius.abs());
+            Aabb::around(cone.pos, [radius, cone.half_height, radius]).transformed(&cone.transform, cone.pos)
+        },
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BvhContent {
+    Object(ObjectId),
+    // the indices of the two child nodes
+    Children(usize, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BvhNode {
+    pub bounds : Aabb,
+    pub content : BvhContent,
+}
+
+// the nodes of the tree, the root is the first node. Every leaf holds exactly one object.
+// only the objects before the first one whose operation can not be reordered are in the tree,
+// the rest have to be applied one at a time in list order after the tree has been gone through
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes : Vec<BvhNode>,
+    ordered_start : usize,
+}
+
+impl Bvh {
+
+    pub fn new() -> Bvh {
+        Bvh { nodes : Vec::new(), ordered_start : 0 }
+    }
+
+    // builds the tree by splitting the objects in half along the longest side of the box around their centers,
+    // which keeps the depth at about log2 of the number of objects
+    pub fn build(object_handeler : &ObjectHandeler) -> Bvh {
+        let ordered_start = object_handeler.object_ids().position(|id| !object_handeler.get_object(id).combine().can_reorder())
+            .unwrap_or(object_handeler.get_order().len());
+
+        let mut objects = Vec::new();
+        for id in object_handeler.object_ids().take(ordered_start) {
+            let combine = object_handeler.get_object(id).combine();
+
+            // a smooth union changes the distance until the object is blend_radius further away than the rest,
+            // with the box grown by that much the node can still be skipped once it is further away than the current distance
+            let bounds = match combine.operation {
+                Operation::SmoothUnion => object_bounds(object_handeler, id).grown(combine.blend_radius.abs()),
+                _ => object_bounds(object_handeler, id),
+            };
+            objects.push((id, bounds));
+        }
+
+        let mut bvh = Bvh { nodes : Vec::with_capacity(2 * objects.len()), ordered_start };
+        if !objects.is_empty() {
+            bvh.build_node(&mut objects);
+        }
+        bvh
+    }
+
+    fn build_node(&mut self, objects : &mut [(ObjectId, Aabb)]) -> usize {
+        let bounds = objects.iter().skip(1).fold(objects[0].1, |bounds, (_, b)| bounds.union(b));
+        let index = self.nodes.len();
+
+        if objects.len() == 1 {
+            self.nodes.push(BvhNode { bounds, content : BvhContent::Object(objects[0].0) });
+            return index;
+        }
+
+        // the children are filled in after they have been built
+        self.nodes.push(BvhNode { bounds, content : BvhContent::Children(0, 0) });
+
+        let centers = objects.iter().skip(1).fold(Aabb::around(objects[0].1.center(), [0.0; 3]), |centers, (_, b)| centers.union(&Aabb::around(b.center(), [0.0; 3])));
+        let size = vec_add(centers.max, centers.min, -1.0);
+        let axis = if size[0] >= size[1] && size[0] >= size[2] { 0 } else if size[1] >= size[2] { 1 } else { 2 };
+
+        let middle = objects.len() / 2;
+        objects.select_nth_unstable_by(middle, |a, b| a.1.center()[axis].total_cmp(&b.1.center()[axis]));
+
+        let (left_objects, right_objects) = objects.split_at_mut(middle);
+        let left = self.build_node(left_objects);
+        let right = self.build_node(right_objects);
+
+        self.nodes[index].content = BvhContent::Children(left, right);
+        index
+    }
+
+    pub fn get_nodes(&self) -> &Vec<BvhNode> {
+        &self.nodes
+    }
+
+    // the position in the list order of the first object that is not in the tree, it and every object after it are not in the tree
+    pub fn get_ordered_start(&self) -> usize {
+        self.ordered_start
+    }
+}
@@ -1,5 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::vec_util::{normalize, get_rotation_quaternion, vec_add, rotate_pos};
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Camera {
     pub pos : [f32; 3],
     pub rotation_axis : [f32; 3],
@@ -28,4 +31,11 @@ impl Camera {
         self.pos = vec_add(rotated_pos, obj_pos, 1.0);
         self.angle -= angle;
     }
-}
\ No newline at end of file
+}
+
+// a saved camera position that can be returned to later
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    pub name : String,
+    pub camera : Camera,
+}
@@ -0,0 +1,496 @@
+// CPU version of the ray marching in shaders/fragment.glsl (_march, rayMarch) and shaders/lighting.glsl (shade), renders a scene without a gpu
+// like sdf.rs this should be kept in sync with the shader
+
+use std::f32::consts::PI;
+use std::{fmt, path::Path};
+
+use crate::camera::Camera;
+use crate::light::{Light, LightKind};
+use crate::material::Material;
+use crate::object_handler::ObjectHandeler;
+use crate::sdf::{approx_norm, min_dist, object_dist, on_ground, surface_at};
+use crate::sky::{sky_color, sky_light};
+use crate::vec_util::{dot, length, normalize, rotate_pos, vec_add, vec_scale};
+
+pub const MIN_DIST : f32 = 0.005;
+pub const MAX_DEPTH : i32 = 150;
+
+#[derive(Debug)]
+pub enum RenderError {
+    // the width or height is 0, or the image has more bytes than fit in a usize
+    Size { width : u32, height : u32 },
+    Image(image::ImageError),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Size { width, height } => write!(f, "can not render an image of {}x{} pixels", width, height),
+            RenderError::Image(err) => write!(f, "could not write the image: {}", err),
+        }
+    }
+}
+
+// the most bounces the side panel allows
+pub const MAX_BOUNCES : u32 = 8;
+// reflected and refracted rays start this far from the surface, on the side they go to
+const BOUNCE_BIAS : f32 = 0.02;
+// rays that would give less than this of the pixel color are not traced
+const MIN_BOUNCE_WEIGHT : f32 = 0.01;
+
+const MAX_SHADOW_STEPS : i32 = 64;
+// shadow rays start this far from the surface, so that they do not hit the surface they start on
+const SHADOW_BIAS : f32 = 0.01;
+// how far shadow rays towards directional lights go
+const MAX_SHADOW_DIST : f32 = 50.0;
+// how far along the normal ambient occlusion looks for other surfaces
+const AO_DIST : f32 = 0.3;
+// a perfectly smooth surface would have its highlight in a single point
+const MIN_ROUGHNESS : f32 = 0.02;
+// how much light non metals reflect straight back with a specular of 1
+const MAX_F0 : f32 = 0.08;
+
+// surfaces closer than SELECTION_DIST to the selected object are tinted with SELECTION_CLR
+pub const SELECTION_CLR : [f32; 3] = [1.0, 0.6, 0.1];
+pub const SELECTION_DIST : f32 = 0.02;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    pub pos : [f32; 3],
+    pub dir : [f32; 3],
+}
+
+pub fn get_dir(uv : [f32; 2], fov : f32) -> [f32; 3] {
+    [uv[0], uv[1], 1.0 / f32::tan((fov / 2.0).to_radians())]
+}
+
+// the shader rotates with q_inv * p * q, the opposite order of rotate_pos
+pub fn rotate_dir(dir : [f32; 3], camera_rotation_quaternion : [f32; 4]) -> [f32; 3] {
+    let q = camera_rotation_quaternion;
+    rotate_pos(dir, [q[0], -q[1], -q[2], -q[3]])
+}
+
+// the ray going through uv, where uv is the screen position normalized to [-1, 1] with y pointing up
+pub fn get_ray(camera : &Camera, uv : [f32; 2]) -> Ray {
+    let dir = get_dir(uv, camera.fov);
+    let dir = rotate_dir(dir, camera.get_rotation_quaternion());
+
+    Ray { pos : camera.pos, dir : normalize(dir) }
+}
+
+// the inverse of get_ray, the uv coordinates where pos is seen on the screen, None if pos is behind the camera
+pub fn project(camera : &Camera, pos : [f32; 3]) -> Option<[f32; 2]> {
+    let p = rotate_pos(vec_add(pos, camera.pos, -1.0), camera.get_rotation_quaternion());
+
+    if p[2] <= 0.0 {
+        return None;
+    }
+
+    let focal_length = get_dir([0.0, 0.0], camera.fov)[2];
+    Some([p[0] * focal_length / p[2], p[1] * focal_length / p[2]])
+}
+
+fn smoothstep(edge0 : f32, edge1 : f32, x : f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// how much of its color a light gives at pos, before the angle to the surface is taken into account
+fn light_color(light : &Light, pos : [f32; 3]) -> [f32; 3] {
+    let mut intensity = light.intensity;
+
+    if light.kind == LightKind::Directional {
+        return vec_scale(light.color, intensity);
+    }
+
+    if light.range > 0.0 {
+        let d = length(vec_add(pos, light.pos, -1.0)) / light.range;
+        intensity /= 1.0 + d * d;
+    }
+
+    if light.kind == LightKind::Spot {
+        let (spot_cos, full_spot_cos) = light.spot_cos();
+        let cos_angle = dot(normalize(vec_add(pos, light.pos, -1.0)), light.direction());
+        intensity *= smoothstep(spot_cos, full_spot_cos, cos_angle);
+    }
+
+    vec_scale(light.color, intensity)
+}
+
+// the direction from the light to pos
+fn light_dir(light : &Light, pos : [f32; 3]) -> [f32; 3] {
+    match light.kind {
+        LightKind::Directional => light.direction(),
+        _ => normalize(vec_add(pos, light.pos, -1.0)),
+    }
+}
+
+// how much of the light gets from pos to max_dist along dir, 0 when something is in the way
+fn soft_shadow(object_handeler : &ObjectHandeler, pos : [f32; 3], dir : [f32; 3], max_dist : f32) -> f32 {
+    let k = 1.0 / f32::max(object_handeler.get_shadow_softness(), 0.001);
+    let mut res : f32 = 1.0;
+    let mut t = SHADOW_BIAS;
+
+    for _ in 0..MAX_SHADOW_STEPS {
+        if t >= max_dist {
+            break;
+        }
+
+        let (h, _) = min_dist(object_handeler, vec_add(pos, dir, t));
+        if h < MIN_DIST {
+            return 0.0;
+        }
+        res = res.min(k * h / t);
+        t += h;
+    }
+    res.clamp(0.0, 1.0)
+}
+
+fn light_visibility(object_handeler : &ObjectHandeler, light : &Light, pos : [f32; 3]) -> f32 {
+    if !object_handeler.get_use_shadows() {
+        return 1.0;
+    }
+    match light.kind {
+        LightKind::Directional => soft_shadow(object_handeler, pos, vec_scale(light.direction(), -1.0), MAX_SHADOW_DIST),
+        _ => {
+            let to_light = vec_add(light.pos, pos, -1.0);
+            soft_shadow(object_handeler, pos, normalize(to_light), length(to_light))
+        },
+    }
+}
+
+// "specularBrdf" in the shader, n is the normal out of the surface, v points towards the viewer and l towards the light
+fn specular_brdf(n : [f32; 3], v : [f32; 3], l : [f32; 3], roughness : f32, f0 : [f32; 3]) -> [f32; 3] {
+    let h = normalize(vec_add(v, l, 1.0));
+    let nh = dot(n, h).max(0.0);
+    let nv = dot(n, v).max(0.0001);
+    let nl = dot(n, l).max(0.0001);
+
+    let alpha = roughness.max(MIN_ROUGHNESS) * roughness.max(MIN_ROUGHNESS);
+    let a2 = alpha * alpha;
+    let denom = nh * nh * (a2 - 1.0) + 1.0;
+    let distribution = a2 / (PI * denom * denom);
+
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let geometry = nv / (nv * (1.0 - k) + k) * nl / (nl * (1.0 - k) + k);
+
+    let schlick = f32::powf(1.0 - dot(h, v).max(0.0), 5.0);
+    f0.map(|f| (f + (1.0 - f) * schlick) * distribution * geometry / (4.0 * nv * nl))
+}
+
+// a shadow only takes away the light on the side facing the light, like in the shader. dir is the direction of the ray that hit the surface
+pub fn shade(object_handeler : &ObjectHandeler, clr : [f32; 3], material : &Material, norm : [f32; 3], pos : [f32; 3], dir : [f32; 3]) -> [f32; 3] {
+    // metals have colored highlights and no diffuse light
+    let f0 = clr.map(|c| MAX_F0 * material.specular * (1.0 - material.metallic) + c * material.metallic);
+    let diffuse = vec_scale(clr, 1.0 - material.metallic);
+
+    // the sky lights the surface from every direction, norm points into it
+    let sky = mul(diffuse, sky_light(object_handeler.get_sky_irradiance(), vec_scale(norm, -1.0)));
+    let mut result = vec_add(vec_scale(clr, material.emissive), sky, object_handeler.get_sky().ambient);
+    let start = vec_add(pos, norm, -SHADOW_BIAS);
+
+    for light in object_handeler.get_lights() {
+        let light_clr = light_color(light, pos);
+        if light_clr == [0.0; 3] {
+            continue;
+        }
+
+        let l = light_dir(light, pos);
+        let d = dot(l, norm);
+        let visibility = if d > 0.0 { light_visibility(object_handeler, light, start) } else { 1.0 };
+
+        let mut c = vec_scale(diffuse, (1.0 + d.min(0.0) + d.max(0.0) * visibility) / 2.0);
+        if d > 0.0 {
+            let specular = specular_brdf(vec_scale(norm, -1.0), vec_scale(dir, -1.0), vec_scale(l, -1.0), material.roughness, f0);
+            c = vec_add(c, specular, d * visibility);
+        }
+        result = vec_add(result, mul(c, light_clr), 1.0);
+    }
+
+    result
+}
+
+// 1 in the open and less where other surfaces are close, norm points into the surface
+fn ambient_occlusion(object_handeler : &ObjectHandeler, pos : [f32; 3], norm : [f32; 3]) -> f32 {
+    let ambient_occlusion = object_handeler.get_ambient_occlusion();
+    let steps = ambient_occlusion.active_steps();
+    if steps == 0 {
+        return 1.0;
+    }
+
+    let mut occlusion = 0.0;
+    for i in 0..steps {
+        let h = AO_DIST * (i + 1) as f32 / steps as f32;
+        let (d, _) = min_dist(object_handeler, vec_add(pos, norm, -h));
+        occlusion += ((h - d) / h).clamp(0.0, 1.0);
+    }
+
+    (1.0 - ambient_occlusion.strength * occlusion / steps as f32).clamp(0.0, 1.0)
+}
+
+// where a marched ray ended up
+pub enum MarchResult {
+    Surface { pos : [f32; 3], dst : f32 },
+    Background(Ray),
+}
+
+// "marchToSurface" in the shader, inside is for rays going through an object, where the distance is negative
+pub fn march_ray(object_handeler : &ObjectHandeler, mut ray : Ray, depth : i32, inside : bool) -> MarchResult {
+    for _ in 0..depth {
+        let (dst, _) = min_dist(object_handeler, ray.pos);
+        let step_dist = if inside { -dst } else { dst };
+
+        if step_dist <= MIN_DIST {
+            return MarchResult::Surface { pos : ray.pos, dst };
+        }
+
+        ray = Ray { pos : vec_add(ray.pos, ray.dir, step_dist), dir : ray.dir };
+    }
+
+    MarchResult::Background(ray)
+}
+
+// "groundFade" in the shader, 0 where the surface at pos is an object. dist is how far the ray went to get to pos
+fn ground_fade(object_handeler : &ObjectHandeler, pos : [f32; 3], dist : f32) -> f32 {
+    let ground = object_handeler.get_ground();
+    if ground.fade_distance <= 0.0 || !on_ground(object_handeler, pos) {
+        return 0.0;
+    }
+    ground.fade(dist)
+}
+
+fn highlight_selected(object_handeler : &ObjectHandeler, clr : [f32; 3], pos : [f32; 3]) -> [f32; 3] {
+    match object_handeler.get_selected() {
+        Some(id) if object_dist(object_handeler, id, pos).abs() < SELECTION_DIST => vec_add(vec_scale(clr, 0.5), SELECTION_CLR, 0.5),
+        _ => clr
+    }
+}
+
+// glsl reflect, n has to be normalized
+fn reflect(dir : [f32; 3], n : [f32; 3]) -> [f32; 3] {
+    vec_add(dir, n, -2.0 * dot(n, dir))
+}
+
+// glsl refract, None instead of a zero vector when the ray can not get through
+fn refract(dir : [f32; 3], n : [f32; 3], eta : f32) -> Option<[f32; 3]> {
+    let d = dot(n, dir);
+    let k = 1.0 - eta * eta * (1.0 - d * d);
+    if k < 0.0 {
+        return None;
+    }
+    Some(vec_add(vec_scale(dir, eta), n, -(eta * d + f32::sqrt(k))))
+}
+
+// a ray that is waiting to be traced, weight is how much of the pixel color it gives
+struct BounceRay {
+    ray : Ray,
+    weight : [f32; 3],
+    bounces : u32,
+    inside : bool,
+}
+
+fn max_component(v : [f32; 3]) -> f32 {
+    v[0].max(v[1]).max(v[2])
+}
+
+fn mul(a : [f32; 3], b : [f32; 3]) -> [f32; 3] {
+    [a[0] * b[0], a[1] * b[1], a[2] * b[2]]
+}
+
+// "_march" in the shader, follows the ray and the rays that are reflected and refracted by the surfaces it hits
+pub fn march(object_handeler : &ObjectHandeler, ray : Ray, depth : i32) -> [f32; 3] {
+    let mut result = [0.0; 3];
+    let bounce_limit = object_handeler.get_max_bounces().min(MAX_BOUNCES);
+
+    let mut stack = vec![BounceRay { ray, weight : [1.0; 3], bounces : 0, inside : false }];
+
+    while let Some(mut current) = stack.pop() {
+        let (p, dst) = match march_ray(object_handeler, current.ray, depth, current.inside) {
+            MarchResult::Surface { pos, dst } => (pos, dst),
+            MarchResult::Background(ray) => {
+                let sky = sky_color(&object_handeler.get_sky(), object_handeler.get_sky_images(), ray.dir);
+                result = vec_add(result, mul(current.weight, sky), 1.0);
+                continue;
+            },
+        };
+
+        let dir = current.ray.dir;
+        // approx_norm points into the surface, facing is the normal on the side the ray came from
+        let norm = approx_norm(object_handeler, p, dst);
+        let facing = if current.inside { norm } else { vec_scale(norm, -1.0) };
+
+        // a ray going through an object leaves it here, or is reflected back in when it can not get out
+        if current.inside {
+            // only the index of refraction is needed, so the textures are left out
+            match refract(dir, facing, surface_at(object_handeler, p, None).0.ior) {
+                Some(refracted) => stack.push(BounceRay {
+                    ray : Ray { pos : vec_add(p, facing, -BOUNCE_BIAS), dir : refracted }, inside : false, ..current
+                }),
+                None if current.bounces < bounce_limit => stack.push(BounceRay {
+                    ray : Ray { pos : vec_add(p, facing, BOUNCE_BIAS), dir : reflect(dir, facing) }, bounces : current.bounces + 1, ..current
+                }),
+                None => {},
+            }
+            continue;
+        }
+
+        let ao = ambient_occlusion(object_handeler, p, norm);
+        if object_handeler.get_ambient_occlusion().debug_view {
+            result = vec_add(result, vec_scale(current.weight, ao), 1.0);
+            continue;
+        }
+
+        // far away the ground fades into the sky, the rays it reflects and refracts fade with it
+        let fade = ground_fade(object_handeler, p, length(vec_add(p, current.ray.pos, -1.0)));
+        if fade > 0.0 {
+            let sky = sky_color(&object_handeler.get_sky(), object_handeler.get_sky_images(), dir);
+            result = vec_add(result, mul(current.weight, sky), fade);
+            current.weight = vec_scale(current.weight, 1.0 - fade);
+            if fade >= 1.0 {
+                continue;
+            }
+        }
+
+        let (material, clr) = surface_at(object_handeler, p, Some(norm));
+        let clr = highlight_selected(object_handeler, clr, p);
+        let reflected = material.reflectivity;
+        let transmitted = (1.0 - material.reflectivity) * material.transparency;
+        let mut surface = 1.0 - reflected - transmitted;
+
+        if current.bounces < bounce_limit {
+            let reflected_weight = vec_scale(current.weight, reflected);
+            if max_component(reflected_weight) > MIN_BOUNCE_WEIGHT {
+                stack.push(BounceRay {
+                    ray : Ray { pos : vec_add(p, facing, BOUNCE_BIAS), dir : reflect(dir, facing) },
+                    weight : reflected_weight, bounces : current.bounces + 1, inside : false,
+                });
+            }
+
+            // colored objects tint the light that goes through them
+            let transmitted_weight = vec_scale(mul(current.weight, clr), transmitted);
+            match refract(dir, facing, 1.0 / material.ior) {
+                Some(refracted) if max_component(transmitted_weight) > MIN_BOUNCE_WEIGHT => stack.push(BounceRay {
+                    ray : Ray { pos : vec_add(p, facing, -BOUNCE_BIAS), dir : refracted },
+                    weight : transmitted_weight, bounces : current.bounces + 1, inside : true,
+                }),
+                _ => {},
+            }
+        } else {
+            surface = 1.0;
+        }
+
+        let shaded = vec_scale(shade(object_handeler, clr, &material, norm, p, dir), surface * ao);
+        result = vec_add(result, mul(current.weight, shaded), 1.0);
+    }
+
+    result
+}
+
+// the shader output is written to the window as is, without any srgb conversion
+fn to_u8(x : f32) -> u8 {
+    (x.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// renders the scene to rgb8 pixels, row by row starting at the top left corner
+pub fn render(object_handeler : &ObjectHandeler, camera : &Camera, width : u32, height : u32) -> Result<Vec<u8>, RenderError> {
+    // an empty image would make chunks_mut panic, and a huge one would overflow the number of bytes
+    let row_len = (width as usize).checked_mul(3);
+    let len = row_len.and_then(|row_len| row_len.checked_mul(height as usize)).filter(|len| *len > 0);
+    let (Some(row_len), Some(len)) = (row_len, len) else {
+        return Err(RenderError::Size { width, height });
+    };
+    let mut pixels = vec![0u8; len];
+
+    // split the rows between threads, each row is independent of the others
+    let num_threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let rows_per_thread = (height as usize).div_ceil(num_threads).max(1);
+
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk) in pixels.chunks_mut(rows_per_thread * row_len).enumerate() {
+            scope.spawn(move || {
+                for (row_in_chunk, row) in chunk.chunks_mut(row_len).enumerate() {
+                    let y = (chunk_index * rows_per_thread + row_in_chunk) as f32;
+
+                    for x in 0..width as usize {
+                        // same as gl_FragCoord, which is the pixel center with y pointing up
+                        let frag_coord = [x as f32 + 0.5, height as f32 - y - 0.5];
+                        let uv = [2.0 * frag_coord[0] / width as f32 - 1.0, 2.0 * frag_coord[1] / height as f32 - 1.0];
+
+                        let color = march(object_handeler, get_ray(camera, uv), MAX_DEPTH);
+
+                        for i in 0..3 {
+                            row[x * 3 + i] = to_u8(color[i]);
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    Ok(pixels)
+}
+
+pub fn render_to_png(object_handeler : &ObjectHandeler, camera : &Camera, width : u32, height : u32, path : impl AsRef<Path>) -> Result<(), RenderError> {
+    let pixels = render(object_handeler, camera, width, height)?;
+    image::save_buffer(path, &pixels, width, height, image::ColorType::Rgb8).map_err(RenderError::Image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_handler::SceneObject;
+    use crate::shapes::{Cube, Sphere};
+    use crate::vec_util::get_rotation_quaternion;
+
+    const WIDTH : u32 = 32;
+    const HEIGHT : u32 = 24;
+
+    // the largest difference of a single channel that still counts as the same image
+    const TOLERANCE : u8 = 4;
+
+    fn golden_path() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/sphere_and_cube.png")
+    }
+
+    // a red sphere and a rotated blue cube standing on the ground in front of the camera
+    fn fixed_scene() -> (ObjectHandeler, Camera) {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut cube = Cube::new([1.2, 0.5, 5.0], [0.5; 3], [0.2, 0.3, 1.0]);
+        cube.transform.rotation = get_rotation_quaternion([0.0, 1.0, 0.0], 0.6);
+        object_handeler.add_object(SceneObject::Sphere(Sphere::new([-0.8, 1.0, 4.0], [1.0, 0.2, 0.2], 1.0)));
+        object_handeler.add_object(SceneObject::Cube(cube));
+
+        let mut camera = Camera::new();
+        camera.pos = [0.0, 1.5, 0.0];
+        (object_handeler, camera)
+    }
+
+    // run with UPDATE_GOLDEN=1 to write the current render as the golden image, after a change that is meant to change it
+    #[test]
+    fn render_matches_golden_image() {
+        let (object_handeler, camera) = fixed_scene();
+        let pixels = render(&object_handeler, &camera, WIDTH, HEIGHT).unwrap();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            image::save_buffer(golden_path(), &pixels, WIDTH, HEIGHT, image::ColorType::Rgb8).expect("failed to write the golden image");
+            return;
+        }
+
+        let golden = image::open(golden_path()).expect("failed to open the golden image").to_rgb8();
+        assert_eq!(golden.dimensions(), (WIDTH, HEIGHT));
+
+        for (i, (actual, expected)) in pixels.iter().zip(golden.as_raw()).enumerate() {
+            let (x, y) = ((i / 3) as u32 % WIDTH, (i / 3) as u32 / WIDTH);
+            assert!(actual.abs_diff(*expected) <= TOLERANCE, "pixel ({}, {}) channel {} is {}, expected {}", x, y, i % 3, actual, expected);
+        }
+    }
+
+    #[test]
+    fn render_rejects_sizes_without_pixels_or_too_many() {
+        let (object_handeler, camera) = fixed_scene();
+        for (width, height) in [(0, HEIGHT), (WIDTH, 0), (u32::MAX, u32::MAX)] {
+            let result = render(&object_handeler, &camera, width, height);
+            assert!(matches!(result, Err(RenderError::Size { .. })), "{}x{} rendered", width, height);
+        }
+    }
+}
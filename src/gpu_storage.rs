@@ -0,0 +1,743 @@
+// the objects of the scene as shader storage buffers, one buffer per type of object
+// the structs here have to match the ones with the same name in shaders/fragment.glsl, GpuLight is in shaders/lighting.glsl
+// and the ones of the sky are in shaders/sky.glsl. The texture images are a texture array, see shaders/texture.glsl,
+// and the sky images are float textures
+
+use std::{fmt, ops::Range};
+use glium::{backend::Facade, buffer::BufferCreationError, implement_uniform_block, uniforms::{UniformBlock, UniformBuffer}};
+use glium::texture::{MipmapsOption, RawImage2d, Texture2d, Texture2dArray, TextureCreationError, UncompressedFloatFormat};
+
+use crate::bvh::{BvhContent, BvhNode};
+use crate::light::Light;
+use crate::material::{LibraryMaterial, Material};
+use crate::object_handler::{ObjectHandeler, ObjectId, ObjectKind};
+use crate::sdf::triangle_normal;
+use crate::shapes::{Capsule, Combine, Cone, Cube, Cylinder, MengerSponge, Mesh, Sphere, Torus, Triangle};
+use crate::texture::{Texture, TextureImage};
+use crate::sky::{SkyImage, SkyImages, SkyKind};
+
+// the smallest GL_MAX_SHADER_STORAGE_BLOCK_SIZE that an OpenGL 4.3 driver is allowed to have,
+// glium does not let us ask the driver for the real value so this is used as the limit for every buffer
+pub const MAX_STORAGE_BLOCK_SIZE : usize = 1 << 24;
+
+// every field is a vec4, so that the layout is the same in rust and in std430
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuSphere {
+    pos : [f32; 4],
+    color : [f32; 4],
+    radius : [f32; 4],
+    rotation : [f32; 4],
+    scale : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuTriangle {
+    v1 : [f32; 4],
+    v2 : [f32; 4],
+    v3 : [f32; 4],
+    norm : [f32; 4],
+    color : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuCube {
+    pos : [f32; 4],
+    dim : [f32; 4],
+    color : [f32; 4],
+    rotation : [f32; 4],
+    scale : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuMengerSponge {
+    pos : [f32; 4],
+    iterations : [f32; 4],
+    color : [f32; 4],
+    rotation : [f32; 4],
+    scale : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+// triangles is (first triangle in the mesh triangle buffer, number of triangles)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuMesh {
+    pos : [f32; 4],
+    scale : [f32; 4],
+    color : [f32; 4],
+    triangles : [i32; 4],
+    rotation : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+    bounds_min : [f32; 4],
+    bounds_max : [f32; 4],
+}
+
+// size is (radius, thickness)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuTorus {
+    pos : [f32; 4],
+    size : [f32; 4],
+    color : [f32; 4],
+    rotation : [f32; 4],
+    scale : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+// size is (half height, radius)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuCapsule {
+    pos : [f32; 4],
+    size : [f32; 4],
+    color : [f32; 4],
+    rotation : [f32; 4],
+    scale : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+// size is (half height, radius)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuCylinder {
+    pos : [f32; 4],
+    size : [f32; 4],
+    color : [f32; 4],
+    rotation : [f32; 4],
+    scale : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+// size is (half height, bottom radius, top radius)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuCone {
+    pos : [f32; 4],
+    size : [f32; 4],
+    color : [f32; 4],
+    rotation : [f32; 4],
+    scale : [f32; 4],
+    combine : [f32; 4],
+    material : [i32; 4],
+    texture : [f32; 4],
+}
+
+// data is (type of the object or -1 for a node with children, index of the object, left child, right child)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuBvhNode {
+    bounds_min : [f32; 4],
+    bounds_max : [f32; 4],
+    data : [i32; 4],
+}
+
+// id is (type of the object, index of the object, 0, 0), the buffer has every object in list order
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuObjectId {
+    id : [i32; 4],
+}
+
+// data is (intensity, range, cosine of the spot angle, cosine of the angle where the spot has its full intensity)
+// kind is (the number of the LightKind, 0, 0, 0)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuLight {
+    pos : [f32; 4],
+    dir : [f32; 4],
+    color : [f32; 4],
+    data : [f32; 4],
+    kind : [i32; 4],
+}
+
+// a material of the library, the objects have its index in material.x
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuMaterial {
+    optics : [f32; 4],
+    surface : [f32; 4],
+}
+
+implement_uniform_block!(GpuSphere, pos, color, radius, rotation, scale, combine, material, texture);
+implement_uniform_block!(GpuTriangle, v1, v2, v3, norm, color, combine, material, texture);
+implement_uniform_block!(GpuCube, pos, dim, color, rotation, scale, combine, material, texture);
+implement_uniform_block!(GpuMengerSponge, pos, iterations, color, rotation, scale, combine, material, texture);
+implement_uniform_block!(GpuMesh, pos, scale, color, triangles, rotation, combine, material, texture, bounds_min, bounds_max);
+implement_uniform_block!(GpuTorus, pos, size, color, rotation, scale, combine, material, texture);
+implement_uniform_block!(GpuCapsule, pos, size, color, rotation, scale, combine, material, texture);
+implement_uniform_block!(GpuCylinder, pos, size, color, rotation, scale, combine, material, texture);
+implement_uniform_block!(GpuCone, pos, size, color, rotation, scale, combine, material, texture);
+implement_uniform_block!(GpuBvhNode, bounds_min, bounds_max, data);
+implement_uniform_block!(GpuObjectId, id);
+implement_uniform_block!(GpuLight, pos, dir, color, data, kind);
+implement_uniform_block!(GpuMaterial, optics, surface);
+
+fn vec4(v : [f32; 3]) -> [f32; 4] {
+    [v[0], v[1], v[2], 0.0]
+}
+
+// (operation, blend radius)
+fn gpu_combine(combine : &Combine) -> [f32; 4] {
+    [combine.operation as i32 as f32, combine.blend_radius, 0.0, 0.0]
+}
+
+// (reflectivity, transparency, index of refraction) and (roughness, metallic, specular, emissive), "Material" in the shader
+fn gpu_material(material : &Material) -> ([f32; 4], [f32; 4]) {
+    (
+        [material.reflectivity, material.transparency, material.ior, 0.0],
+        [material.roughness, material.metallic, material.specular, material.emissive],
+    )
+}
+
+// (pattern, image, scale, sharpness), "ObjectTexture" in the shader
+fn gpu_texture(texture : &Texture) -> [f32; 4] {
+    [texture.pattern as i32 as f32, texture.image as f32, texture.scale, texture.sharpness]
+}
+
+impl From<&Sphere> for GpuSphere {
+    fn from(sphere : &Sphere) -> Self {
+        GpuSphere {
+            pos : vec4(sphere.pos),
+            color : vec4(sphere.color),
+            radius : [sphere.radius, 0.0, 0.0, 0.0],
+            rotation : sphere.transform.rotation,
+            scale : vec4(sphere.transform.scale),
+            combine : gpu_combine(&sphere.combine),
+            material : [sphere.material as i32, 0, 0, 0],
+            texture : gpu_texture(&sphere.texture),
+        }
+    }
+}
+
+impl From<&Triangle> for GpuTriangle {
+    fn from(triangle : &Triangle) -> Self {
+        GpuTriangle {
+            v1 : vec4(triangle.v1),
+            v2 : vec4(triangle.v2),
+            v3 : vec4(triangle.v3),
+            norm : vec4(triangle_normal(triangle)),
+            color : vec4(triangle.color),
+            combine : gpu_combine(&triangle.combine),
+            material : [triangle.material as i32, 0, 0, 0],
+            texture : gpu_texture(&triangle.texture),
+        }
+    }
+}
+
+impl From<&Cube> for GpuCube {
+    fn from(cube : &Cube) -> Self {
+        GpuCube {
+            pos : vec4(cube.pos),
+            dim : vec4(cube.dim),
+            color : vec4(cube.color),
+            rotation : cube.transform.rotation,
+            scale : vec4(cube.transform.scale),
+            combine : gpu_combine(&cube.combine),
+            material : [cube.material as i32, 0, 0, 0],
+            texture : gpu_texture(&cube.texture),
+        }
+    }
+}
+
+impl From<&MengerSponge> for GpuMengerSponge {
+    fn from(menger_sponge : &MengerSponge) -> Self {
+        GpuMengerSponge {
+            pos : vec4(menger_sponge.pos),
+            iterations : [menger_sponge.iterations, 0.0, 0.0, 0.0],
+            color : vec4(menger_sponge.color),
+            rotation : menger_sponge.transform.rotation,
+            scale : vec4(menger_sponge.transform.scale),
+            combine : gpu_combine(&menger_sponge.combine),
+            material : [menger_sponge.material as i32, 0, 0, 0],
+            texture : gpu_texture(&menger_sponge.texture),
+        }
+    }
+}
+
+impl From<&Mesh> for GpuMesh {
+    fn from(mesh : &Mesh) -> Self {
+        GpuMesh {
+            pos : vec4(mesh.pos),
+            scale : vec4(mesh.transform.scale),
+            color : vec4(mesh.color),
+            triangles : [mesh.first_triangle as i32, mesh.num_triangles as i32, 0, 0],
+            rotation : mesh.transform.rotation,
+            combine : gpu_combine(&mesh.combine),
+            material : [mesh.material as i32, 0, 0, 0],
+            texture : gpu_texture(&mesh.texture),
+            bounds_min : vec4(mesh.bounds_min),
+            bounds_max : vec4(mesh.bounds_max),
+        }
+    }
+}
+
+impl From<&Torus> for GpuTorus {
+    fn from(torus : &Torus) -> Self {
+        GpuTorus {
+            pos : vec4(torus.pos),
+            size : [torus.radius, torus.thickness, 0.0, 0.0],
+            color : vec4(torus.color),
+            rotation : torus.transform.rotation,
+            scale : vec4(torus.transform.scale),
+            combine : gpu_combine(&torus.combine),
+            material : [torus.material as i32, 0, 0, 0],
+            texture : gpu_texture(&torus.texture),
+        }
+    }
+}
+
+impl From<&Capsule> for GpuCapsule {
+    fn from(capsule : &Capsule) -> Self {
+        GpuCapsule {
+            pos : vec4(capsule.pos),
+            size : [capsule.half_height, capsule.radius, 0.0, 0.0],
+            color : vec4(capsule.color),
+            rotation : capsule.transform.rotation,
+            scale : vec4(capsule.transform.scale),
+            combine : gpu_combine(&capsule.combine),
+            material : [capsule.material as i32, 0, 0, 0],
+            texture : gpu_texture(&capsule.texture),
+        }
+    }
+}
+
+impl From<&Cylinder> for GpuCylinder {
+    fn from(cylinder : &Cylinder) -> Self {
+        GpuCylinder {
+            pos : vec4(cylinder.pos),
+            size : [cylinder.half_height, cylinder.radius, 0.0, 0.0],
+            color : vec4(cylinder.color),
+            rotation : cylinder.transform.rotation,
+            scale : vec4(cylinder.transform.scale),
+            combine : gpu_combine(&cylinder.combine),
+            material : [cylinder.material as i32, 0, 0, 0],
+            texture : gpu_texture(&cylinder.texture),
+        }
+    }
+}
+
+impl From<&Cone> for GpuCone {
+    fn from(cone : &Cone) -> Self {
+        GpuCone {
+            pos : vec4(cone.pos),
+            size : [cone.half_height, cone.bottom_radius, cone.top_radius, 0.0],
+            color : vec4(cone.color),
+            rotation : cone.transform.rotation,
+            scale : vec4(cone.transform.scale),
+            combine : gpu_combine(&cone.combine),
+            material : [cone.material as i32, 0, 0, 0],
+            texture : gpu_texture(&cone.texture),
+        }
+    }
+}
+
+impl From<&BvhNode> for GpuBvhNode {
+    fn from(node : &BvhNode) -> Self {
+        let data = match node.content {
+            BvhContent::Object(id) => [id.kind as i32, id.index as i32, 0, 0],
+            BvhContent::Children(left, right) => [-1, 0, left as i32, right as i32],
+        };
+        GpuBvhNode { bounds_min : vec4(node.bounds.min), bounds_max : vec4(node.bounds.max), data }
+    }
+}
+
+impl From<&ObjectId> for GpuObjectId {
+    fn from(id : &ObjectId) -> Self {
+        GpuObjectId { id : [id.kind as i32, id.index as i32, 0, 0] }
+    }
+}
+
+impl From<&LibraryMaterial> for GpuMaterial {
+    fn from(material : &LibraryMaterial) -> Self {
+        let (optics, surface) = gpu_material(&material.material);
+        GpuMaterial { optics, surface }
+    }
+}
+
+impl From<&Light> for GpuLight {
+    fn from(light : &Light) -> Self {
+        let (spot_cos, full_spot_cos) = light.spot_cos();
+        GpuLight {
+            pos : vec4(light.pos),
+            dir : vec4(light.direction()),
+            color : vec4(light.color),
+            data : [light.intensity, light.range, spot_cos, full_spot_cos],
+            kind : [light.kind as i32, 0, 0, 0],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GpuStorageError {
+    TooManyObjects { name : &'static str, count : usize, max : usize },
+    Creation { name : &'static str, err : BufferCreationError },
+    Textures(TextureCreationError),
+    Sky(TextureCreationError),
+}
+
+impl fmt::Display for GpuStorageError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuStorageError::TooManyObjects { name, count, max } => write!(f, "too many {}: {} do not fit on the gpu (at most {})", name, count, max),
+            GpuStorageError::Creation { name, err } => write!(f, "could not create the gpu buffer for the {}: {}", name, err),
+            GpuStorageError::Textures(err) => write!(f, "could not create the texture array for the texture images: {}", err),
+            GpuStorageError::Sky(err) => write!(f, "could not create the textures for the sky images: {}", err),
+        }
+    }
+}
+
+// a storage buffer with room for more objects than it holds, so that adding objects does not always need a new buffer
+pub struct GpuBuffer<T : Copy + 'static> {
+    // what is in the buffer, for error messages
+    name : &'static str,
+    buffer : UniformBuffer<[T]>,
+    len : usize,
+}
+
+impl<T> GpuBuffer<T> where T : Copy + Default + UniformBlock + 'static {
+
+    fn new<F : ?Sized + Facade>(facade : &F, name : &'static str, objects : &[T]) -> Result<GpuBuffer<T>, GpuStorageError> {
+        let max = max_objects::<T>();
+        if objects.len() > max {
+            return Err(GpuStorageError::TooManyObjects { name, count : objects.len(), max });
+        }
+
+        // an empty buffer can not be bound, so there is always room for at least one object
+        let capacity = objects.len().max(1).next_power_of_two().min(max);
+        let buffer : UniformBuffer<[T]> = UniformBuffer::empty_unsized_dynamic(facade, capacity * std::mem::size_of::<T>())
+            .map_err(|err| GpuStorageError::Creation { name, err })?;
+
+        // the part after the objects is never read by the shader, but it should not be garbage
+        let mut content = objects.to_vec();
+        content.resize(capacity, T::default());
+        buffer.write(&content[..]);
+
+        Ok(GpuBuffer { name, buffer, len : objects.len() })
+    }
+
+    // writes the objects in the modified range, a new buffer is only created when the objects no longer fit
+    fn update<F, S>(&mut self, facade : &F, objects : &[S], modified : Option<Range<usize>>) -> Result<(), GpuStorageError>
+        where F : ?Sized + Facade, T : for<'a> From<&'a S>
+    {
+        if objects.len() > self.buffer.len() {
+            let objects : Vec<T> = objects.iter().map(T::from).collect();
+            *self = GpuBuffer::new(facade, self.name, &objects)?;
+            return Ok(());
+        }
+
+        if let Some(range) = modified {
+            let range = range.start.min(objects.len())..range.end.min(objects.len());
+
+            if !range.is_empty() {
+                let content : Vec<T> = objects[range.clone()].iter().map(T::from).collect();
+                self.buffer.slice(range).unwrap().write(&content[..]);
+            }
+        }
+
+        self.len = objects.len();
+        Ok(())
+    }
+
+    pub fn get_buffer(&self) -> &UniformBuffer<[T]> {
+        &self.buffer
+    }
+}
+
+// the scene as it is stored on the gpu
+pub struct GpuStorage {
+    pub spheres : GpuBuffer<GpuSphere>,
+    pub triangles : GpuBuffer<GpuTriangle>,
+    pub cubes : GpuBuffer<GpuCube>,
+    pub menger_sponges : GpuBuffer<GpuMengerSponge>,
+    pub meshes : GpuBuffer<GpuMesh>,
+    pub mesh_triangles : GpuBuffer<GpuTriangle>,
+    pub tori : GpuBuffer<GpuTorus>,
+    pub capsules : GpuBuffer<GpuCapsule>,
+    pub cylinders : GpuBuffer<GpuCylinder>,
+    pub cones : GpuBuffer<GpuCone>,
+    pub bvh_nodes : GpuBuffer<GpuBvhNode>,
+    pub object_order : GpuBuffer<GpuObjectId>,
+    pub lights : GpuBuffer<GpuLight>,
+    pub materials : GpuBuffer<GpuMaterial>,
+    // a layer for every texture image
+    pub texture_images : Texture2dArray,
+    num_of_texture_images : usize,
+    // the faces of the cubemap are the layers of an array, glium can not write to the faces of a cubemap texture
+    pub sky_cubemap : Texture2dArray,
+    pub sky_panorama : Texture2d,
+    // whether the textures have the images, they have a black pixel when they do not
+    has_cubemap : bool,
+    has_panorama : bool,
+}
+
+impl GpuStorage {
+
+    // uploads every object in the scene
+    pub fn new<F : ?Sized + Facade>(facade : &F, object_handeler : &mut ObjectHandeler) -> Result<GpuStorage, GpuStorageError> {
+        let spheres : Vec<GpuSphere> = object_handeler.get_spheres().iter().map(GpuSphere::from).collect();
+        let triangles : Vec<GpuTriangle> = object_handeler.get_triangles().iter().map(GpuTriangle::from).collect();
+        let cubes : Vec<GpuCube> = object_handeler.get_cubes().iter().map(GpuCube::from).collect();
+        let menger_sponges : Vec<GpuMengerSponge> = object_handeler.get_menger_sponges().iter().map(GpuMengerSponge::from).collect();
+        let meshes : Vec<GpuMesh> = object_handeler.get_meshes().iter().map(GpuMesh::from).collect();
+        let mesh_triangles : Vec<GpuTriangle> = object_handeler.get_mesh_triangles().iter().map(GpuTriangle::from).collect();
+        let tori : Vec<GpuTorus> = object_handeler.get_tori().iter().map(GpuTorus::from).collect();
+        let capsules : Vec<GpuCapsule> = object_handeler.get_capsules().iter().map(GpuCapsule::from).collect();
+        let cylinders : Vec<GpuCylinder> = object_handeler.get_cylinders().iter().map(GpuCylinder::from).collect();
+        let cones : Vec<GpuCone> = object_handeler.get_cones().iter().map(GpuCone::from).collect();
+        let object_order : Vec<GpuObjectId> = object_handeler.get_order().iter().map(GpuObjectId::from).collect();
+        let lights : Vec<GpuLight> = object_handeler.get_lights().iter().map(GpuLight::from).collect();
+        let materials : Vec<GpuMaterial> = object_handeler.get_materials().iter().map(GpuMaterial::from).collect();
+        let texture_images = gpu_textures(facade, object_handeler.get_textures())?;
+        let (sky_cubemap, sky_panorama) = gpu_sky(facade, object_handeler.get_sky_images())?;
+
+        object_handeler.update_bvh();
+        let bvh_nodes : Vec<GpuBvhNode> = object_handeler.get_bvh().unwrap().get_nodes().iter().map(GpuBvhNode::from).collect();
+
+        let storage = GpuStorage {
+            spheres : GpuBuffer::new(facade, "spheres", &spheres)?,
+            triangles : GpuBuffer::new(facade, "triangles", &triangles)?,
+            cubes : GpuBuffer::new(facade, "cubes", &cubes)?,
+            menger_sponges : GpuBuffer::new(facade, "menger sponges", &menger_sponges)?,
+            meshes : GpuBuffer::new(facade, "meshes", &meshes)?,
+            mesh_triangles : GpuBuffer::new(facade, "mesh triangles", &mesh_triangles)?,
+            tori : GpuBuffer::new(facade, "tori", &tori)?,
+            capsules : GpuBuffer::new(facade, "capsules", &capsules)?,
+            cylinders : GpuBuffer::new(facade, "cylinders", &cylinders)?,
+            cones : GpuBuffer::new(facade, "cones", &cones)?,
+            bvh_nodes : GpuBuffer::new(facade, "bvh nodes", &bvh_nodes)?,
+            object_order : GpuBuffer::new(facade, "object order", &object_order)?,
+            lights : GpuBuffer::new(facade, "lights", &lights)?,
+            materials : GpuBuffer::new(facade, "materials", &materials)?,
+            texture_images,
+            num_of_texture_images : object_handeler.get_textures().len(),
+            sky_cubemap,
+            sky_panorama,
+            has_cubemap : object_handeler.get_sky_images().has(SkyKind::Cubemap),
+            has_panorama : object_handeler.get_sky_images().has(SkyKind::Equirectangular),
+        };
+
+        for kind in ObjectKind::ALL {
+            object_handeler.clear_modified(kind);
+        }
+        object_handeler.clear_mesh_triangles_modified();
+        object_handeler.clear_bvh_modified();
+        object_handeler.clear_order_modified();
+        object_handeler.clear_lights_modified();
+        object_handeler.clear_materials_modified();
+        object_handeler.clear_textures_modified();
+        object_handeler.clear_sky_images_modified();
+
+        Ok(storage)
+    }
+
+    // writes the objects that changed since the last update, cheap when nothing has changed
+    // if a kind of object does not fit on the gpu its old buffer is kept, and it is tried again on the next update
+    pub fn update<F : ?Sized + Facade>(&mut self, facade : &F, object_handeler : &mut ObjectHandeler) -> Result<(), GpuStorageError> {
+        let mut result = Ok(());
+
+        for kind in ObjectKind::ALL {
+            let modified = object_handeler.get_modified(kind);
+
+            let kind_result = match kind {
+                ObjectKind::Sphere => self.spheres.update(facade, object_handeler.get_spheres(), modified),
+                ObjectKind::Triangle => self.triangles.update(facade, object_handeler.get_triangles(), modified),
+                ObjectKind::Cube => self.cubes.update(facade, object_handeler.get_cubes(), modified),
+                ObjectKind::MengerSponge => self.menger_sponges.update(facade, object_handeler.get_menger_sponges(), modified),
+                ObjectKind::Mesh => self.meshes.update(facade, object_handeler.get_meshes(), modified),
+                ObjectKind::Torus => self.tori.update(facade, object_handeler.get_tori(), modified),
+                ObjectKind::Capsule => self.capsules.update(facade, object_handeler.get_capsules(), modified),
+                ObjectKind::Cylinder => self.cylinders.update(facade, object_handeler.get_cylinders(), modified),
+                ObjectKind::Cone => self.cones.update(facade, object_handeler.get_cones(), modified),
+            };
+
+            match kind_result {
+                Ok(()) => object_handeler.clear_modified(kind),
+                Err(err) => result = Err(err),
+            }
+        }
+
+        let modified = object_handeler.get_mesh_triangles_modified();
+        match self.mesh_triangles.update(facade, object_handeler.get_mesh_triangles(), modified) {
+            Ok(()) => object_handeler.clear_mesh_triangles_modified(),
+            Err(err) => result = Err(err),
+        }
+
+        // the whole order is written when it changes, the shader leaves out the objects that are not in their buffers
+        if object_handeler.is_order_modified() {
+            let order = object_handeler.get_order();
+            match self.object_order.update(facade, order, Some(0..order.len())) {
+                Ok(()) => object_handeler.clear_order_modified(),
+                Err(err) => result = Err(err),
+            }
+        }
+
+        // the tree is only uploaded when it matches the objects on the gpu, without it the shader loops over every object
+        object_handeler.update_bvh();
+        if result.is_err() {
+            self.bvh_nodes.len = 0;
+        }
+        else if object_handeler.is_bvh_modified() || self.bvh_nodes.len == 0 {
+            let nodes = object_handeler.get_bvh().unwrap().get_nodes();
+
+            match self.bvh_nodes.update(facade, nodes, Some(0..nodes.len())) {
+                Ok(()) => object_handeler.clear_bvh_modified(),
+                Err(err) => {
+                    self.bvh_nodes.len = 0;
+                    result = Err(err);
+                },
+            }
+        }
+
+        // there are only a few lights, so all of them are written when one changes
+        if object_handeler.is_lights_modified() {
+            let lights = object_handeler.get_lights();
+            match self.lights.update(facade, lights, Some(0..lights.len())) {
+                Ok(()) => object_handeler.clear_lights_modified(),
+                Err(err) => result = Err(err),
+            }
+        }
+
+        if object_handeler.is_materials_modified() {
+            let materials = object_handeler.get_materials();
+            match self.materials.update(facade, materials, Some(0..materials.len())) {
+                Ok(()) => object_handeler.clear_materials_modified(),
+                Err(err) => result = Err(err),
+            }
+        }
+
+        // images are only added, and then all of them are written again.
+        // if the array can not be created trying again would fail every frame, so the old images are kept until the next image is added
+        if object_handeler.is_textures_modified() {
+            match gpu_textures(facade, object_handeler.get_textures()) {
+                Ok(texture_images) => {
+                    self.texture_images = texture_images;
+                    self.num_of_texture_images = object_handeler.get_textures().len();
+                },
+                Err(err) => result = Err(err),
+            }
+            object_handeler.clear_textures_modified();
+        }
+
+        // like the texture images, the old sky is kept until the next images are loaded when the textures can not be created
+        if object_handeler.is_sky_images_modified() {
+            match gpu_sky(facade, object_handeler.get_sky_images()) {
+                Ok((sky_cubemap, sky_panorama)) => {
+                    self.sky_cubemap = sky_cubemap;
+                    self.sky_panorama = sky_panorama;
+                    self.has_cubemap = object_handeler.get_sky_images().has(SkyKind::Cubemap);
+                    self.has_panorama = object_handeler.get_sky_images().has(SkyKind::Equirectangular);
+                },
+                Err(err) => result = Err(err),
+            }
+            object_handeler.clear_sky_images_modified();
+        }
+
+        result
+    }
+
+    // the number of objects in the buffers, this is what the shader should loop over
+    // it can differ from the object handeler if the last update failed
+    pub fn get_num_of(&self, kind : ObjectKind) -> usize {
+        match kind {
+            ObjectKind::Sphere => self.spheres.len,
+            ObjectKind::Triangle => self.triangles.len,
+            ObjectKind::Cube => self.cubes.len,
+            ObjectKind::MengerSponge => self.menger_sponges.len,
+            ObjectKind::Mesh => self.meshes.len,
+            ObjectKind::Torus => self.tori.len,
+            ObjectKind::Capsule => self.capsules.len,
+            ObjectKind::Cylinder => self.cylinders.len,
+            ObjectKind::Cone => self.cones.len,
+        }
+    }
+
+    // zero when the shader should not use the bvh
+    pub fn get_num_of_bvh_nodes(&self) -> usize {
+        self.bvh_nodes.len
+    }
+
+    // the objects in the list order on the gpu
+    pub fn get_num_of_objects(&self) -> usize {
+        self.object_order.len
+    }
+
+    pub fn get_num_of_lights(&self) -> usize {
+        self.lights.len
+    }
+
+    // the images in the texture array, objects with a higher image index are drawn without their texture
+    pub fn get_num_of_texture_images(&self) -> usize {
+        self.num_of_texture_images
+    }
+
+    // whether the sky textures have the images of the kind of sky, the gradient needs none
+    pub fn has_sky_images(&self, kind : SkyKind) -> bool {
+        match kind {
+            SkyKind::Gradient => true,
+            SkyKind::Cubemap => self.has_cubemap,
+            SkyKind::Equirectangular => self.has_panorama,
+        }
+    }
+}
+
+// every image is a layer of the array, they all have the size TextureImage::load scales them to.
+// the rows are uploaded from the top, so the top of an image is at t = 0
+fn gpu_textures<F : ?Sized + Facade>(facade : &F, textures : &[TextureImage]) -> Result<Texture2dArray, GpuStorageError> {
+    let mut layers : Vec<RawImage2d<u8>> = textures.iter()
+        .map(|texture| RawImage2d::from_raw_rgba(texture.pixels.iter().flat_map(|rgba| rgba.to_le_bytes()).collect(), (texture.width, texture.height)))
+        .collect();
+
+    // an array without layers can not be created, the shader does not read this one
+    if layers.is_empty() {
+        layers.push(RawImage2d::from_raw_rgba(vec![255; 4], (1, 1)));
+    }
+
+    // no mipmaps, the shader samples the full size image like the cpu renderer does
+    Texture2dArray::with_format(facade, layers, UncompressedFloatFormat::U8U8U8U8, MipmapsOption::NoMipmap).map_err(GpuStorageError::Textures)
+}
+
+// the cubemap faces as the layers of an array and the panorama, in half floats so that hdr images keep their bright parts.
+// like the texture images the rows are uploaded from the top
+fn gpu_sky<F : ?Sized + Facade>(facade : &F, images : &SkyImages) -> Result<(Texture2dArray, Texture2d), GpuStorageError> {
+    let raw_image = |image : &SkyImage| RawImage2d::from_raw_rgb(image.pixels.iter().flatten().copied().collect::<Vec<f32>>(), (image.width, image.height));
+    // textures without pixels can not be created, the shader does not read these
+    let black = || RawImage2d::from_raw_rgb(vec![0.0f32; 3], (1, 1));
+
+    let faces = match images.cubemap.is_empty() {
+        true => vec![black()],
+        false => images.cubemap.iter().map(raw_image).collect(),
+    };
+    let panorama = images.panorama.as_ref().map_or_else(black, raw_image);
+
+    let cubemap = Texture2dArray::with_format(facade, faces, UncompressedFloatFormat::F16F16F16, MipmapsOption::NoMipmap).map_err(GpuStorageError::Sky)?;
+    let panorama = Texture2d::with_format(facade, panorama, UncompressedFloatFormat::F16F16F16, MipmapsOption::NoMipmap).map_err(GpuStorageError::Sky)?;
+    Ok((cubemap, panorama))
+}
+
+// the most objects of type T that fit in one storage buffer
+fn max_objects<T>() -> usize {
+    MAX_STORAGE_BLOCK_SIZE / std::mem::size_of::<T>()
+}
@@ -0,0 +1,87 @@
+// the ground plane under the objects, the cpu version of shaders/ground.glsl
+// it is added to the objects with a union after all of them, so it is lit, shadowed and reflected like an object
+
+use serde::{Deserialize, Serialize};
+
+use crate::texture::{pattern_value, Pattern, TextureImage};
+use crate::vec_util::{cross, dot, length, normalize};
+
+// an infinite plane, a scene has at most one. It is not in the list of objects and can not be picked in the scene
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ground {
+    pub enabled : bool,
+    // the plane goes through normal * height
+    pub height : f32,
+    // points up out of the ground, does not have to be normalized
+    pub normal : [f32; 3],
+    // the patterns go from second_color to color, images are multiplied with color
+    pub color : [f32; 3],
+    pub second_color : [f32; 3],
+    // the index of the material in the library of the object handeler
+    pub material : usize,
+    pub pattern : Pattern,
+    // the index of the image in the object handeler, only used by Pattern::Image
+    pub image : usize,
+    // the width of a tile of the checker, the other patterns repeat over the same distance
+    pub tile_size : f32,
+    // how far from where a ray starts the ground has faded into the sky, 0 does not fade
+    pub fade_distance : f32,
+}
+
+impl Ground {
+    // looks like the floor before there was a ground, except that it is lit
+    pub const DEFAULT : Ground = Ground {
+        enabled : true, height : 0.0, normal : [0.0, 1.0, 0.0],
+        color : [0.6; 3], second_color : [0.8; 3], material : 0,
+        pattern : Pattern::Checker, image : 0, tile_size : 0.2, fade_distance : 15.0,
+    };
+
+    // normalized, straight up if normal is zero
+    pub fn up(&self) -> [f32; 3] {
+        if length(self.normal) == 0.0 {
+            return [0.0, 1.0, 0.0];
+        }
+        normalize(self.normal)
+    }
+
+    // "groundDist" in the shader, negative below the ground
+    pub fn dist(&self, pos : [f32; 3]) -> f32 {
+        dot(pos, self.up()) - self.height
+    }
+
+    // "groundUv" in the shader, the position along the ground in tiles. x and z for flat ground
+    fn uv(&self, pos : [f32; 3]) -> [f32; 2] {
+        let n = self.up();
+        let u = normalize(if n[2].abs() < 0.999 { cross(n, [0.0, 0.0, 1.0]) } else { cross(n, [1.0, 0.0, 0.0]) });
+        let v = cross(u, n);
+        [dot(pos, u) / self.tile_size, dot(pos, v) / self.tile_size]
+    }
+
+    // "groundColorAt" in the shader, the color with the pattern at pos
+    pub fn color_at(&self, images : &[TextureImage], pos : [f32; 3]) -> [f32; 3] {
+        let uv = self.uv(pos);
+        if self.pattern == Pattern::Image {
+            let image = images[self.image].color(uv);
+            return std::array::from_fn(|i| self.color[i] * image[i]);
+        }
+
+        let h = pattern_value(self.pattern, uv);
+        std::array::from_fn(|i| self.second_color[i] + (self.color[i] - self.second_color[i]) * h)
+    }
+
+    // "groundFade" in the shader, how much of the sky is seen instead of the ground after a ray has gone dist
+    pub fn fade(&self, dist : f32) -> f32 {
+        if self.fade_distance <= 0.0 {
+            return 0.0;
+        }
+        let t = (dist / self.fade_distance).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Default for Ground {
+    fn default() -> Self {
+        Ground::DEFAULT
+    }
+}
@@ -0,0 +1,448 @@
+use egui::{Color32, Id, LayerId, Order, Pos2, Rect, Stroke, Ui, Vec2};
+
+use crate::camera::Camera;
+use crate::cpu_renderer::{get_ray, project};
+use crate::object_handler::{ObjectHandeler, ObjectId, ObjectKind, SceneObject};
+use crate::shapes::{Capsule, Cone, Cube, Cylinder, MengerSponge, Mesh, Sphere, Torus, Transform};
+use crate::picking::{screen_to_uv, uv_to_screen};
+use crate::vec_util::{cross, dot, get_rotation_quaternion, length, q_mul, vec_add, vec_scale};
+
+const AXES : [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+const AXIS_COLORS : [Color32; 3] = [Color32::from_rgb(230, 60, 60), Color32::from_rgb(60, 200, 60), Color32::from_rgb(60, 110, 240)];
+const HOVER_COLOR : Color32 = Color32::from_rgb(255, 200, 0);
+
+const GIZMO_SIZE : f32 = 0.25; // length of the axes relative to the distance from the camera, so the gizmo keeps its size on screen
+const HANDLE_RADIUS : f32 = 8.0; // how close to a handle the pointer has to be to grab it, in pixels
+const PLANE_HANDLE_START : f32 = 0.3; // where the plane handles are along the axes, relative to the axis length
+const PLANE_HANDLE_END : f32 = 0.45;
+const SCALE_SENSITIVITY : f32 = 100.0; // pixels of mouse movement to double the size with the center and plane handles
+const RING_SEGMENTS : usize = 48; // the rings of the rotate mode are drawn as this many lines
+const ROTATION_SNAP : f32 = std::f32::consts::PI / 12.0; // the rotation snaps to 15 degrees, the grid size is for lengths
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GizmoMode {
+    Translate,
+    Scale,
+    Rotate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Handle {
+    Axis(usize),
+    Plane(usize), // the plane orthogonal to the axis
+    Center,       // uniform scaling
+    Ring(usize),  // rotation around the axis
+}
+
+// the object as it was when the drag started, all changes are relative to this
+#[derive(Clone, Copy)]
+struct Drag {
+    handle : Handle,
+    id : ObjectId,
+    start_pointer : Pos2,
+    start_pos : [f32; 3],
+    start_size : [f32; 3],
+    start_rotation : [f32; 4],
+    start_plane_hit : Option<[f32; 3]>,
+}
+
+// the gizmo projected to the screen
+struct ScreenGizmo {
+    origin : Pos2,
+    axis_len : f32, // in world units
+    ends : [Option<Pos2>; 3],
+    planes : [Option<[Pos2; 4]>; 3],
+    rings : [Option<Vec<Pos2>>; 3], // around the axes, in the planes orthogonal to them
+}
+
+pub struct Gizmo {
+    pub mode : GizmoMode,
+    pub snap : bool,
+    pub snap_step : f32,
+    drag : Option<Drag>,
+    hovered : Option<Handle>,
+}
+
+fn snap(x : f32, step : f32) -> f32 {
+    (x / step).round() * step
+}
+
+fn dist_to_segment(p : Pos2, a : Pos2, b : Pos2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_sq().max(1e-6)).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+fn dist_to_ring(p : Pos2, ring : &[Pos2]) -> f32 {
+    (0..ring.len()).map(|i| dist_to_segment(p, ring[i], ring[(i + 1) % ring.len()])).fold(f32::INFINITY, f32::min)
+}
+
+// the quad is convex, so the point is inside if it is on the same side of every edge
+fn inside_quad(p : Pos2, quad : &[Pos2; 4]) -> bool {
+    let sides : Vec<f32> = (0..4).map(|i| {
+        let a = quad[i];
+        let b = quad[(i + 1) % 4];
+        (b - a).x * (p - a).y - (b - a).y * (p - a).x
+    }).collect();
+
+    sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+}
+
+// the size that scaling changes, objects without a size can only be moved
+fn get_size(object_handeler : &ObjectHandeler, id : ObjectId) -> Option<[f32; 3]> {
+    match id.kind {
+        ObjectKind::Sphere => Some([object_handeler.get_spheres()[id.index].radius; 3]),
+        ObjectKind::Cube => Some(object_handeler.get_cubes()[id.index].dim),
+        // these have no size of their own, so their transform is scaled
+        ObjectKind::MengerSponge => Some(object_handeler.get_menger_sponges()[id.index].transform.scale),
+        ObjectKind::Mesh => Some(object_handeler.get_meshes()[id.index].transform.scale),
+        // the shapes around the y axis have one radius for x and z
+        ObjectKind::Torus => {
+            let torus = &object_handeler.get_tori()[id.index];
+            Some([torus.radius, torus.thickness, torus.radius])
+        },
+        ObjectKind::Capsule => {
+            let capsule = &object_handeler.get_capsules()[id.index];
+            Some([capsule.radius, capsule.half_height, capsule.radius])
+        },
+        ObjectKind::Cylinder => {
+            let cylinder = &object_handeler.get_cylinders()[id.index];
+            Some([cylinder.radius, cylinder.half_height, cylinder.radius])
+        },
+        ObjectKind::Cone => {
+            let cone = &object_handeler.get_cones()[id.index];
+            let radius = f32::max(cone.bottom_radius, cone.top_radius);
+            Some([radius, cone.half_height, radius])
+        },
+        _ => None
+    }
+}
+
+// the rotation that the rotate mode changes, triangles have no transform
+fn get_rotation(object_handeler : &ObjectHandeler, id : ObjectId) -> Option<[f32; 4]> {
+    let mut object = object_handeler.get_object(id);
+    object.transform_mut().map(|transform| transform.rotation)
+}
+
+fn set_rotation(object_handeler : &mut ObjectHandeler, id : ObjectId, rotation : [f32; 4]) {
+    let mut object = object_handeler.get_object(id);
+    if let Some(transform) = object.transform_mut() {
+        transform.rotation = rotation;
+        object_handeler.set_object(id, object);
+    }
+}
+
+fn set_size(object_handeler : &mut ObjectHandeler, id : ObjectId, size : [f32; 3]) {
+    let object = match object_handeler.get_object(id) {
+        SceneObject::Sphere(sphere) => SceneObject::Sphere(Sphere { radius : size[0], ..sphere }),
+        SceneObject::Cube(cube) => SceneObject::Cube(Cube { dim : size, ..cube }),
+        SceneObject::MengerSponge(menger_sponge) => SceneObject::MengerSponge(MengerSponge { transform : Transform { scale : size, ..menger_sponge.transform }, ..menger_sponge }),
+        SceneObject::Mesh(mesh) => SceneObject::Mesh(Mesh { transform : Transform { scale : size, ..mesh.transform }, ..mesh }),
+        SceneObject::Torus(torus) => SceneObject::Torus(Torus { radius : size[0], thickness : size[1], ..torus }),
+        SceneObject::Capsule(capsule) => SceneObject::Capsule(Capsule { radius : size[0], half_height : size[1], ..capsule }),
+        SceneObject::Cylinder(cylinder) => SceneObject::Cylinder(Cylinder { radius : size[0], half_height : size[1], ..cylinder }),
+        // both radii are scaled, so that the shape of the cone stays the same
+        SceneObject::Cone(cone) => {
+            let radius = f32::max(cone.bottom_radius, cone.top_radius);
+            let factor = if radius > 0.0 { size[0] / radius } else { 1.0 };
+            SceneObject::Cone(Cone { bottom_radius : cone.bottom_radius * factor, top_radius : cone.top_radius * factor, half_height : size[1], ..cone })
+        },
+        _ => return
+    };
+    object_handeler.set_object(id, object);
+}
+
+impl Gizmo {
+
+    pub fn new() -> Gizmo {
+        Gizmo {
+            mode : GizmoMode::Translate,
+            snap : false,
+            snap_step : 0.25,
+            drag : None,
+            hovered : None,
+        }
+    }
+
+    pub fn settings_ui(&mut self, ui : &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.mode, GizmoMode::Translate, "Move");
+            ui.selectable_value(&mut self.mode, GizmoMode::Scale, "Scale");
+            ui.selectable_value(&mut self.mode, GizmoMode::Rotate, "Rotate");
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.snap, "Snap to grid");
+            ui.add(egui::DragValue::new(&mut self.snap_step).speed(0.01).clamp_range(0.01..=10.0));
+        });
+    }
+
+    fn to_screen(screen_rect : Rect, camera : &Camera, pos : [f32; 3]) -> Option<Pos2> {
+        let uv = project(camera, pos)?;
+        let p = uv_to_screen(uv, [screen_rect.width(), screen_rect.height()]);
+        Some(screen_rect.min + Vec2::new(p[0], p[1]))
+    }
+
+    fn screen_gizmo(screen_rect : Rect, camera : &Camera, pos : [f32; 3]) -> Option<ScreenGizmo> {
+        let origin = Self::to_screen(screen_rect, camera, pos)?;
+        let axis_len = length(vec_add(pos, camera.pos, -1.0)) * GIZMO_SIZE;
+
+        let ends = AXES.map(|axis| Self::to_screen(screen_rect, camera, vec_add(pos, axis, axis_len)));
+
+        let planes = [0, 1, 2].map(|normal| {
+            let a = vec_scale(AXES[(normal + 1) % 3], axis_len);
+            let b = vec_scale(AXES[(normal + 2) % 3], axis_len);
+            let corner = |u : f32, v : f32| Self::to_screen(screen_rect, camera, vec_add(vec_add(pos, a, u), b, v));
+
+            Some([
+                corner(PLANE_HANDLE_START, PLANE_HANDLE_START)?,
+                corner(PLANE_HANDLE_END, PLANE_HANDLE_START)?,
+                corner(PLANE_HANDLE_END, PLANE_HANDLE_END)?,
+                corner(PLANE_HANDLE_START, PLANE_HANDLE_END)?,
+            ])
+        });
+
+        let rings = [0, 1, 2].map(|normal| {
+            let a = vec_scale(AXES[(normal + 1) % 3], axis_len);
+            let b = vec_scale(AXES[(normal + 2) % 3], axis_len);
+
+            (0..RING_SEGMENTS).map(|i| {
+                let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                Self::to_screen(screen_rect, camera, vec_add(vec_add(pos, a, angle.cos()), b, angle.sin()))
+            }).collect::<Option<Vec<Pos2>>>()
+        });
+
+        Some(ScreenGizmo { origin, axis_len, ends, planes, rings })
+    }
+
+    fn can_drag(&self, object_handeler : &ObjectHandeler, id : ObjectId) -> bool {
+        match self.mode {
+            GizmoMode::Translate => true,
+            GizmoMode::Scale => get_size(object_handeler, id).is_some(),
+            GizmoMode::Rotate => get_rotation(object_handeler, id).is_some(),
+        }
+    }
+
+    fn handle_at(&self, pointer : Pos2, gizmo : &ScreenGizmo) -> Option<Handle> {
+        // the rotate mode only has the rings
+        if self.mode == GizmoMode::Rotate {
+            return gizmo.rings.iter().enumerate()
+                .filter_map(|(axis, ring)| ring.as_ref().map(|ring| (axis, dist_to_ring(pointer, ring))))
+                .filter(|(_, dist)| *dist < HANDLE_RADIUS)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(axis, _)| Handle::Ring(axis));
+        }
+
+        if self.mode == GizmoMode::Scale && pointer.distance(gizmo.origin) < HANDLE_RADIUS * 1.5 {
+            return Some(Handle::Center);
+        }
+
+        for (normal, plane) in gizmo.planes.iter().enumerate() {
+            if plane.is_some_and(|quad| inside_quad(pointer, &quad)) {
+                return Some(Handle::Plane(normal));
+            }
+        }
+
+        gizmo.ends.iter().enumerate()
+            .filter_map(|(axis, end)| end.map(|end| (axis, dist_to_segment(pointer, gizmo.origin, end))))
+            .filter(|(_, dist)| *dist < HANDLE_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(axis, _)| Handle::Axis(axis))
+    }
+
+    // where the ray under the pointer hits the plane through pos that is orthogonal to the axis
+    fn plane_hit(screen_rect : Rect, camera : &Camera, pointer : Pos2, pos : [f32; 3], normal : usize) -> Option<[f32; 3]> {
+        let p = pointer - screen_rect.min;
+        let ray = get_ray(camera, screen_to_uv([p.x, p.y], [screen_rect.width(), screen_rect.height()]));
+        let n = AXES[normal];
+
+        let denom = dot(ray.dir, n);
+        if denom.abs() < 1e-6 {
+            return None;
+        }
+
+        let t = dot(vec_add(pos, ray.pos, -1.0), n) / denom;
+        if t < 0.0 { None } else { Some(vec_add(ray.pos, ray.dir, t)) }
+    }
+
+    // how far along the axis the pointer has been dragged, in world units
+    fn axis_movement(screen_rect : Rect, camera : &Camera, drag : &Drag, pointer : Pos2, axis : usize) -> f32 {
+        let Some(gizmo) = Self::screen_gizmo(screen_rect, camera, drag.start_pos) else { return 0.0 };
+        let Some(end) = gizmo.ends[axis] else { return 0.0 };
+
+        let axis_on_screen = end - gizmo.origin;
+        if axis_on_screen.length_sq() < 1e-6 {
+            return 0.0; // the axis points straight at the camera
+        }
+
+        (pointer - drag.start_pointer).dot(axis_on_screen) / axis_on_screen.length_sq() * gizmo.axis_len
+    }
+
+    fn update_drag(&self, drag : &Drag, pointer : Pos2, screen_rect : Rect, camera : &Camera, object_handeler : &mut ObjectHandeler) {
+        match self.mode {
+            GizmoMode::Translate => {
+                let mut pos = drag.start_pos;
+                let moved_axes = match drag.handle {
+                    Handle::Axis(axis) => {
+                        pos = vec_add(pos, AXES[axis], Self::axis_movement(screen_rect, camera, drag, pointer, axis));
+                        vec![axis]
+                    },
+                    Handle::Plane(normal) => {
+                        if let (Some(start), Some(hit)) = (drag.start_plane_hit, Self::plane_hit(screen_rect, camera, pointer, drag.start_pos, normal)) {
+                            pos = vec_add(pos, vec_add(hit, start, -1.0), 1.0);
+                        }
+                        vec![(normal + 1) % 3, (normal + 2) % 3]
+                    },
+                    Handle::Center | Handle::Ring(_) => vec![],
+                };
+
+                if self.snap {
+                    for axis in moved_axes {
+                        pos[axis] = snap(pos[axis], self.snap_step);
+                    }
+                }
+                object_handeler.set_object_pos(drag.id, pos);
+            },
+            GizmoMode::Scale => {
+                let vertical_factor = 1.0 - (pointer.y - drag.start_pointer.y) / SCALE_SENSITIVITY;
+
+                let scaled_axes = match drag.handle {
+                    Handle::Axis(axis) => {
+                        let axis_len = Self::screen_gizmo(screen_rect, camera, drag.start_pos).map_or(1.0, |gizmo| gizmo.axis_len);
+                        let factor = 1.0 + Self::axis_movement(screen_rect, camera, drag, pointer, axis) / axis_len;
+                        vec![(axis, factor)]
+                    },
+                    Handle::Plane(normal) => vec![((normal + 1) % 3, vertical_factor), ((normal + 2) % 3, vertical_factor)],
+                    Handle::Center => vec![(0, vertical_factor), (1, vertical_factor), (2, vertical_factor)],
+                    Handle::Ring(_) => vec![],
+                };
+
+                let mut size = drag.start_size;
+                for (axis, factor) in scaled_axes {
+                    size[axis] = drag.start_size[axis] * factor.max(0.01);
+                    if self.snap {
+                        size[axis] = snap(size[axis], self.snap_step).max(self.snap_step);
+                    }
+                }
+
+                // spheres can only be scaled uniformly, so use whichever axis was dragged
+                if drag.id.kind == ObjectKind::Sphere {
+                    let radius = size.iter().copied().find(|s| *s != drag.start_size[0]).unwrap_or(size[0]);
+                    size = [radius; 3];
+                }
+                if matches!(drag.id.kind, ObjectKind::Torus | ObjectKind::Capsule | ObjectKind::Cylinder | ObjectKind::Cone) {
+                    let radius = if size[2] != drag.start_size[2] { size[2] } else { size[0] };
+                    size[0] = radius;
+                    size[2] = radius;
+                }
+                set_size(object_handeler, drag.id, size);
+            },
+            GizmoMode::Rotate => {
+                // the angle between where the ring plane was grabbed and where the pointer is now, seen from the center
+                let Handle::Ring(axis) = drag.handle else { return };
+                let (Some(start), Some(hit)) = (drag.start_plane_hit, Self::plane_hit(screen_rect, camera, pointer, drag.start_pos, axis)) else { return };
+                let from = vec_add(start, drag.start_pos, -1.0);
+                let to = vec_add(hit, drag.start_pos, -1.0);
+
+                let mut angle = f32::atan2(dot(cross(from, to), AXES[axis]), dot(from, to));
+                if self.snap {
+                    angle = snap(angle, ROTATION_SNAP);
+                }
+                // around the world axis, after the rotation the object already had
+                set_rotation(object_handeler, drag.id, q_mul(get_rotation_quaternion(AXES[axis], angle), drag.start_rotation));
+            },
+        }
+    }
+
+    // handles dragging of the gizmo, returns true if the pointer is used by the gizmo so that it should not be used for anything else
+    pub fn interact(&mut self, ctx : &egui::Context, camera : &Camera, object_handeler : &mut ObjectHandeler) -> bool {
+        let selected = object_handeler.get_selected().filter(|id| self.can_drag(object_handeler, *id));
+        let Some(id) = selected else {
+            self.drag = None;
+            self.hovered = None;
+            return false;
+        };
+
+        let screen_rect = ctx.screen_rect();
+        let over_gui = ctx.is_pointer_over_area();
+        let (pointer, primary_down, primary_pressed) = ctx.input(|i| (i.pointer.hover_pos(), i.pointer.primary_down(), i.pointer.primary_pressed()));
+
+        if let Some(drag) = self.drag {
+            if primary_down {
+                if let Some(pointer) = pointer {
+                    self.update_drag(&drag, pointer, screen_rect, camera, object_handeler);
+                }
+            } else {
+                self.drag = None;
+            }
+            return true; // also when the drag ends, so that releasing the button does not count as a click
+        }
+
+        let gizmo = Self::screen_gizmo(screen_rect, camera, object_handeler.get_object_pos(id));
+        self.hovered = match (pointer, gizmo) {
+            (Some(pointer), Some(gizmo)) if !over_gui => self.handle_at(pointer, &gizmo),
+            _ => None
+        };
+
+        if let (Some(handle), Some(pointer), true) = (self.hovered, pointer, primary_pressed) {
+            let start_pos = object_handeler.get_object_pos(id);
+            self.drag = Some(Drag {
+                handle,
+                id,
+                start_pointer : pointer,
+                start_pos,
+                start_size : get_size(object_handeler, id).unwrap_or([1.0; 3]),
+                start_rotation : get_rotation(object_handeler, id).unwrap_or([1.0, 0.0, 0.0, 0.0]),
+                start_plane_hit : match handle {
+                    Handle::Plane(normal) | Handle::Ring(normal) => Self::plane_hit(screen_rect, camera, pointer, start_pos, normal),
+                    _ => None
+                },
+            });
+        }
+
+        self.hovered.is_some()
+    }
+
+    // draws the gizmo on top of the scene, should be called after the side panel so that it is not drawn over it
+    pub fn paint(&self, ctx : &egui::Context, camera : &Camera, object_handeler : &ObjectHandeler) {
+        let Some(id) = object_handeler.get_selected().filter(|id| self.can_drag(object_handeler, *id)) else { return };
+        let Some(gizmo) = Self::screen_gizmo(ctx.screen_rect(), camera, object_handeler.get_object_pos(id)) else { return };
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("gizmo"))).with_clip_rect(ctx.available_rect());
+
+        let active = self.drag.map(|drag| drag.handle).or(self.hovered);
+        let color = |handle : Handle, default : Color32| if active == Some(handle) { HOVER_COLOR } else { default };
+
+        if self.mode == GizmoMode::Rotate {
+            for (axis, ring) in gizmo.rings.iter().enumerate() {
+                if let Some(ring) = ring {
+                    painter.add(egui::Shape::closed_line(ring.clone(), Stroke::new(3.0, color(Handle::Ring(axis), AXIS_COLORS[axis]))));
+                }
+            }
+            return;
+        }
+
+        for (normal, plane) in gizmo.planes.iter().enumerate() {
+            if let Some(quad) = plane {
+                let fill = color(Handle::Plane(normal), AXIS_COLORS[normal].gamma_multiply(0.5));
+                painter.add(egui::Shape::convex_polygon(quad.to_vec(), fill, Stroke::new(1.0, AXIS_COLORS[normal])));
+            }
+        }
+
+        for (axis, end) in gizmo.ends.iter().enumerate() {
+            if let Some(end) = end {
+                let axis_color = color(Handle::Axis(axis), AXIS_COLORS[axis]);
+                painter.line_segment([gizmo.origin, *end], Stroke::new(3.0, axis_color));
+
+                if self.mode == GizmoMode::Translate {
+                    painter.circle_filled(*end, 6.0, axis_color);
+                } else {
+                    painter.rect_filled(Rect::from_center_size(*end, Vec2::splat(10.0)), 0.0, axis_color);
+                }
+            }
+        }
+
+        if self.mode == GizmoMode::Scale {
+            painter.circle_filled(gizmo.origin, HANDLE_RADIUS, color(Handle::Center, Color32::WHITE));
+        }
+    }
+}
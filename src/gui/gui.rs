@@ -5,7 +5,17 @@ use glium::glutin::surface::WindowSurface;
 use winit::window::Window;
 
 use crate::{specific_gui_functionality::*, Camera, ObjectHandeler};
+use crate::object_handler::{ObjectId, SceneObject};
+use crate::shapes::{Capsule, Combine, Cone, Cube, Cylinder, MengerSponge, Mesh, Operation, Sphere, Torus, Transform, Triangle};
+use crate::cpu_renderer::MAX_BOUNCES;
+use crate::material::LibraryMaterial;
+use crate::texture::{Pattern, Texture, TextureImage};
+use crate::vec_util::{quaternion_from_euler, quaternion_to_euler};
 use crate::input_handler::InputHandler;
+use crate::shader_handler::ShaderError;
+use crate::history::{EditCommand, History};
+use super::gizmo::Gizmo;
+use super::node_editor::NodeGraphGui;
 
 struct StateHandeler{
     pub create_object : bool,
@@ -23,6 +33,20 @@ pub struct GuiHandeler<'a>{
     egui_glium : EguiGlium,
     state_handeler : StateHandeler,
     create_object_gui : CreateRenderObjectGui<'a>,
+    scene_file_gui : SceneFileGui,
+    mesh_import_gui : MeshImportGui,
+    textures_gui : TexturesGui,
+    gizmo : Gizmo,
+    history : History,
+    history_gui : HistoryGui,
+    camera_bookmarks_gui : CameraBookmarksGui,
+    lights_gui : LightsGui,
+    materials_gui : MaterialsGui,
+    sky_gui : SkyGui,
+    ground_gui : GroundGui,
+    gpu_error : Option<String>,
+    node_graph_gui : NodeGraphGui,
+    shader_error_gui : ShaderErrorGui,
     mouse_handler : InputHandler
 }
 
@@ -34,6 +58,20 @@ impl GuiHandeler<'_>{
             egui_glium : egui_glium_src,
             state_handeler : StateHandeler::new(),
             create_object_gui: CreateRenderObjectGui::new(),
+            scene_file_gui : SceneFileGui::new(),
+            mesh_import_gui : MeshImportGui::new(),
+            textures_gui : TexturesGui::new(),
+            gizmo : Gizmo::new(),
+            history : History::new(),
+            history_gui : HistoryGui::new(),
+            camera_bookmarks_gui : CameraBookmarksGui::new(),
+            lights_gui : LightsGui::new(),
+            materials_gui : MaterialsGui::new(),
+            sky_gui : SkyGui::new(),
+            ground_gui : GroundGui::new(),
+            gpu_error : None,
+            node_graph_gui : NodeGraphGui::new(),
+            shader_error_gui : ShaderErrorGui::new(),
             mouse_handler : InputHandler::new()
         }
     }
@@ -42,156 +80,411 @@ impl GuiHandeler<'_>{
         self.egui_glium.paint(display, target);
     }
 
+    // shown until the scene can be uploaded to the gpu again
+    pub fn set_gpu_error(&mut self, error : Option<String>){
+        self.gpu_error = error;
+    }
+
+    // the shader files do not compile, shown in a window until they do
+    pub fn set_shader_error(&mut self, error : Option<ShaderError>){
+        self.shader_error_gui.set_error(error);
+    }
+
+    // the objectsDist of the node graph, when it has changed since the last frame
+    pub fn take_graph_min_dist(&mut self) -> Option<String>{
+        self.node_graph_gui.take_min_dist()
+    }
+
+    // the shader with the node graph could not be built
+    pub fn set_graph_error(&mut self, error : Option<String>){
+        self.node_graph_gui.set_error(error);
+    }
+
     pub fn get_responce(&mut self, window : &Window, event : &winit::event::WindowEvent) -> EventResponse{
         return self.egui_glium.on_event(&window, &event);
     }
 
-    pub fn update_gui(&mut self, window : &Window, object_handeler : &mut ObjectHandeler, should_update_objects : &mut bool, camera : &mut Camera){
-
-        *should_update_objects = false;
+    pub fn update_gui(&mut self, window : &Window, object_handeler : &mut ObjectHandeler, camera : &mut Camera){
 
         self.egui_glium.run(&window, |egui_ctx| {
 
-            self.mouse_handler.handle(egui_ctx, camera, object_handeler);
+            // ctrl+z and ctrl+shift+z
+            self.history_gui.shortcuts(egui_ctx, &mut self.history, object_handeler, camera);
+
+            // the gizmo gets the pointer first, so that dragging it does not select other objects
+            let selected_before = object_handeler.get_selected().map(|id| (id, object_handeler.get_object(id)));
+            let pointer_used = self.gizmo.interact(egui_ctx, camera, object_handeler);
+            if let Some((id, before)) = selected_before {
+                self.history.record_change(EditCommand::ChangeObject { id, before, after : object_handeler.get_object(id) }, true);
+            }
+
+            self.mouse_handler.handle(egui_ctx, camera, object_handeler, !pointer_used);
             
             egui::SidePanel::left("my_side_panel").show(egui_ctx, |ui| {
 
+                if let Some(error) = &self.gpu_error {
+                    ui.colored_label(egui::Color32::RED, format!("The scene shown is out of date, {}", error));
+                    ui.separator();
+                }
+
+                // saving and opening scene files
+                if self.scene_file_gui.show(ui, object_handeler, camera) {
+                    self.history.clear(); // the history refers to objects in the old scene
+                }
+
+                ui.add_space(15.0);
+                ui.separator();
+
+                // object picked in the scene
+                Self::selected_object(object_handeler, &mut self.history, ui);
+                self.gizmo.settings_ui(ui);
+
+                ui.add_space(15.0);
+                ui.separator();
+
                 // objects present in scene
-                Self::collapsing_objects_tree(object_handeler, should_update_objects, ui);
+                Self::collapsing_objects_tree(object_handeler, &mut self.history, ui);
 
                 // Adding space
                 ui.add_space(15.0);
                 ui.separator();
 
                 // gui to create object
-                self.create_object_gui.show(&mut self.state_handeler.create_object, ui, object_handeler, should_update_objects);
+                self.create_object_gui.show(&mut self.state_handeler.create_object, ui, object_handeler, &mut self.history);
+                self.mesh_import_gui.show(ui, object_handeler, &mut self.history);
+                self.textures_gui.show(ui, object_handeler);
+
+                ui.add_space(15.0);
+                ui.separator();
+
+                self.lights_gui.show(ui, object_handeler, &mut self.history);
+                self.materials_gui.show(ui, object_handeler, &mut self.history);
+                self.sky_gui.show(ui, object_handeler, &mut self.history);
+                self.ground_gui.show(ui, object_handeler, &mut self.history);
+                self.camera_bookmarks_gui.show(ui, object_handeler, camera, &mut self.history);
+                self.history_gui.show(ui, &mut self.history, object_handeler, camera);
+
+                ui.add_space(15.0);
+                ui.separator();
+
+                // these only change how the scene is drawn, so they are not part of the history
+                let mut use_bvh = object_handeler.get_use_bvh();
+                if ui.checkbox(&mut use_bvh, "Use BVH").changed() {
+                    object_handeler.set_use_bvh(use_bvh);
+                }
+
+                let mut use_shadows = object_handeler.get_use_shadows();
+                if ui.checkbox(&mut use_shadows, "Shadows").changed() {
+                    object_handeler.set_use_shadows(use_shadows);
+                }
+                if use_shadows {
+                    let mut shadow_softness = object_handeler.get_shadow_softness();
+                    ui.label("Shadow softness");
+                    if ui.add(egui::Slider::new(&mut shadow_softness, 0.0..=1.0).min_decimals(2)).changed() {
+                        object_handeler.set_shadow_softness(shadow_softness);
+                    }
+                }
+
+                let mut ambient_occlusion = object_handeler.get_ambient_occlusion();
+                ui.checkbox(&mut ambient_occlusion.enabled, "Ambient occlusion");
+                if ambient_occlusion.enabled {
+                    ui.label("Ambient occlusion strength");
+                    ui.add(egui::Slider::new(&mut ambient_occlusion.strength, 0.0..=3.0).min_decimals(2));
+                    ui.label("Ambient occlusion steps");
+                    ui.add(egui::Slider::new(&mut ambient_occlusion.steps, 1..=16));
+                    ui.checkbox(&mut ambient_occlusion.debug_view, "Show only ambient occlusion");
+                }
+                if ambient_occlusion != object_handeler.get_ambient_occlusion() {
+                    object_handeler.set_ambient_occlusion(ambient_occlusion);
+                }
+
+                let mut max_bounces = object_handeler.get_max_bounces();
+                ui.label("Max bounces");
+                if ui.add(egui::Slider::new(&mut max_bounces, 0..=MAX_BOUNCES)).changed() {
+                    object_handeler.set_max_bounces(max_bounces);
+                }
+
+                self.node_graph_gui.show_toggle(ui);
 
             });
-        });
-    }
 
+            self.node_graph_gui.show_window(egui_ctx);
+            self.shader_error_gui.show(egui_ctx);
 
-    fn collapsing_objects_tree(object_handeler : &mut ObjectHandeler, should_update_objects : &mut bool, ui : &mut Ui){
+            // a slider or gizmo drag has ended, so the next change is a new step in the history.
+            // a text field keeps its edit open until it loses focus, so that typing a name is one step
+            if !egui_ctx.input(|i| i.pointer.any_down()) && !egui_ctx.wants_keyboard_input() {
+                self.history.end_continuous_edit();
+            }
+            self.history.compact_mesh_triangles(object_handeler);
 
-        ui.collapsing("Spheres", |ui_inside| { 
-            let spheres = object_handeler.get_spheres_reference();
-            let mut id_counter = 0;
+            // drawn after the side panel, so that it stays in the part of the window that shows the scene
+            self.gizmo.paint(egui_ctx, camera, object_handeler);
+        });
+    }
 
-            for i in 0..spheres.len() {
-                let mut break_ = false;
-                ui_inside.collapsing(id_counter.to_string(), |ui_inside_inside|{
 
-                    {
-                        let sphere = spheres.get_mut(i).unwrap();
-                        ui_inside_inside.label("Radius");
-                        if ui_inside_inside.add(egui::widgets::Slider::new(&mut sphere.radius, RangeInclusive::new(0.0f32, 3.0f32)).min_decimals(2)).enabled(){
-                            *should_update_objects = true;
-                        };
-                        ui_inside_inside.label("Color");
-                        if egui::color_picker::color_edit_button_rgb(ui_inside_inside, &mut sphere.color).enabled(){
-                            *should_update_objects = true;
-                        };
-                        ui_inside_inside.label("Position X");
-                        ui_inside_inside.add(egui::Slider::new(&mut sphere.pos[0], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Position Y");
-                        ui_inside_inside.add(egui::Slider::new(&mut sphere.pos[1], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Position Z");
-                        ui_inside_inside.add(egui::Slider::new(&mut sphere.pos[2], -5.0..=5.0).min_decimals(1));
+    // properties of the object selected by clicking in the scene
+    fn selected_object(object_handeler : &mut ObjectHandeler, history : &mut History, ui : &mut Ui){
+        let Some(id) = object_handeler.get_selected() else {
+            ui.label("No object selected");
+            return;
+        };
 
-                    }
-                    
-                    if ui_inside_inside.button("Remove").clicked(){
-                        spheres.remove(i); // thus remove this sphere object
-                        break_ = true;
-                    }
-                });
+        ui.label(format!("Selected: {:?} {}", id.kind, id.index));
 
-                if break_{break;}
-                id_counter += 1;
+        Self::object_properties(object_handeler, id, history, ui);
 
+        ui.horizontal(|ui| {
+            if ui.button("Remove").clicked(){
+                Self::remove_object(object_handeler, id, history);
+            }
+            if ui.button("Deselect").clicked(){
+                object_handeler.set_selected(None);
             }
-            
         });
+    }
 
-        ui.collapsing("Cubes", |ui_inside| { 
-            let cubes = object_handeler.get_cubes_reference();
-            let mut id_counter = 0;
-
-            for i in 0..cubes.len() {
-                let mut break_ = false;
-                ui_inside.collapsing(id_counter.to_string(), |ui_inside_inside|{
-
-                    {
-                        let cube = cubes.get_mut(i).unwrap();
-                        ui_inside_inside.label("Color");
-                        if egui::color_picker::color_edit_button_rgb(ui_inside_inside, &mut cube.color).enabled(){
-                            *should_update_objects = true;
-                        };
-                        ui_inside_inside.label("Position X");
-                        ui_inside_inside.add(egui::Slider::new(&mut cube.pos[0], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Position Y");
-                        ui_inside_inside.add(egui::Slider::new(&mut cube.pos[1], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Position Z");
-                        ui_inside_inside.add(egui::Slider::new(&mut cube.pos[2], -5.0..=5.0).min_decimals(1));
-
-                        ui_inside_inside.label("Dim X");
-                        ui_inside_inside.add(egui::Slider::new(&mut cube.dim[0], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Dim Y");
-                        ui_inside_inside.add(egui::Slider::new(&mut cube.dim[1], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Dim Z");
-                        ui_inside_inside.add(egui::Slider::new(&mut cube.dim[2], -5.0..=5.0).min_decimals(1));
+    // every object in list order, which is the order their operations are applied in
+    fn collapsing_objects_tree(object_handeler : &mut ObjectHandeler, history : &mut History, ui : &mut Ui){
+        ui.label("Each object is combined with the objects above it in this list");
 
+        let mut removed = None;
+        let mut moved = None;
+        let order = object_handeler.get_order().clone();
+
+        for (position, id) in order.iter().copied().enumerate() {
+            ui.collapsing(format!("{:?} {}", id.kind, id.index), |ui_inside|{
+                Self::object_properties(object_handeler, id, history, ui_inside);
+
+                ui_inside.horizontal(|ui_inside| {
+                    if ui_inside.add_enabled(position > 0, egui::Button::new("Move up")).clicked(){
+                        moved = Some((id, position, position - 1));
                     }
-                    
-                    if ui_inside_inside.button("Remove").clicked(){
-                        cubes.remove(i); // thus remove this sphere object
-                        break_ = true;
+                    if ui_inside.add_enabled(position + 1 < order.len(), egui::Button::new("Move down")).clicked(){
+                        moved = Some((id, position, position + 1));
+                    }
+                    if ui_inside.button("Remove").clicked(){
+                        removed = Some(id); // thus remove this object
                     }
                 });
+            });
+        }
 
-                if break_{break;}
-                id_counter += 1;
+        if let Some((id, from, to)) = moved {
+            object_handeler.move_object(id, to);
+            history.push(EditCommand::MoveObject { id, from, to });
+        }
+        if let Some(id) = removed {
+            Self::remove_object(object_handeler, id, history);
+        }
+    }
 
-            }
-        });
+    fn remove_object(object_handeler : &mut ObjectHandeler, id : ObjectId, history : &mut History){
+        let position = object_handeler.order_position(id);
+        let object = object_handeler.remove_object(id);
+        history.push(EditCommand::RemoveObject { id, object, position });
+    }
 
-        
-        ui.collapsing("Menger sponges", |ui_inside| { 
-            let menger_sponges = object_handeler.get_menger_sponges_reference();
-            let mut id_counter = 0;
-
-            for i in 0..menger_sponges.len() {
-                let mut break_ = false;
-                ui_inside.collapsing(id_counter.to_string(), |ui_inside_inside|{
-
-                    {
-                        let menger_sponge = menger_sponges.get_mut(i).unwrap();
-                        ui_inside_inside.label("Color");
-                        if egui::color_picker::color_edit_button_rgb(ui_inside_inside, &mut menger_sponge.color).enabled(){
-                            *should_update_objects = true;
-                        };
-                        ui_inside_inside.label("Position X");
-                        ui_inside_inside.add(egui::Slider::new(&mut menger_sponge.pos[0], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Position Y");
-                        ui_inside_inside.add(egui::Slider::new(&mut menger_sponge.pos[1], -5.0..=5.0).min_decimals(1));
-                        ui_inside_inside.label("Position Z");
-                        ui_inside_inside.add(egui::Slider::new(&mut menger_sponge.pos[2], -5.0..=5.0).min_decimals(1));
-
-                        ui_inside_inside.label("Iterations");
-                        ui_inside_inside.add(egui::Slider::new(&mut menger_sponge.iterations, 1.0..=100.0));
+    // widgets for editing an object, changes are recorded in the history
+    fn object_properties(object_handeler : &mut ObjectHandeler, id : ObjectId, history : &mut History, ui : &mut Ui){
+        let before = object_handeler.get_object(id);
+        let mut object = before;
+
+        // the object is edited as a copy, so that only this object is written to the gpu
+        match &mut object {
+            SceneObject::Sphere(sphere) => Self::sphere_properties(sphere, ui),
+            SceneObject::Cube(cube) => Self::cube_properties(cube, ui),
+            SceneObject::Triangle(triangle) => Self::triangle_properties(triangle, ui),
+            SceneObject::MengerSponge(menger_sponge) => Self::menger_sponge_properties(menger_sponge, ui),
+            SceneObject::Mesh(mesh) => Self::mesh_properties(mesh, ui),
+            SceneObject::Torus(torus) => Self::torus_properties(torus, ui),
+            SceneObject::Capsule(capsule) => Self::capsule_properties(capsule, ui),
+            SceneObject::Cylinder(cylinder) => Self::cylinder_properties(cylinder, ui),
+            SceneObject::Cone(cone) => Self::cone_properties(cone, ui),
+        }
 
-                    }
-                    
-                    if ui_inside_inside.button("Remove").clicked(){
-                        menger_sponges.remove(i); // thus remove this sphere object
-                        break_ = true;
+        Self::combine_properties(object.combine_mut(), ui);
+        Self::material_select(object.material_mut(), object_handeler.get_materials(), ui);
+        Self::texture_properties(object.texture_mut(), object_handeler.get_textures(), ui);
+
+        if let Some(transform) = object.transform_mut() {
+            Self::transform_properties(transform, ui);
+        }
+
+        if object != before {
+            object_handeler.set_object(id, object);
+        }
+
+        // while a slider is dragged every frame changes the object, these are merged into one step
+        let continuous = ui.input(|i| i.pointer.any_down());
+        history.record_change(EditCommand::ChangeObject { id, before, after : object }, continuous);
+    }
+
+    fn vec3_sliders(name : &str, v : &mut [f32; 3], range : RangeInclusive<f32>, ui : &mut Ui){
+        for (i, axis) in ["X", "Y", "Z"].iter().enumerate() {
+            ui.label(format!("{} {}", name, axis));
+            ui.add(egui::Slider::new(&mut v[i], range.clone()).min_decimals(1));
+        }
+    }
+
+    fn combine_properties(combine : &mut Combine, ui : &mut Ui){
+        egui::ComboBox::from_label("Operation")
+            .selected_text(combine.operation.name())
+            .show_ui(ui, |ui| {
+                for operation in Operation::ALL {
+                    ui.selectable_value(&mut combine.operation, operation, operation.name());
+                }
+            });
+
+        if combine.operation.is_smooth() {
+            ui.label("Blend radius");
+            ui.add(egui::Slider::new(&mut combine.blend_radius, 0.01..=2.0).min_decimals(2));
+        }
+    }
+
+    // the materials themselves are edited in the library
+    fn material_select(material : &mut usize, materials : &[LibraryMaterial], ui : &mut Ui){
+        egui::ComboBox::from_label("Material")
+            .selected_text(&materials[*material].name)
+            .show_ui(ui, |ui| {
+                for (i, library_material) in materials.iter().enumerate() {
+                    ui.selectable_value(material, i, &library_material.name);
+                }
+            });
+    }
+
+    fn texture_properties(texture : &mut Texture, images : &[TextureImage], ui : &mut Ui){
+        egui::ComboBox::from_label("Texture")
+            .selected_text(texture.pattern.name())
+            .show_ui(ui, |ui| {
+                // an image texture needs an image to point to
+                for pattern in Pattern::ALL.into_iter().filter(|pattern| *pattern != Pattern::Image || !images.is_empty()) {
+                    ui.selectable_value(&mut texture.pattern, pattern, pattern.name());
+                }
+            });
+
+        if texture.pattern == Pattern::None {
+            return;
+        }
+
+        if texture.pattern == Pattern::Image {
+            egui::ComboBox::from_label("Image")
+                .selected_text(&images[texture.image].path)
+                .show_ui(ui, |ui| {
+                    for (i, image) in images.iter().enumerate() {
+                        ui.selectable_value(&mut texture.image, i, &image.path);
                     }
                 });
-                if break_{break;}
-                id_counter += 1;
+        }
 
-            }
-        });
+        ui.label("Texture scale");
+        ui.add(egui::Slider::new(&mut texture.scale, 0.1..=20.0).logarithmic(true));
+        ui.label("Texture sharpness");
+        ui.add(egui::Slider::new(&mut texture.sharpness, 1.0..=16.0));
+    }
+
+    // the rotation is edited as angles in degrees. it is only written back when a slider is moved,
+    // otherwise converting back and forth would change the object a tiny bit every frame
+    fn transform_properties(transform : &mut Transform, ui : &mut Ui){
+        let mut angles = quaternion_to_euler(transform.rotation).map(f32::to_degrees);
+        let mut rotated = false;
+
+        for (i, axis) in ["X", "Y", "Z"].iter().enumerate() {
+            // the y angle from quaternion_to_euler is always in this range
+            let range = if i == 1 { -90.0..=90.0 } else { -180.0..=180.0 };
+            ui.label(format!("Rotation {}", axis));
+            rotated |= ui.add(egui::Slider::new(&mut angles[i], range).suffix("°")).changed();
+        }
+        if rotated {
+            transform.rotation = quaternion_from_euler(angles.map(f32::to_radians));
+        }
+
+        for (i, axis) in ["X", "Y", "Z"].iter().enumerate() {
+            ui.label(format!("Scale {}", axis));
+            ui.add(egui::Slider::new(&mut transform.scale[i], 0.01..=10.0).logarithmic(true));
+        }
+    }
+
+    fn color_picker(color : &mut [f32; 3], ui : &mut Ui){
+        ui.label("Color");
+        egui::color_picker::color_edit_button_rgb(ui, color);
+    }
+
+    fn sphere_properties(sphere : &mut Sphere, ui : &mut Ui){
+        ui.label("Radius");
+        ui.add(egui::widgets::Slider::new(&mut sphere.radius, RangeInclusive::new(0.0f32, 3.0f32)).min_decimals(2));
+        Self::color_picker(&mut sphere.color, ui);
+        Self::vec3_sliders("Position", &mut sphere.pos, -5.0..=5.0, ui);
+    }
+
+    fn cube_properties(cube : &mut Cube, ui : &mut Ui){
+        Self::color_picker(&mut cube.color, ui);
+        Self::vec3_sliders("Position", &mut cube.pos, -5.0..=5.0, ui);
+        Self::vec3_sliders("Dim", &mut cube.dim, -5.0..=5.0, ui);
+    }
+
+    fn triangle_properties(triangle : &mut Triangle, ui : &mut Ui){
+        Self::color_picker(&mut triangle.color, ui);
+        Self::vec3_sliders("Vertex 1", &mut triangle.v1, -5.0..=5.0, ui);
+        Self::vec3_sliders("Vertex 2", &mut triangle.v2, -5.0..=5.0, ui);
+        Self::vec3_sliders("Vertex 3", &mut triangle.v3, -5.0..=5.0, ui);
+    }
+
+    fn menger_sponge_properties(menger_sponge : &mut MengerSponge, ui : &mut Ui){
+        Self::color_picker(&mut menger_sponge.color, ui);
+        Self::vec3_sliders("Position", &mut menger_sponge.pos, -5.0..=5.0, ui);
+
+        ui.label("Iterations");
+        ui.add(egui::Slider::new(&mut menger_sponge.iterations, 1.0..=100.0));
+    }
+
+    fn mesh_properties(mesh : &mut Mesh, ui : &mut Ui){
+        ui.label(format!("{} triangles", mesh.num_triangles));
+        Self::color_picker(&mut mesh.color, ui);
+        Self::vec3_sliders("Position", &mut mesh.pos, -5.0..=5.0, ui);
+    }
+
+    fn torus_properties(torus : &mut Torus, ui : &mut Ui){
+        Self::color_picker(&mut torus.color, ui);
+        Self::vec3_sliders("Position", &mut torus.pos, -5.0..=5.0, ui);
+
+        ui.label("Radius");
+        ui.add(egui::Slider::new(&mut torus.radius, 0.0..=3.0).min_decimals(2));
+        ui.label("Thickness");
+        ui.add(egui::Slider::new(&mut torus.thickness, 0.0..=3.0).min_decimals(2));
+    }
+
+    fn capsule_properties(capsule : &mut Capsule, ui : &mut Ui){
+        Self::color_picker(&mut capsule.color, ui);
+        Self::vec3_sliders("Position", &mut capsule.pos, -5.0..=5.0, ui);
+
+        ui.label("Half height");
+        ui.add(egui::Slider::new(&mut capsule.half_height, 0.0..=3.0).min_decimals(2));
+        ui.label("Radius");
+        ui.add(egui::Slider::new(&mut capsule.radius, 0.0..=3.0).min_decimals(2));
+    }
+
+    fn cylinder_properties(cylinder : &mut Cylinder, ui : &mut Ui){
+        Self::color_picker(&mut cylinder.color, ui);
+        Self::vec3_sliders("Position", &mut cylinder.pos, -5.0..=5.0, ui);
+
+        ui.label("Half height");
+        ui.add(egui::Slider::new(&mut cylinder.half_height, 0.0..=3.0).min_decimals(2));
+        ui.label("Radius");
+        ui.add(egui::Slider::new(&mut cylinder.radius, 0.0..=3.0).min_decimals(2));
+    }
+
+    fn cone_properties(cone : &mut Cone, ui : &mut Ui){
+        Self::color_picker(&mut cone.color, ui);
+        Self::vec3_sliders("Position", &mut cone.pos, -5.0..=5.0, ui);
+
+        ui.label("Half height");
+        ui.add(egui::Slider::new(&mut cone.half_height, 0.0..=3.0).min_decimals(2));
+        ui.label("Bottom radius");
+        ui.add(egui::Slider::new(&mut cone.bottom_radius, 0.0..=3.0).min_decimals(2));
+        ui.label("Top radius");
+        ui.add(egui::Slider::new(&mut cone.top_radius, 0.0..=3.0).min_decimals(2));
     }
 
 }
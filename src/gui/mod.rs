@@ -1,2 +1,4 @@
 pub mod gui;
-pub mod specific_gui_functionality;
\ No newline at end of file
+pub mod specific_gui_functionality;
+pub mod gizmo;
+pub mod node_editor;
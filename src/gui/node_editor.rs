@@ -0,0 +1,234 @@
+use egui::{Color32, Ui};
+use egui_snarl::ui::{PinInfo, SnarlStyle, SnarlViewer};
+use egui_snarl::{InPin, NodeId, OutPin, Snarl};
+
+use crate::node_graph::{self, SdfNode};
+use crate::shapes::Operation;
+
+const PIN_COLOR : Color32 = Color32::from_rgb(0x80, 0xb0, 0xff);
+
+pub struct NodeGraphGui{
+    graph : Snarl<SdfNode>,
+    open : bool,
+    // the connections or values changed, so the shader has to be compiled again
+    changed : bool,
+    error : Option<String>,
+}
+
+impl NodeGraphGui{
+
+    pub fn new() -> NodeGraphGui{
+        NodeGraphGui{
+            graph : node_graph::default_graph(),
+            open : false,
+            changed : false,
+            error : None,
+        }
+    }
+
+    // the checkbox in the side panel
+    pub fn show_toggle(&mut self, ui : &mut Ui){
+        ui.checkbox(&mut self.open, "Node graph editor");
+    }
+
+    pub fn show_window(&mut self, ctx : &egui::Context){
+        let graph = &mut self.graph;
+        let error = &self.error;
+        let mut viewer = SdfNodeViewer{ changed : false };
+
+        egui::Window::new("Node graph").open(&mut self.open).default_size([700.0, 400.0]).show(ctx, |ui| {
+            ui.label("Right click to add nodes, the node connected to the output is rendered");
+            if let Some(error) = error {
+                ui.colored_label(Color32::RED, error);
+            }
+            graph.show(&mut viewer, &SnarlStyle::new(), "sdf_node_graph", ui);
+        });
+
+        self.changed |= viewer.changed;
+    }
+
+    // the compiled objectsDist when the graph has changed since the last call, errors in the graph are shown in the window
+    pub fn take_min_dist(&mut self) -> Option<String>{
+        if !std::mem::take(&mut self.changed) {
+            return None;
+        }
+
+        match node_graph::compile(&self.graph) {
+            Ok(min_dist) => Some(min_dist),
+            Err(err) => {
+                self.error = Some(err.to_string());
+                None
+            }
+        }
+    }
+
+    // set when the shader with the compiled graph could not be built, the last working one is still used
+    pub fn set_error(&mut self, error : Option<String>){
+        self.error = error;
+    }
+}
+
+struct SdfNodeViewer{
+    changed : bool,
+}
+
+impl SdfNodeViewer{
+    fn vec3_edit(&mut self, ui : &mut Ui, label : &str, value : &mut [f32; 3]){
+        ui.horizontal(|ui| {
+            ui.label(label);
+            for x in value.iter_mut() {
+                self.changed |= ui.add(egui::DragValue::new(x).speed(0.01)).changed();
+            }
+        });
+    }
+
+    fn float_edit(&mut self, ui : &mut Ui, label : &str, value : &mut f32, range : std::ops::RangeInclusive<f32>){
+        ui.horizontal(|ui| {
+            ui.label(label);
+            self.changed |= ui.add(egui::DragValue::new(value).speed(0.01).clamp_range(range)).changed();
+        });
+    }
+
+    fn color_edit(&mut self, ui : &mut Ui, color : &mut [f32; 3]){
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            self.changed |= ui.color_edit_button_rgb(color).changed();
+        });
+    }
+}
+
+impl SnarlViewer<SdfNode> for SdfNodeViewer{
+    fn title(&mut self, node : &SdfNode) -> String{
+        node.name().to_string()
+    }
+
+    fn inputs(&mut self, node : &SdfNode) -> usize{
+        node.input_names().len()
+    }
+
+    fn outputs(&mut self, node : &SdfNode) -> usize{
+        node.num_of_outputs()
+    }
+
+    fn show_input(&mut self, pin : &InPin, ui : &mut Ui, _scale : f32, snarl : &mut Snarl<SdfNode>) -> PinInfo{
+        ui.label(snarl[pin.id.node].input_names()[pin.id.input]);
+        PinInfo::circle().with_fill(PIN_COLOR)
+    }
+
+    fn show_output(&mut self, _pin : &OutPin, ui : &mut Ui, _scale : f32, _snarl : &mut Snarl<SdfNode>) -> PinInfo{
+        ui.label("Distance");
+        PinInfo::circle().with_fill(PIN_COLOR)
+    }
+
+    fn input_color(&mut self, _pin : &InPin, _style : &egui::Style, _snarl : &mut Snarl<SdfNode>) -> Color32{
+        PIN_COLOR
+    }
+
+    fn output_color(&mut self, _pin : &OutPin, _style : &egui::Style, _snarl : &mut Snarl<SdfNode>) -> Color32{
+        PIN_COLOR
+    }
+
+    fn has_body(&mut self, node : &SdfNode) -> bool{
+        !matches!(node, SdfNode::Output | SdfNode::Scene)
+    }
+
+    fn show_body(&mut self, node : NodeId, _inputs : &[InPin], _outputs : &[OutPin], ui : &mut Ui, _scale : f32, snarl : &mut Snarl<SdfNode>){
+        ui.vertical(|ui| {
+            match &mut snarl[node] {
+                SdfNode::Output | SdfNode::Scene => {},
+                SdfNode::Sphere { radius, color } => {
+                    self.float_edit(ui, "Radius", radius, 0.01..=100.0);
+                    self.color_edit(ui, color);
+                },
+                SdfNode::Cube { size, color } => {
+                    self.vec3_edit(ui, "Size", size);
+                    self.color_edit(ui, color);
+                },
+                SdfNode::Torus { radius, thickness, color } => {
+                    self.float_edit(ui, "Radius", radius, 0.01..=100.0);
+                    self.float_edit(ui, "Thickness", thickness, 0.01..=100.0);
+                    self.color_edit(ui, color);
+                },
+                SdfNode::Capsule { half_height, radius, color } | SdfNode::Cylinder { half_height, radius, color } => {
+                    self.float_edit(ui, "Half height", half_height, 0.0..=100.0);
+                    self.float_edit(ui, "Radius", radius, 0.01..=100.0);
+                    self.color_edit(ui, color);
+                },
+                SdfNode::Translate { offset } => self.vec3_edit(ui, "Offset", offset),
+                SdfNode::Rotate { angles } => self.vec3_edit(ui, "Degrees", angles),
+                SdfNode::Scale { factor } => self.float_edit(ui, "Factor", factor, 0.01..=100.0),
+                SdfNode::Repeat { spacing } => {
+                    self.vec3_edit(ui, "Spacing", spacing);
+                    ui.label("0 is not repeated");
+                },
+                SdfNode::Mirror { axes } => {
+                    ui.horizontal(|ui| {
+                        for (axis, mirrored) in ["x", "y", "z"].iter().zip(axes.iter_mut()) {
+                            self.changed |= ui.checkbox(mirrored, *axis).changed();
+                        }
+                    });
+                },
+                SdfNode::Twist { amount } => self.float_edit(ui, "Amount", amount, -10.0..=10.0),
+                SdfNode::Round { radius } => self.float_edit(ui, "Radius", radius, 0.0..=10.0),
+                SdfNode::Combine(combine) => {
+                    egui::ComboBox::from_id_source(("combine", node))
+                        .selected_text(combine.operation.name())
+                        .show_ui(ui, |ui| {
+                            for operation in Operation::ALL {
+                                self.changed |= ui.selectable_value(&mut combine.operation, operation, operation.name()).changed();
+                            }
+                        });
+                    if combine.operation.is_smooth() {
+                        self.float_edit(ui, "Blend radius", &mut combine.blend_radius, 0.01..=2.0);
+                    }
+                },
+            }
+        });
+    }
+
+    fn graph_menu(&mut self, pos : egui::Pos2, ui : &mut Ui, _scale : f32, snarl : &mut Snarl<SdfNode>){
+        ui.label("Add node");
+        for node in SdfNode::addable() {
+            if ui.button(node.name()).clicked() {
+                snarl.insert_node(pos, node);
+                ui.close_menu();
+            }
+        }
+    }
+
+    fn node_menu(&mut self, node : NodeId, _inputs : &[InPin], _outputs : &[OutPin], ui : &mut Ui, _scale : f32, snarl : &mut Snarl<SdfNode>){
+        // without the output there is nothing to render
+        if snarl[node] == SdfNode::Output {
+            ui.label("The output can not be removed");
+            return;
+        }
+
+        if ui.button("Remove").clicked() {
+            snarl.remove_node(node);
+            self.changed = true;
+            ui.close_menu();
+        }
+    }
+
+    // an input only takes one distance, connecting another one replaces it
+    fn connect(&mut self, from : &OutPin, to : &InPin, snarl : &mut Snarl<SdfNode>){
+        snarl.drop_inputs(to.id);
+        snarl.connect(from.id, to.id);
+        self.changed = true;
+    }
+
+    fn disconnect(&mut self, from : &OutPin, to : &InPin, snarl : &mut Snarl<SdfNode>){
+        snarl.disconnect(from.id, to.id);
+        self.changed = true;
+    }
+
+    fn drop_inputs(&mut self, pin : &InPin, snarl : &mut Snarl<SdfNode>){
+        snarl.drop_inputs(pin.id);
+        self.changed = true;
+    }
+
+    fn drop_outputs(&mut self, pin : &OutPin, snarl : &mut Snarl<SdfNode>){
+        snarl.drop_outputs(pin.id);
+        self.changed = true;
+    }
+}
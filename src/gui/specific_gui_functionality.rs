@@ -1,9 +1,23 @@
 use egui::Ui;
-use crate::{shapes::{Cube, Sphere}, ObjectHandeler};
+use crate::{scene::SceneDocument, shapes::{Capsule, Cone, Cube, Cylinder, Sphere, Torus}, Camera, ObjectHandeler};
+use crate::camera::CameraBookmark;
+use crate::history::{EditCommand, History};
+use crate::light::{Light, LightKind};
+use crate::material::{unique_material_name, LibraryMaterial, Material};
+use crate::object_handler::SceneObject;
+use crate::obj_file;
+use crate::texture::{Pattern, TextureError, TextureImage};
+use crate::ground::Ground;
+use crate::sky::{Sky, SkyImage, SkyImages, SkyKind, MAX_CUBEMAP_SIZE, MAX_PANORAMA_SIZE};
+use crate::shader_handler::ShaderError;
 
 pub struct CreateRenderObjectGui<'a>{
     sphere : Sphere, 
     cube : Cube,
+    torus : Torus,
+    capsule : Capsule,
+    cylinder : Cylinder,
+    cone : Cone,
     selected : &'a str
 
 
@@ -15,11 +29,15 @@ impl CreateRenderObjectGui<'_>{
         CreateRenderObjectGui{
             sphere : Sphere::new([0.0; 3], [0.5; 3], 1.0),
             cube : Cube::new([0.0; 3], [1.0; 3], [0.5; 3]),
+            torus : Torus::new([0.0; 3], 1.0, 0.3, [0.5; 3]),
+            capsule : Capsule::new([0.0; 3], 0.5, 0.5, [0.5; 3]),
+            cylinder : Cylinder::new([0.0; 3], 0.5, 0.5, [0.5; 3]),
+            cone : Cone::new([0.0; 3], 0.5, 0.5, 0.0, [0.5; 3]),
             selected : "Sphere"
         }   
     }
 
-    pub fn show(&mut self, create_object_gui_active : &mut bool, ui : &mut Ui, object_handeler : &mut ObjectHandeler, should_update_objects : &mut bool){
+    pub fn show(&mut self, create_object_gui_active : &mut bool, ui : &mut Ui, object_handeler : &mut ObjectHandeler, history : &mut History){
 
         let mut str = String::from("3.0");
         match create_object_gui_active{
@@ -30,6 +48,10 @@ impl CreateRenderObjectGui<'_>{
                 .show_ui(ui, |ui| {
                     ui.selectable_value(&mut self.selected, "Sphere", "Sphere");
                     ui.selectable_value(&mut self.selected, "Cube", "Cube");
+                    ui.selectable_value(&mut self.selected, "Torus", "Torus");
+                    ui.selectable_value(&mut self.selected, "Capsule", "Capsule");
+                    ui.selectable_value(&mut self.selected, "Cylinder", "Cylinder");
+                    ui.selectable_value(&mut self.selected, "Cone", "Cone");
                 });
 
                 match self.selected {
@@ -60,10 +82,48 @@ impl CreateRenderObjectGui<'_>{
 
                         if ui.button("Create object").clicked(){
                             *create_object_gui_active = false;
-                            object_handeler.add_sphere(self.sphere);
-                            *should_update_objects = true; // to make sure that main loop re-uploads objects to scene
+                            let object = SceneObject::Sphere(self.sphere);
+                            let id = object_handeler.add_object(object);
+                            history.push(EditCommand::AddObject { id, object });
                         }
                     }, 
+                    "Torus" => {
+                        Self::position_edit(&mut self.torus.pos, ui);
+                        Self::size_edit("Radius", &mut self.torus.radius, ui);
+                        Self::size_edit("Thickness", &mut self.torus.thickness, ui);
+                        ui.label("Color");
+                        egui::color_picker::color_edit_button_rgb(ui, &mut self.torus.color);
+
+                        Self::create_button(SceneObject::Torus(self.torus), create_object_gui_active, ui, object_handeler, history);
+                    },
+                    "Capsule" => {
+                        Self::position_edit(&mut self.capsule.pos, ui);
+                        Self::size_edit("Half height", &mut self.capsule.half_height, ui);
+                        Self::size_edit("Radius", &mut self.capsule.radius, ui);
+                        ui.label("Color");
+                        egui::color_picker::color_edit_button_rgb(ui, &mut self.capsule.color);
+
+                        Self::create_button(SceneObject::Capsule(self.capsule), create_object_gui_active, ui, object_handeler, history);
+                    },
+                    "Cylinder" => {
+                        Self::position_edit(&mut self.cylinder.pos, ui);
+                        Self::size_edit("Half height", &mut self.cylinder.half_height, ui);
+                        Self::size_edit("Radius", &mut self.cylinder.radius, ui);
+                        ui.label("Color");
+                        egui::color_picker::color_edit_button_rgb(ui, &mut self.cylinder.color);
+
+                        Self::create_button(SceneObject::Cylinder(self.cylinder), create_object_gui_active, ui, object_handeler, history);
+                    },
+                    "Cone" => {
+                        Self::position_edit(&mut self.cone.pos, ui);
+                        Self::size_edit("Half height", &mut self.cone.half_height, ui);
+                        Self::size_edit("Bottom radius", &mut self.cone.bottom_radius, ui);
+                        Self::size_edit("Top radius", &mut self.cone.top_radius, ui);
+                        ui.label("Color");
+                        egui::color_picker::color_edit_button_rgb(ui, &mut self.cone.color);
+
+                        Self::create_button(SceneObject::Cone(self.cone), create_object_gui_active, ui, object_handeler, history);
+                    },
                     &_=> {
 
                         // to tweak coordinates/position
@@ -106,8 +166,9 @@ impl CreateRenderObjectGui<'_>{
 
                         if ui.button("Create object").clicked(){
                             *create_object_gui_active = false;  // changes visibility of this gui code
-                            object_handeler.add_cube(self.cube);
-                            *should_update_objects = true;  // to make sure that main loop re-uploads objects to scene
+                            let object = SceneObject::Cube(self.cube);
+                            let id = object_handeler.add_object(object);
+                            history.push(EditCommand::AddObject { id, object });
                         }
                     }
                 }
@@ -124,4 +185,710 @@ impl CreateRenderObjectGui<'_>{
             }
         }  
     }
-}
\ No newline at end of file
+
+    fn position_edit(pos : &mut [f32; 3], ui : &mut Ui){
+        ui.label("Position");
+        ui.horizontal(|ui| {
+            for coord in pos.iter_mut() {
+                ui.add(egui::DragValue::new(coord).speed(0.1));
+            }
+        });
+    }
+
+    // sizes can not be negative
+    fn size_edit(name : &str, size : &mut f32, ui : &mut Ui){
+        ui.label(name);
+        ui.add(egui::DragValue::new(size).speed(0.05).clamp_range(0.0..=10.0));
+    }
+
+    fn create_button(object : SceneObject, create_object_gui_active : &mut bool, ui : &mut Ui, object_handeler : &mut ObjectHandeler, history : &mut History){
+        if ui.button("Create object").clicked(){
+            *create_object_gui_active = false; // changes visibility of this gui code
+            let id = object_handeler.add_object(object);
+            history.push(EditCommand::AddObject { id, object });
+        }
+    }
+}
+
+pub struct SceneFileGui{
+    path : String,
+    status : String
+}
+
+impl SceneFileGui{
+
+    pub fn new() -> SceneFileGui{
+        SceneFileGui{
+            path : String::from("scene.json"),
+            status : String::new()
+        }
+    }
+
+    // returns true when a scene file was opened and replaced the current scene
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler, camera : &mut Camera) -> bool{
+
+        let mut opened = false;
+
+        ui.label("Scene file");
+        egui::TextEdit::singleline(&mut self.path).show(ui);
+
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked(){
+                self.status = match SceneDocument::from_scene(object_handeler, camera).save(&self.path) {
+                    Ok(()) => format!("Saved to {}", self.path),
+                    Err(err) => err.to_string()
+                };
+            }
+
+            if ui.button("Open").clicked(){
+                self.status = match SceneDocument::load(&self.path) {
+                    Ok(document) => {
+                        document.apply(object_handeler, camera);
+                        opened = true;
+                        format!("Opened {}", self.path)
+                    },
+                    Err(err) => err.to_string()
+                };
+            }
+        });
+
+        if !self.status.is_empty(){
+            ui.label(&self.status);
+        }
+
+        opened
+    }
+}
+
+pub struct MeshImportGui{
+    path : String,
+    color : [f32; 3],
+    status : String
+}
+
+impl MeshImportGui{
+
+    pub fn new() -> MeshImportGui{
+        MeshImportGui{
+            path : String::from("mesh.obj"),
+            color : [0.8, 0.8, 0.8],
+            status : String::new()
+        }
+    }
+
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler, history : &mut History){
+
+        ui.collapsing("Import mesh", |ui| {
+            ui.label("OBJ file");
+            egui::TextEdit::singleline(&mut self.path).show(ui);
+
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                egui::color_picker::color_edit_button_rgb(ui, &mut self.color);
+            });
+
+            if ui.button("Import").clicked(){
+                let result = obj_file::load(&self.path).and_then(|triangles| obj_file::add_mesh(object_handeler, triangles, self.color));
+
+                self.status = match result {
+                    Ok(id) => {
+                        let object = object_handeler.get_object(id);
+                        history.push(EditCommand::AddObject { id, object });
+                        format!("Imported {}", self.path)
+                    },
+                    Err(err) => err.to_string()
+                };
+            }
+
+            if !self.status.is_empty(){
+                ui.label(&self.status);
+            }
+        });
+    }
+}
+
+pub struct TexturesGui{
+    path : String,
+    status : String
+}
+
+impl TexturesGui{
+
+    pub fn new() -> TexturesGui{
+        TexturesGui{
+            path : String::from("texture.png"),
+            status : String::new()
+        }
+    }
+
+    // images are not part of the history, only the objects that use them
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler){
+
+        ui.collapsing("Textures", |ui| {
+            ui.label("PNG file");
+            egui::TextEdit::singleline(&mut self.path).show(ui);
+
+            if ui.button("Load").clicked(){
+                self.status = match TextureImage::load(&self.path).and_then(|texture| object_handeler.add_texture(texture)) {
+                    Ok(_) => format!("Loaded {}", self.path),
+                    Err(err) => err.to_string()
+                };
+            }
+
+            if !self.status.is_empty(){
+                ui.label(&self.status);
+            }
+
+            for (i, texture) in object_handeler.get_textures().iter().enumerate() {
+                ui.label(format!("{} : {}", i, texture.path));
+            }
+        });
+    }
+}
+
+pub struct CameraBookmarksGui{
+    name : String
+}
+
+impl CameraBookmarksGui{
+
+    pub fn new() -> CameraBookmarksGui{
+        CameraBookmarksGui{
+            name : String::from("View")
+        }
+    }
+
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler, camera : &mut Camera, history : &mut History){
+
+        ui.collapsing("Camera bookmarks", |ui| {
+            ui.horizontal(|ui| {
+                egui::TextEdit::singleline(&mut self.name).desired_width(120.0).show(ui);
+
+                if ui.button("Save view").clicked(){
+                    let bookmark = CameraBookmark { name : self.name.clone(), camera : *camera };
+                    let index = object_handeler.get_camera_bookmarks().len();
+
+                    object_handeler.get_camera_bookmarks_reference().push(bookmark.clone());
+                    history.push(EditCommand::AddBookmark { index, bookmark });
+                }
+            });
+
+            // done after the loop, the list can not change while it is being shown
+            let mut go_to = None;
+            let mut removed = None;
+
+            for (i, bookmark) in object_handeler.get_camera_bookmarks().iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(&bookmark.name);
+                    if ui.button("Go to").clicked(){
+                        go_to = Some(i);
+                    }
+                    if ui.button("Remove").clicked(){
+                        removed = Some(i);
+                    }
+                });
+            }
+
+            if let Some(i) = go_to {
+                let bookmark = &object_handeler.get_camera_bookmarks()[i];
+
+                if bookmark.camera != *camera {
+                    history.push(EditCommand::MoveCamera { before : *camera, after : bookmark.camera, bookmark_name : bookmark.name.clone() });
+                    *camera = bookmark.camera;
+                }
+            }
+
+            if let Some(index) = removed {
+                let bookmark = object_handeler.get_camera_bookmarks_reference().remove(index);
+                history.push(EditCommand::RemoveBookmark { index, bookmark });
+            }
+        });
+    }
+}
+
+pub struct LightsGui{
+    // the kind of light that "Add light" adds
+    kind : LightKind
+}
+
+impl LightsGui{
+
+    pub fn new() -> LightsGui{
+        LightsGui{
+            kind : LightKind::Point
+        }
+    }
+
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler, history : &mut History){
+
+        ui.collapsing("Lights", |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("new_light_kind")
+                    .selected_text(self.kind.name())
+                    .show_ui(ui, |ui| {
+                        for kind in LightKind::ALL {
+                            ui.selectable_value(&mut self.kind, kind, kind.name());
+                        }
+                    });
+
+                if ui.button("Add light").clicked(){
+                    let light = Light::new(self.kind);
+                    let index = object_handeler.get_lights().len();
+
+                    object_handeler.insert_light(index, light);
+                    history.push(EditCommand::AddLight { index, light });
+                }
+            });
+
+            if object_handeler.get_lights().is_empty() {
+                ui.label("Without lights every object is black");
+            }
+
+            let mut removed = None;
+
+            for i in 0..object_handeler.get_lights().len() {
+                let before = object_handeler.get_lights()[i];
+
+                ui.collapsing(format!("{} {}", before.kind.name(), i), |ui| {
+                    // edited as a copy, like the objects
+                    let mut light = before;
+                    Self::light_properties(&mut light, ui);
+
+                    if light != before {
+                        object_handeler.set_light(i, light);
+                    }
+                    let continuous = ui.input(|i| i.pointer.any_down());
+                    history.record_change(EditCommand::ChangeLight { index : i, before, after : light }, continuous);
+
+                    if ui.button("Remove").clicked(){
+                        removed = Some(i);
+                    }
+                });
+            }
+
+            if let Some(index) = removed {
+                let light = object_handeler.remove_light(index);
+                history.push(EditCommand::RemoveLight { index, light });
+            }
+        });
+    }
+
+    fn light_properties(light : &mut Light, ui : &mut Ui){
+        egui::ComboBox::from_label("Kind")
+            .selected_text(light.kind.name())
+            .show_ui(ui, |ui| {
+                for kind in LightKind::ALL {
+                    ui.selectable_value(&mut light.kind, kind, kind.name());
+                }
+            });
+
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            egui::color_picker::color_edit_button_rgb(ui, &mut light.color);
+        });
+        ui.label("Intensity");
+        ui.add(egui::Slider::new(&mut light.intensity, 0.0..=10.0).min_decimals(2));
+
+        // drag values, the default light is far outside the range of a slider
+        if light.kind.has_pos() {
+            Self::vec3_drag("Position", &mut light.pos, ui);
+        }
+        if light.kind.has_dir() {
+            Self::vec3_drag("Direction", &mut light.dir, ui);
+        }
+
+        if light.kind.has_pos() {
+            ui.label("Range, 0 does not get darker with distance");
+            ui.add(egui::Slider::new(&mut light.range, 0.0..=100.0).logarithmic(true));
+        }
+        if light.kind == LightKind::Spot {
+            ui.label("Spot angle");
+            ui.add(egui::Slider::new(&mut light.spot_angle, 1.0..=90.0).suffix("°"));
+        }
+    }
+
+    fn vec3_drag(name : &str, v : &mut [f32; 3], ui : &mut Ui){
+        ui.horizontal(|ui| {
+            ui.label(name);
+            for x in v.iter_mut() {
+                ui.add(egui::DragValue::new(x).speed(0.05));
+            }
+        });
+    }
+}
+
+pub struct MaterialsGui{}
+
+impl MaterialsGui{
+
+    pub fn new() -> MaterialsGui{
+        MaterialsGui{}
+    }
+
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler, history : &mut History){
+
+        ui.collapsing("Materials", |ui| {
+            if ui.button("Add material").clicked(){
+                let index = object_handeler.get_materials().len();
+                let name = unique_material_name(object_handeler.get_materials(), index, &format!("Material {}", index));
+                let material = LibraryMaterial::new(name, Material::DEFAULT);
+
+                object_handeler.insert_material(index, material.clone());
+                history.push(EditCommand::AddMaterial { index, material });
+            }
+
+            let mut removed = None;
+
+            for i in 0..object_handeler.get_materials().len() {
+                let before = object_handeler.get_materials()[i].clone();
+
+                // the id does not come from the name, so that the header stays open while it is renamed
+                egui::CollapsingHeader::new(&before.name).id_source(("material", i)).show(ui, |ui| {
+                    // edited as a copy, like the objects
+                    let mut material = before.clone();
+                    let name_response = ui.text_edit_singleline(&mut material.name);
+                    // scene files refer to the materials by name, a name that is taken gets a number once the field is left
+                    if name_response.lost_focus() {
+                        material.name = unique_material_name(object_handeler.get_materials(), i, &material.name);
+                    } else if material.name != unique_material_name(object_handeler.get_materials(), i, &material.name) {
+                        ui.colored_label(ui.visuals().warn_fg_color, "Another material has this name");
+                    }
+                    Self::material_properties(&mut material.material, ui);
+
+                    if material != before {
+                        object_handeler.set_material(i, material.clone());
+                    }
+                    // typing the name is one step, like dragging a slider
+                    let continuous = ui.input(|i| i.pointer.any_down()) || name_response.has_focus() || name_response.lost_focus();
+                    history.record_change(EditCommand::ChangeMaterial { index : i, before, after : material }, continuous);
+
+                    // the objects and the ground that use a removed material get the first one, undo gives it back to them
+                    let users = object_handeler.material_users(i);
+                    let response = ui.add_enabled(i > 0, egui::Button::new("Remove")).on_disabled_hover_text("New objects get the first material");
+                    let response = match (users.ground, users.objects.len()) {
+                        (false, 0) => response,
+                        (true, 0) => response.on_hover_text("The ground uses it, it gets the first material"),
+                        (false, n) => response.on_hover_text(format!("Used by {} objects, they get the first material", n)),
+                        (true, n) => response.on_hover_text(format!("Used by the ground and {} objects, they get the first material", n)),
+                    };
+                    if response.clicked(){
+                        removed = Some(i);
+                    }
+                });
+            }
+
+            if let Some(index) = removed {
+                let (material, users) = object_handeler.remove_material(index);
+                history.push(EditCommand::RemoveMaterial { index, material, users });
+            }
+        });
+    }
+
+    fn material_properties(material : &mut Material, ui : &mut Ui){
+        ui.label("Roughness");
+        ui.add(egui::Slider::new(&mut material.roughness, 0.0..=1.0).min_decimals(2));
+        ui.label("Metallic");
+        ui.add(egui::Slider::new(&mut material.metallic, 0.0..=1.0).min_decimals(2));
+        ui.label("Specular");
+        ui.add(egui::Slider::new(&mut material.specular, 0.0..=1.0).min_decimals(2));
+        ui.label("Emissive");
+        ui.add(egui::Slider::new(&mut material.emissive, 0.0..=5.0).min_decimals(2));
+        ui.label("Reflectivity");
+        ui.add(egui::Slider::new(&mut material.reflectivity, 0.0..=1.0).min_decimals(2));
+        ui.label("Transparency");
+        ui.add(egui::Slider::new(&mut material.transparency, 0.0..=1.0).min_decimals(2));
+        if material.transparency > 0.0 {
+            ui.label("Index of refraction");
+            ui.add(egui::Slider::new(&mut material.ior, 1.0..=3.0).min_decimals(2));
+        }
+    }
+}
+
+pub struct SkyGui{
+    panorama_path : String,
+    // the faces in the order +x, -x, +y, -y, +z, -z
+    cubemap_paths : [String; 6],
+    status : String
+}
+
+impl SkyGui{
+
+    const FACE_NAMES : [&'static str; 6] = ["+X", "-X", "+Y", "-Y", "+Z", "-Z"];
+
+    pub fn new() -> SkyGui{
+        SkyGui{
+            panorama_path : String::from("sky.hdr"),
+            cubemap_paths : ["px.png", "nx.png", "py.png", "ny.png", "pz.png", "nz.png"].map(String::from),
+            status : String::new()
+        }
+    }
+
+    // the images of the sky are not part of the history, like the images of the textures
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler, history : &mut History){
+
+        ui.collapsing("Sky", |ui| {
+            // edited as a copy, like the lights
+            let before = object_handeler.get_sky();
+            let mut sky = before;
+
+            egui::ComboBox::from_label("Sky")
+                .selected_text(sky.kind.name())
+                .show_ui(ui, |ui| {
+                    // a cubemap or a panorama can only be picked once it is loaded
+                    for kind in SkyKind::ALL {
+                        if object_handeler.get_sky_images().has(kind) {
+                            ui.selectable_value(&mut sky.kind, kind, kind.name());
+                        }
+                    }
+                });
+
+            if sky.kind == SkyKind::Gradient {
+                for (name, clr) in [("Zenith", &mut sky.zenith), ("Horizon", &mut sky.horizon), ("Ground", &mut sky.ground)] {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        egui::color_picker::color_edit_button_rgb(ui, clr);
+                    });
+                }
+            }
+
+            ui.label("Intensity");
+            ui.add(egui::Slider::new(&mut sky.intensity, 0.01..=10.0).logarithmic(true));
+            ui.label("Ambient light");
+            ui.add(egui::Slider::new(&mut sky.ambient, 0.0..=2.0).min_decimals(2));
+
+            if sky != before {
+                object_handeler.set_sky(sky);
+            }
+
+            ui.separator();
+            ui.label("Equirectangular panorama, PNG or HDR file");
+            egui::TextEdit::singleline(&mut self.panorama_path).show(ui);
+
+            if ui.button("Load panorama").clicked(){
+                let cubemap = object_handeler.get_sky_images().cubemap.clone();
+                let images = SkyImage::load(&self.panorama_path, MAX_PANORAMA_SIZE).and_then(|panorama| SkyImages::new(cubemap, Some(panorama)));
+                self.status = Self::set_images(images, SkyKind::Equirectangular, object_handeler);
+            }
+
+            ui.label("Cubemap faces, PNG or HDR files");
+            for (name, path) in Self::FACE_NAMES.iter().zip(self.cubemap_paths.iter_mut()) {
+                ui.horizontal(|ui| {
+                    ui.label(*name);
+                    egui::TextEdit::singleline(path).show(ui);
+                });
+            }
+
+            if ui.button("Load cubemap").clicked(){
+                let panorama = object_handeler.get_sky_images().panorama.clone();
+                let faces : Result<Vec<SkyImage>, _> = self.cubemap_paths.iter().map(|path| SkyImage::load(path, MAX_CUBEMAP_SIZE)).collect();
+                let images = faces.and_then(|faces| SkyImages::new(faces, panorama));
+                self.status = Self::set_images(images, SkyKind::Cubemap, object_handeler);
+            }
+
+            if !self.status.is_empty(){
+                ui.label(&self.status);
+            }
+
+            // loading images switches to them, which is recorded with the other changes
+            let continuous = ui.input(|i| i.pointer.any_down());
+            history.record_change(EditCommand::ChangeSky { before, after : object_handeler.get_sky() }, continuous);
+        });
+    }
+
+    // the sky shows the new images right away
+    fn set_images(images : Result<SkyImages, TextureError>, kind : SkyKind, object_handeler : &mut ObjectHandeler) -> String {
+        match images {
+            Ok(images) => {
+                object_handeler.set_sky_images(images);
+                object_handeler.set_sky(Sky { kind, ..object_handeler.get_sky() });
+                format!("Loaded the {}", kind.name().to_lowercase())
+            },
+            Err(err) => err.to_string()
+        }
+    }
+}
+
+pub struct GroundGui{}
+
+impl GroundGui{
+
+    pub fn new() -> GroundGui{
+        GroundGui{}
+    }
+
+    pub fn show(&mut self, ui : &mut Ui, object_handeler : &mut ObjectHandeler, history : &mut History){
+
+        ui.collapsing("Ground", |ui| {
+            // edited as a copy, like the lights
+            let before = object_handeler.get_ground();
+            let mut ground = before;
+
+            ui.checkbox(&mut ground.enabled, "Enabled");
+
+            if ground.enabled {
+                Self::ground_properties(&mut ground, object_handeler, ui);
+            }
+
+            if ground != before {
+                object_handeler.set_ground(ground);
+            }
+            let continuous = ui.input(|i| i.pointer.any_down());
+            history.record_change(EditCommand::ChangeGround { before, after : ground }, continuous);
+        });
+    }
+
+    fn ground_properties(ground : &mut Ground, object_handeler : &ObjectHandeler, ui : &mut Ui){
+        ui.label("Height");
+        ui.add(egui::Slider::new(&mut ground.height, -10.0..=10.0).min_decimals(2));
+        // a drag value for each axis, it is normalized when it is used
+        ui.horizontal(|ui| {
+            ui.label("Normal");
+            for x in ground.normal.iter_mut() {
+                ui.add(egui::DragValue::new(x).speed(0.05));
+            }
+        });
+
+        let materials = object_handeler.get_materials();
+        egui::ComboBox::from_label("Material")
+            .selected_text(&materials[ground.material].name)
+            .show_ui(ui, |ui| {
+                for (i, library_material) in materials.iter().enumerate() {
+                    ui.selectable_value(&mut ground.material, i, &library_material.name);
+                }
+            });
+
+        let images = object_handeler.get_textures();
+        egui::ComboBox::from_label("Pattern")
+            .selected_text(ground.pattern.name())
+            .show_ui(ui, |ui| {
+                for pattern in Pattern::ALL.into_iter().filter(|pattern| *pattern != Pattern::Image || !images.is_empty()) {
+                    ui.selectable_value(&mut ground.pattern, pattern, pattern.name());
+                }
+            });
+
+        if ground.pattern == Pattern::Image {
+            egui::ComboBox::from_label("Image")
+                .selected_text(&images[ground.image].path)
+                .show_ui(ui, |ui| {
+                    for (i, image) in images.iter().enumerate() {
+                        ui.selectable_value(&mut ground.image, i, &image.path);
+                    }
+                });
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            egui::color_picker::color_edit_button_rgb(ui, &mut ground.color);
+        });
+        // an image is only multiplied with the first color
+        if ground.pattern != Pattern::None && ground.pattern != Pattern::Image {
+            ui.horizontal(|ui| {
+                ui.label("Second color");
+                egui::color_picker::color_edit_button_rgb(ui, &mut ground.second_color);
+            });
+        }
+
+        if ground.pattern != Pattern::None {
+            ui.label("Tile size");
+            ui.add(egui::Slider::new(&mut ground.tile_size, 0.01..=10.0).logarithmic(true));
+        }
+        ui.label("Fade distance, 0 does not fade");
+        ui.add(egui::Slider::new(&mut ground.fade_distance, 0.0..=100.0).logarithmic(true));
+    }
+}
+
+pub struct HistoryGui{}
+
+impl HistoryGui{
+
+    pub fn new() -> HistoryGui{
+        HistoryGui{}
+    }
+
+    // ctrl+z undoes, ctrl+shift+z and ctrl+y redoes
+    pub fn shortcuts(&mut self, ctx : &egui::Context, history : &mut History, object_handeler : &mut ObjectHandeler, camera : &mut Camera){
+        if ctx.wants_keyboard_input() {
+            return; // text fields have their own undo
+        }
+
+        let redo = ctx.input_mut(|i| {
+            i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z) || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)
+        });
+        // checked after redo, since ctrl+shift+z also matches ctrl+z
+        let undo = ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z));
+
+        if redo {
+            history.redo(object_handeler, camera);
+        }
+        if undo {
+            history.undo(object_handeler, camera);
+        }
+    }
+
+    pub fn show(&mut self, ui : &mut Ui, history : &mut History, object_handeler : &mut ObjectHandeler, camera : &mut Camera){
+
+        ui.collapsing("History", |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(history.can_undo(), egui::Button::new("Undo")).clicked(){
+                    history.undo(object_handeler, camera);
+                }
+                if ui.add_enabled(history.can_redo(), egui::Button::new("Redo")).clicked(){
+                    history.redo(object_handeler, camera);
+                }
+            });
+
+            for command in history.undo_steps() {
+                ui.label(command.describe());
+            }
+
+            // steps that have been undone are greyed out
+            for command in history.redo_steps() {
+                ui.weak(command.describe());
+            }
+        });
+    }
+}
+
+// the compiler log of shader files that do not compile, with the source line under each line of the log that points to one
+pub struct ShaderErrorGui{
+    error : Option<ShaderError>
+}
+
+impl ShaderErrorGui{
+
+    pub fn new() -> ShaderErrorGui{
+        ShaderErrorGui{
+            error : None
+        }
+    }
+
+    pub fn set_error(&mut self, error : Option<ShaderError>){
+        self.error = error;
+    }
+
+    pub fn show(&mut self, ctx : &egui::Context){
+        let Some(error) = &self.error else {
+            return;
+        };
+
+        egui::Window::new("Shader error").default_size([600.0, 300.0]).show(ctx, |ui| {
+            ui.colored_label(egui::Color32::RED, &error.message);
+            if error.generated {
+                ui.label("The scene is drawn with the generic shader instead");
+            } else {
+                ui.label("The last shader that compiled is used until the file is fixed and saved");
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for log_line in error.log.lines() {
+                    ui.monospace(log_line);
+
+                    if let Some((number, line)) = error.source_line(log_line) {
+                        ui.weak(egui::RichText::new(format!("{:>5} | {}", number, line)).monospace());
+                    }
+                }
+            });
+        });
+    }
+}
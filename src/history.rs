@@ -0,0 +1,392 @@
+use crate::camera::{Camera, CameraBookmark};
+use crate::light::Light;
+use crate::material::LibraryMaterial;
+use crate::ground::Ground;
+use crate::sky::Sky;
+use crate::shapes::Mesh;
+use crate::object_handler::{MaterialUsers, ObjectHandeler, ObjectId, SceneObject};
+
+// every change to the scene that can be undone, each command holds what is needed to go both ways
+#[derive(Clone, Debug)]
+pub enum EditCommand {
+    AddObject { id : ObjectId, object : SceneObject },
+    // position is where the object was in the list order
+    RemoveObject { id : ObjectId, object : SceneObject, position : usize },
+    ChangeObject { id : ObjectId, before : SceneObject, after : SceneObject },
+    MoveObject { id : ObjectId, from : usize, to : usize },
+    AddBookmark { index : usize, bookmark : CameraBookmark },
+    RemoveBookmark { index : usize, bookmark : CameraBookmark },
+    MoveCamera { before : Camera, after : Camera, bookmark_name : String },
+    AddLight { index : usize, light : Light },
+    RemoveLight { index : usize, light : Light },
+    ChangeLight { index : usize, before : Light, after : Light },
+    AddMaterial { index : usize, material : LibraryMaterial },
+    // users are the objects and the ground that had the material, they get the first material while it is removed
+    RemoveMaterial { index : usize, material : LibraryMaterial, users : MaterialUsers },
+    ChangeMaterial { index : usize, before : LibraryMaterial, after : LibraryMaterial },
+    ChangeGround { before : Ground, after : Ground },
+    ChangeSky { before : Sky, after : Sky },
+}
+
+impl EditCommand {
+
+    pub fn describe(&self) -> String {
+        match self {
+            EditCommand::AddObject { id, .. } => format!("Add {:?} {}", id.kind, id.index),
+            EditCommand::RemoveObject { id, .. } => format!("Remove {:?} {}", id.kind, id.index),
+            EditCommand::ChangeObject { id, .. } => format!("Change {:?} {}", id.kind, id.index),
+            EditCommand::MoveObject { id, .. } => format!("Move {:?} {}", id.kind, id.index),
+            EditCommand::AddBookmark { bookmark, .. } => format!("Add bookmark \"{}\"", bookmark.name),
+            EditCommand::RemoveBookmark { bookmark, .. } => format!("Remove bookmark \"{}\"", bookmark.name),
+            EditCommand::MoveCamera { bookmark_name, .. } => format!("Go to bookmark \"{}\"", bookmark_name),
+            EditCommand::AddLight { index, light } => format!("Add {} light {}", light.kind.name().to_lowercase(), index),
+            EditCommand::RemoveLight { index, light } => format!("Remove {} light {}", light.kind.name().to_lowercase(), index),
+            EditCommand::ChangeLight { index, .. } => format!("Change light {}", index),
+            EditCommand::AddMaterial { material, .. } => format!("Add material \"{}\"", material.name),
+            EditCommand::RemoveMaterial { material, .. } => format!("Remove material \"{}\"", material.name),
+            EditCommand::ChangeMaterial { after, .. } => format!("Change material \"{}\"", after.name),
+            EditCommand::ChangeGround { .. } => String::from("Change ground"),
+            EditCommand::ChangeSky { .. } => String::from("Change sky"),
+        }
+    }
+
+    // a change command where before and after are the same
+    fn is_unchanged(&self) -> bool {
+        match self {
+            EditCommand::ChangeObject { before, after, .. } => before == after,
+            EditCommand::ChangeLight { before, after, .. } => before == after,
+            EditCommand::ChangeMaterial { before, after, .. } => before == after,
+            EditCommand::ChangeGround { before, after } => before == after,
+            EditCommand::ChangeSky { before, after } => before == after,
+            _ => false,
+        }
+    }
+
+    // takes the after of next if it changes the same thing as this, returns false if it does not
+    fn merge(&mut self, next : &EditCommand) -> bool {
+        match (self, next) {
+            (EditCommand::ChangeObject { id, after, .. }, EditCommand::ChangeObject { id : next_id, after : next_after, .. }) if id == next_id => *after = *next_after,
+            (EditCommand::ChangeLight { index, after, .. }, EditCommand::ChangeLight { index : next_index, after : next_after, .. }) if index == next_index => *after = *next_after,
+            (EditCommand::ChangeMaterial { index, after, .. }, EditCommand::ChangeMaterial { index : next_index, after : next_after, .. }) if index == next_index => *after = next_after.clone(),
+            (EditCommand::ChangeGround { after, .. }, EditCommand::ChangeGround { after : next_after, .. }) => *after = *next_after,
+            (EditCommand::ChangeSky { after, .. }, EditCommand::ChangeSky { after : next_after, .. }) => *after = *next_after,
+            _ => return false,
+        }
+        true
+    }
+
+    // the copies of meshes in the command, their triangles have to be kept while the command can be undone or redone
+    fn meshes_mut(&mut self) -> Vec<&mut Mesh> {
+        match self {
+            EditCommand::AddObject { object : SceneObject::Mesh(mesh), .. } => vec![mesh],
+            EditCommand::RemoveObject { object : SceneObject::Mesh(mesh), .. } => vec![mesh],
+            EditCommand::ChangeObject { before : SceneObject::Mesh(before), after : SceneObject::Mesh(after), .. } => vec![before, after],
+            _ => Vec::new(),
+        }
+    }
+
+    fn redo(&self, object_handeler : &mut ObjectHandeler, camera : &mut Camera) {
+        match self {
+            // added objects are last in the list order
+            EditCommand::AddObject { id, object } => object_handeler.insert_object(*id, *object, object_handeler.get_order().len()),
+            EditCommand::RemoveObject { id, .. } => {object_handeler.remove_object(*id);},
+            EditCommand::ChangeObject { id, after, .. } => object_handeler.set_object(*id, *after),
+            EditCommand::MoveObject { id, to, .. } => object_handeler.move_object(*id, *to),
+            EditCommand::AddBookmark { index, bookmark } => object_handeler.get_camera_bookmarks_reference().insert(*index, bookmark.clone()),
+            EditCommand::RemoveBookmark { index, .. } => {object_handeler.get_camera_bookmarks_reference().remove(*index);},
+            EditCommand::MoveCamera { after, .. } => *camera = *after,
+            EditCommand::AddLight { index, light } => object_handeler.insert_light(*index, *light),
+            EditCommand::RemoveLight { index, .. } => {object_handeler.remove_light(*index);},
+            EditCommand::ChangeLight { index, after, .. } => object_handeler.set_light(*index, *after),
+            EditCommand::AddMaterial { index, material } => object_handeler.insert_material(*index, material.clone()),
+            EditCommand::RemoveMaterial { index, .. } => {object_handeler.remove_material(*index);},
+            EditCommand::ChangeMaterial { index, after, .. } => object_handeler.set_material(*index, after.clone()),
+            EditCommand::ChangeGround { after, .. } => object_handeler.set_ground(*after),
+            EditCommand::ChangeSky { after, .. } => object_handeler.set_sky(*after),
+        }
+    }
+
+    fn undo(&self, object_handeler : &mut ObjectHandeler, camera : &mut Camera) {
+        match self {
+            EditCommand::AddObject { id, .. } => {object_handeler.remove_object(*id);},
+            EditCommand::RemoveObject { id, object, position } => object_handeler.insert_object(*id, *object, *position),
+            EditCommand::ChangeObject { id, before, .. } => object_handeler.set_object(*id, *before),
+            EditCommand::MoveObject { id, from, .. } => object_handeler.move_object(*id, *from),
+            EditCommand::AddBookmark { index, .. } => {object_handeler.get_camera_bookmarks_reference().remove(*index);},
+            EditCommand::RemoveBookmark { index, bookmark } => object_handeler.get_camera_bookmarks_reference().insert(*index, bookmark.clone()),
+            EditCommand::MoveCamera { before, .. } => *camera = *before,
+            EditCommand::AddLight { index, .. } => {object_handeler.remove_light(*index);},
+            EditCommand::RemoveLight { index, light } => object_handeler.insert_light(*index, *light),
+            EditCommand::ChangeLight { index, before, .. } => object_handeler.set_light(*index, *before),
+            EditCommand::AddMaterial { index, .. } => {object_handeler.remove_material(*index);},
+            EditCommand::RemoveMaterial { index, material, users } => {
+                object_handeler.insert_material(*index, material.clone());
+                object_handeler.set_material_users(*index, users);
+            },
+            EditCommand::ChangeMaterial { index, before, .. } => object_handeler.set_material(*index, before.clone()),
+            EditCommand::ChangeGround { before, .. } => object_handeler.set_ground(*before),
+            EditCommand::ChangeSky { before, .. } => object_handeler.set_sky(*before),
+        }
+    }
+}
+
+pub struct History {
+    undo_stack : Vec<EditCommand>,
+    redo_stack : Vec<EditCommand>,
+    // true while the last command is a change that is still being made, for example a slider that is being dragged
+    continuous_edit : bool,
+    // commands with meshes have been forgotten, so the triangles of those meshes may no longer be needed
+    meshes_dropped : bool,
+}
+
+impl History {
+
+    pub fn new() -> History {
+        History {
+            undo_stack : Vec::new(),
+            redo_stack : Vec::new(),
+            continuous_edit : false,
+            meshes_dropped : false,
+        }
+    }
+
+    // records a command that has already been applied to the scene
+    pub fn push(&mut self, command : EditCommand) {
+        self.undo_stack.push(command);
+        self.meshes_dropped |= self.redo_stack.iter_mut().any(|command| !command.meshes_mut().is_empty());
+        self.redo_stack.clear();
+        self.continuous_edit = false;
+    }
+
+    // records a change that has already been applied to the scene. Continuous changes to the same thing are merged into one step
+    // until end_continuous_edit is called, changes that do not change anything are not recorded
+    pub fn record_change(&mut self, command : EditCommand, continuous : bool) {
+        if command.is_unchanged() {
+            return;
+        }
+
+        if self.continuous_edit && continuous {
+            if let Some(last) = self.undo_stack.last_mut() {
+                if last.merge(&command) {
+                    return;
+                }
+            }
+        }
+
+        self.push(command);
+        self.continuous_edit = continuous;
+    }
+
+    pub fn end_continuous_edit(&mut self) {
+        self.continuous_edit = false;
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.continuous_edit = false;
+        self.meshes_dropped = true;
+    }
+
+    // drops the mesh triangles that neither the scene nor the history uses, after commands with meshes have been forgotten
+    pub fn compact_mesh_triangles(&mut self, object_handeler : &mut ObjectHandeler) {
+        if !self.meshes_dropped {
+            return;
+        }
+        self.meshes_dropped = false;
+
+        let commands = self.undo_stack.iter_mut().chain(self.redo_stack.iter_mut());
+        let mut kept : Vec<&mut Mesh> = commands.flat_map(|command| command.meshes_mut()).collect();
+        object_handeler.compact_mesh_triangles(&mut kept);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn undo(&mut self, object_handeler : &mut ObjectHandeler, camera : &mut Camera) -> bool {
+        let Some(command) = self.undo_stack.pop() else { return false };
+
+        command.undo(object_handeler, camera);
+        self.redo_stack.push(command);
+        self.continuous_edit = false;
+        true
+    }
+
+    pub fn redo(&mut self, object_handeler : &mut ObjectHandeler, camera : &mut Camera) -> bool {
+        let Some(command) = self.redo_stack.pop() else { return false };
+
+        command.redo(object_handeler, camera);
+        self.undo_stack.push(command);
+        self.continuous_edit = false;
+        true
+    }
+
+    // the steps that can be undone, oldest first
+    pub fn undo_steps(&self) -> impl Iterator<Item = &EditCommand> {
+        self.undo_stack.iter()
+    }
+
+    // the steps that can be redone, the next one to redo first
+    pub fn redo_steps(&self) -> impl Iterator<Item = &EditCommand> {
+        self.redo_stack.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+    use super::*;
+    use crate::shapes::Triangle;
+
+    fn light_at(x : f32) -> Light {
+        Light::point([x, 0.0, 0.0])
+    }
+
+    // like a slider in the side panel, the change is applied and then recorded
+    fn change_light(history : &mut History, object_handeler : &mut ObjectHandeler, index : usize, x : f32, continuous : bool) {
+        let before = object_handeler.get_lights()[index];
+        object_handeler.set_light(index, light_at(x));
+        history.record_change(EditCommand::ChangeLight { index, before, after : light_at(x) }, continuous);
+    }
+
+    // triangles that can be told apart by their first vertex
+    fn triangles(range : Range<usize>) -> Vec<Triangle> {
+        range.map(|i| Triangle::new([i as f32, 0.0, 0.0], [i as f32, 1.0, 0.0], [i as f32, 0.0, 1.0], [1.0; 3])).collect()
+    }
+
+    fn mesh_triangles_of(object_handeler : &ObjectHandeler, mesh : &Mesh) -> Vec<Triangle> {
+        object_handeler.get_mesh_triangles()[mesh.triangles()].to_vec()
+    }
+
+    #[test]
+    fn undo_and_redo_in_order() {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut camera = Camera::new();
+        let mut history = History::new();
+        object_handeler.set_lights(Vec::new());
+
+        for i in 0..3 {
+            object_handeler.insert_light(i, light_at(i as f32));
+            history.push(EditCommand::AddLight { index : i, light : light_at(i as f32) });
+        }
+
+        assert!(history.undo(&mut object_handeler, &mut camera));
+        assert!(history.undo(&mut object_handeler, &mut camera));
+        assert_eq!(object_handeler.get_lights(), &vec![light_at(0.0)]);
+        assert_eq!(history.redo_steps().map(|step| step.describe()).collect::<Vec<String>>(), vec!["Add point light 1", "Add point light 2"]);
+
+        assert!(history.redo(&mut object_handeler, &mut camera));
+        assert_eq!(object_handeler.get_lights(), &vec![light_at(0.0), light_at(1.0)]);
+
+        // a new step forgets the steps that could be redone
+        object_handeler.insert_light(2, light_at(5.0));
+        history.push(EditCommand::AddLight { index : 2, light : light_at(5.0) });
+        assert!(!history.can_redo());
+        assert!(!history.redo(&mut object_handeler, &mut camera));
+
+        while history.undo(&mut object_handeler, &mut camera) {}
+        assert!(object_handeler.get_lights().is_empty());
+        assert_eq!(history.redo_steps().count(), 3);
+    }
+
+    #[test]
+    fn continuous_changes_are_merged() {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut camera = Camera::new();
+        let mut history = History::new();
+        object_handeler.set_lights(vec![light_at(0.0), light_at(0.0)]);
+
+        // dragging a slider is one step, from before the drag to where it ends
+        for x in [1.0, 2.0, 3.0] {
+            change_light(&mut history, &mut object_handeler, 0, x, true);
+        }
+        assert_eq!(history.undo_steps().count(), 1);
+
+        // the next drag is a new step
+        history.end_continuous_edit();
+        change_light(&mut history, &mut object_handeler, 0, 4.0, true);
+        assert_eq!(history.undo_steps().count(), 2);
+
+        // changes to another light or changes that are not continuous are not merged
+        change_light(&mut history, &mut object_handeler, 1, 1.0, true);
+        change_light(&mut history, &mut object_handeler, 1, 2.0, false);
+        change_light(&mut history, &mut object_handeler, 1, 3.0, false);
+        assert_eq!(history.undo_steps().count(), 5);
+
+        for _ in 0..3 {
+            history.undo(&mut object_handeler, &mut camera);
+        }
+        assert_eq!(object_handeler.get_lights(), &vec![light_at(4.0), light_at(0.0)]);
+        history.undo(&mut object_handeler, &mut camera);
+        assert_eq!(object_handeler.get_lights()[0], light_at(3.0));
+        history.undo(&mut object_handeler, &mut camera);
+        assert_eq!(object_handeler.get_lights()[0], light_at(0.0));
+    }
+
+    #[test]
+    fn unchanged_is_not_recorded() {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut history = History::new();
+        object_handeler.set_lights(vec![light_at(1.0)]);
+
+        change_light(&mut history, &mut object_handeler, 0, 1.0, false);
+        assert!(!history.can_undo());
+
+        // an unchanged frame in the middle of a drag does not end the step
+        change_light(&mut history, &mut object_handeler, 0, 2.0, true);
+        change_light(&mut history, &mut object_handeler, 0, 2.0, true);
+        change_light(&mut history, &mut object_handeler, 0, 3.0, true);
+        assert_eq!(history.undo_steps().count(), 1);
+    }
+
+    #[test]
+    fn compacting_moves_the_meshes_in_the_history() {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut camera = Camera::new();
+        let mut history = History::new();
+
+        // the triangles of the dropped mesh are between the kept ones, so the mesh after them has to move
+        let kept_range = object_handeler.add_mesh_triangles(triangles(0..2));
+        let dropped_range = object_handeler.add_mesh_triangles(triangles(2..5));
+        let removed_range = object_handeler.add_mesh_triangles(triangles(5..6));
+        object_handeler.add_object(SceneObject::Mesh(Mesh::new([0.0; 3], [1.0; 3], kept_range.start, kept_range.len())));
+
+        // only in the undo steps
+        let removed = SceneObject::Mesh(Mesh::new([0.0; 3], [1.0; 3], removed_range.start, removed_range.len()));
+        let id = object_handeler.add_object(removed);
+        history.push(EditCommand::AddObject { id, object : removed });
+        let position = object_handeler.order_position(id);
+        let object = object_handeler.remove_object(id);
+        history.push(EditCommand::RemoveObject { id, object, position });
+
+        // only in a redo step, which is forgotten by the next step
+        let dropped = SceneObject::Mesh(Mesh::new([0.0; 3], [1.0; 3], dropped_range.start, dropped_range.len()));
+        let id = object_handeler.add_object(dropped);
+        history.push(EditCommand::AddObject { id, object : dropped });
+        history.undo(&mut object_handeler, &mut camera);
+        object_handeler.insert_light(0, light_at(0.0));
+        history.push(EditCommand::AddLight { index : 0, light : light_at(0.0) });
+
+        history.compact_mesh_triangles(&mut object_handeler);
+        let mut expected = triangles(0..2);
+        expected.extend(triangles(5..6));
+        assert_eq!(object_handeler.get_mesh_triangles(), &expected);
+        assert_eq!(mesh_triangles_of(&object_handeler, &object_handeler.get_meshes()[0]), triangles(0..2));
+
+        // undoing the removal brings back the mesh with its own triangles
+        history.undo(&mut object_handeler, &mut camera);
+        history.undo(&mut object_handeler, &mut camera);
+        let meshes = object_handeler.get_meshes();
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(mesh_triangles_of(&object_handeler, &meshes[1]), triangles(5..6));
+
+        // compacting again without anything forgotten keeps every triangle
+        history.compact_mesh_triangles(&mut object_handeler);
+        assert_eq!(object_handeler.get_mesh_triangles(), &expected);
+    }
+}
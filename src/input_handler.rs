@@ -1,5 +1,6 @@
 use egui;
 use crate::camera::Camera;
+use crate::picking::{pick, screen_to_uv};
 use crate::{vec_util::*, ObjectHandeler};
 
 pub struct InputHandler {
@@ -13,11 +14,33 @@ impl InputHandler {
         }
     }
 
-    pub fn handle(&mut self, ctx : &egui::Context, camera : &mut Camera, object_handler : &mut ObjectHandeler) {
+    pub fn handle(&mut self, ctx : &egui::Context, camera : &mut Camera, object_handler : &mut ObjectHandeler, allow_selection : bool) {
         self.rotate_camera(ctx, camera, 0.00009);
         self.move_camera(ctx, camera, 0.00015);
         self.zoom(ctx, camera, 0.05);
-        self.keyboard_inputs(ctx, object_handler);
+        if allow_selection {
+            self.select_object(ctx, camera, object_handler);
+        }
+    }
+
+    // clicking on the scene selects the object under the cursor, clicking on nothing clears the selection
+    fn select_object(&mut self, ctx : &egui::Context, camera : &Camera, object_handler : &mut ObjectHandeler) {
+        if ctx.is_pointer_over_area() {
+            return; // the click was meant for the gui
+        }
+
+        let screen_rect = ctx.screen_rect();
+
+        ctx.input(|i| {
+            if !i.pointer.primary_clicked() {
+                return;
+            }
+
+            if let Some(pos) = i.pointer.interact_pos() {
+                let uv = screen_to_uv([pos.x, pos.y], [screen_rect.width(), screen_rect.height()]);
+                object_handler.set_selected(pick(object_handler, camera, uv));
+            }
+        })
     }
 
     fn zoom(&mut self, ctx : &egui::Context, camera : &mut Camera, sensitivity : f32) {
@@ -67,23 +90,4 @@ impl InputHandler {
             }
         })
     }
-
-    fn keyboard_inputs(&mut self, ctx : &egui::Context, object_handler : &mut ObjectHandeler) {
-        ctx.input(|i| {
-            let keys_down = &i.keys_down;
-
-            if keys_down.contains(&egui::Key::Num1) {
-                object_handler.set_render_mode(0);
-            }
-            if keys_down.contains(&egui::Key::Num2) {
-                object_handler.set_render_mode(1);
-            }
-            if keys_down.contains(&egui::Key::Num3) {
-                object_handler.set_render_mode(2);
-            }
-            if keys_down.contains(&egui::Key::Num4) {
-                object_handler.set_render_mode(3);
-            }
-        })
-    }
-}
\ No newline at end of file
+}
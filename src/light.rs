@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vec_util::{length, normalize};
+
+// the part of the cone of a spot light, from the edge inwards, in which the light fades out
+const SPOT_EDGE : f32 = 0.1;
+
+// the numbers are the ones used in the shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightKind {
+    Directional = 0,
+    Point = 1,
+    Spot = 2,
+}
+
+impl LightKind {
+    pub const ALL : [LightKind; 3] = [LightKind::Directional, LightKind::Point, LightKind::Spot];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LightKind::Directional => "Directional",
+            LightKind::Point => "Point",
+            LightKind::Spot => "Spot",
+        }
+    }
+
+    pub fn has_pos(&self) -> bool {
+        matches!(self, LightKind::Point | LightKind::Spot)
+    }
+
+    pub fn has_dir(&self) -> bool {
+        matches!(self, LightKind::Directional | LightKind::Spot)
+    }
+}
+
+// a light in the scene, the fields that its kind does not use are kept so that switching the kind back does not lose them
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Light {
+    pub kind : LightKind,
+    pub pos : [f32; 3],
+    // the direction the light shines in, does not have to be normalized
+    pub dir : [f32; 3],
+    pub color : [f32; 3],
+    pub intensity : f32,
+    // point and spot lights have half their intensity at this distance, 0 does not get darker with distance
+    pub range : f32,
+    // the angle between the middle and the edge of the cone of a spot light, in degrees
+    pub spot_angle : f32,
+}
+
+impl Light {
+    pub fn new(kind : LightKind) -> Self {
+        Light {
+            kind,
+            pos : [0.0, 4.0, 0.0],
+            dir : [-0.3, -1.0, -0.2],
+            color : [1.0; 3],
+            intensity : 1.0,
+            range : 0.0,
+            spot_angle : 30.0,
+        }
+    }
+
+    // the light scenes had before there were more lights
+    pub fn point(pos : [f32; 3]) -> Self {
+        Light { pos, ..Light::new(LightKind::Point) }
+    }
+
+    // normalized, straight down if dir is zero
+    pub fn direction(&self) -> [f32; 3] {
+        if length(self.dir) == 0.0 {
+            return [0.0, -1.0, 0.0];
+        }
+        normalize(self.dir)
+    }
+
+    // the cosines of the angles at which a spot light is dark and at which it has its full intensity
+    pub fn spot_cos(&self) -> (f32, f32) {
+        let angle = self.spot_angle.to_radians();
+        (f32::cos(angle), f32::cos(angle * (1.0 - SPOT_EDGE)))
+    }
+}
+
+// darkens the surface where other surfaces are close, like in cavities and corners
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmbientOcclusion {
+    pub enabled : bool,
+    pub strength : f32,
+    // the number of distances sampled along the normal
+    pub steps : u32,
+    // shows only the ambient occlusion instead of the shaded colors
+    pub debug_view : bool,
+}
+
+impl AmbientOcclusion {
+    pub const DEFAULT : AmbientOcclusion = AmbientOcclusion { enabled : true, strength : 1.0, steps : 5, debug_view : false };
+
+    // the steps for the shader, 0 when it is turned off
+    pub fn active_steps(&self) -> u32 {
+        if self.enabled { self.steps } else { 0 }
+    }
+}
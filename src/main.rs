@@ -1,26 +1,50 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release // from demo
 
 use egui::ViewportId;
-use glium::{backend::glutin::SimpleWindowBuilder, glutin::surface::WindowSurface, implement_vertex, uniform};
+use glium::{backend::glutin::SimpleWindowBuilder, glutin::surface::WindowSurface, implement_vertex, uniform, Surface};
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter, Sampler, SamplerBehavior, SamplerWrapFunction};
 use shapes::{Cube, Sphere, MengerSponge};
 use winit::{
     event,
-    event_loop::{EventLoop, EventLoopBuilder},
+    event_loop::{ControlFlow, EventLoop, EventLoopBuilder},
 };
-use std::{fs, time::Instant};
+use std::path::PathBuf;
+use std::time::Instant;
 
 mod vec_util;
 mod gui;
 
 mod input_handler;
 mod shapes;
+mod light;
+mod material;
+mod texture;
+mod sky;
+mod ground;
 mod camera;
 mod object_handler;
+mod scene;
+mod sdf;
+mod cpu_renderer;
+mod picking;
+mod history;
+mod gpu_storage;
+mod bvh;
+mod benchmark;
+mod obj_file;
+mod node_graph;
+mod shader_gen;
+mod shader_files;
+mod shader_handler;
 
 use gui::*;
 use object_handler::*;
 use crate::shapes::Triangle;
 use camera::*;
+use scene::SceneDocument;
+use gpu_storage::GpuStorage;
+use sky::SkyKind;
+use shader_handler::ShaderHandler;
 
 
 
@@ -31,23 +55,91 @@ fn init_spheres() -> Vec<Sphere> {
     ]
 }
 
+// returns the value after a command line flag, for example the file in `--scene <file>`
+fn get_arg(flag : &str) -> Option<String> {
+    let args : Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).cloned()
+}
+
+// parses `--size <width>x<height>`, defaults to the size of the window
+fn get_size_arg() -> Result<(u32, u32), String> {
+    let Some(size) = get_arg("--size") else {
+        return Ok((1000, 700));
+    };
+    let parse = |value : &str| value.parse::<u32>().ok().filter(|value| *value > 0);
+
+    size.split_once('x')
+        .and_then(|(width, height)| Some((parse(width)?, parse(height)?)))
+        .ok_or_else(|| format!("Invalid size {}, expected <width>x<height> with a width and height of at least 1", size))
+}
+
 fn main() {
+    let mut object_handeler = object_handler::ObjectHandeler::new();
+
+    // create camera 
+    let mut camera = Camera::new();
+    camera.pos = [0.0, 1.0, -3.0];
+    camera.set_rotation_axis([0.0, 1.0, 0.0]);
+
+    // frames to render for the benchmark, without any frames there is nothing to time
+    let benchmark_frames = get_arg("--benchmark").map(|frames| match frames.parse::<u32>() {
+        Ok(num_of_frames) if num_of_frames > 0 => num_of_frames,
+        _ => {
+            eprintln!("Invalid number of frames {}, expected a whole number of at least 1", frames);
+            std::process::exit(1);
+        },
+    });
+
+    // a scene file that can not be opened is reported, and the window starts with the default scene instead.
+    // a render or benchmark of the default scene would look like it worked, so they exit
+    let headless = benchmark_frames.is_some() || get_arg("--render").is_some();
+    let document = get_arg("--scene").and_then(|path| match SceneDocument::load(&path) {
+        Ok(document) => Some(document),
+        Err(err) if headless => {
+            eprintln!("Failed to open scene {}: {}", path, err);
+            std::process::exit(1);
+        },
+        Err(err) => {
+            eprintln!("Failed to open scene {}: {}, starting with the default scene", path, err);
+            None
+        }
+    });
+
+    // Setup scene, either from a scene file or the default one
+    match document {
+        Some(document) => document.apply(&mut object_handeler, &mut camera),
+        None if benchmark_frames.is_some() => benchmark::create_scene(&mut object_handeler),
+        None => {
+            let spheres = init_spheres();
+            let triangles : Vec<Triangle> = vec![Triangle::new([-1.5, -1.5, 1.0], [-1.5, 1.5, 1.0], [1.5, -1.5, 1.0], [0.8078, 0.1647, 0.3569])];
+            let cubes : Vec<Cube> = vec![Cube::new([0.0, 0.0, 0.0], [0.4, 2.0, 0.4], [0.8078, 0.1647, 0.3569])];
+            let menger_sponges : Vec<MengerSponge> = vec![MengerSponge::new([0.0, 0.0, 0.0], 10.0, [0.8078, 0.1647, 0.3569]), MengerSponge::new([2.0, 0.0, 0.0], 10.0, [0.8078, 0.1647, 0.3569])];
+            object_handeler.add_spheres_from(spheres);
+            object_handeler.add_menger_sponges_from(menger_sponges);
+        }
+    }
+
+    // render the scene on the cpu to an image and exit, without opening a window
+    if let Some(path) = get_arg("--render") {
+        let (width, height) = get_size_arg().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        });
+        object_handeler.update_bvh();
+        if let Err(err) = cpu_renderer::render_to_png(&object_handeler, &camera, width, height, &path) {
+            eprintln!("Failed to render {}: {}", path, err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // setup glinum and window
     let event_loop = EventLoopBuilder::with_user_event().build().unwrap();
     let (window, display) = create_display(&event_loop);
 
-    // setup gui and objects
+    // setup gui
     let mut gui_handeler = gui::gui::GuiHandeler::new(egui_glium::EguiGlium::new(ViewportId::ROOT, &display, &window, &event_loop));
-    let mut object_handeler = object_handler::ObjectHandeler::new();
-
-    // Setup scene
-    let spheres = init_spheres();
-    let triangles : Vec<Triangle> = vec![Triangle::new([-1.5, -1.5, 1.0], [-1.5, 1.5, 1.0], [1.5, -1.5, 1.0], [0.8078, 0.1647, 0.3569])];
-    let cubes : Vec<Cube> = vec![Cube::new([0.0, 0.0, 0.0], [0.4, 2.0, 0.4], [0.8078, 0.1647, 0.3569])];
-    let menger_sponges : Vec<MengerSponge> = vec![MengerSponge::new([0.0, 0.0, 0.0], 10.0, [0.8078, 0.1647, 0.3569]), MengerSponge::new([2.0, 0.0, 0.0], 10.0, [0.8078, 0.1647, 0.3569])];
-    object_handeler.add_spheres_from(spheres);
-    object_handeler.add_menger_sponges_from(menger_sponges);
-
     
     // building the vertex buffer, which contains all the vertices that we will draw
     let vertex_buffer = {
@@ -77,31 +169,36 @@ fn main() {
     // building the index buffer - indices
     let index_buffer = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
 
-    // compiling shaders and linking them together
-    let source_vertex = fs::read_to_string("shaders/vertex.glsl").expect("Failed to read shader file");
-    let source_fragment = fs::read_to_string("shaders/fragment.glsl").expect("Failed to read shader file");
-
-    // load shaders
-    let program = glium::Program::from_source(&display, &source_vertex, &source_fragment, None).unwrap();
+    // compiling shaders and linking them together, if they do not compile the window shows why
+    // --shaders reads them from a directory and reloads them when they change, otherwise the ones in the binary are used
+    let shader_dir = get_arg("--shaders").map(PathBuf::from).or_else(shader_files::development_dir);
+    let mut shader_handler = ShaderHandler::new(&display, shader_dir);
+    gui_handeler.set_shader_error(shader_handler.get_error().cloned());
 
     // from demo code
     // In this case we use a closure for simplicity, however keep in mind that most serious
     // applications should probably use a function that takes the resources as an argument.
-    let _ptr = program.get_frag_data_location("f_color").unwrap(); // will be zero; internal glium location for f_color that is "out" for fragment shader
+    let _ptr = shader_handler.get_program().get_frag_data_location("f_color").unwrap(); // will be zero; internal glium location for f_color that is "out" for fragment shader
 
-    // load sphere and triangle uniform buffer
-    let mut sphere_array = object_handeler.get_uniform_buffer_spheres(&display);
-    let mut triangle_array = object_handeler.get_uniform_buffer_triangles(&display);
-    let mut cube_array = object_handeler.get_uniform_buffer_cubes(&display);
-    let mut menger_sponge_array = object_handeler.get_uniform_buffer_menger_sponges(&display);
+    // upload the objects to the gpu
+    let mut gpu_storage = GpuStorage::new(&display, &mut object_handeler).unwrap_or_else(|err| panic!("Failed to upload scene: {}", err));
 
-    // create camera 
-    let mut camera = Camera::new();
-    camera.pos = [0.0, 1.0, -3.0];
-    camera.set_rotation_axis([0.0, 1.0, 0.0]);
+    if let Some(frames) = benchmark_frames {
+        if let Some(err) = shader_handler.get_error() {
+            panic!("Failed to compile the shaders: {}", err);
+        }
+        benchmark::run(&mut object_handeler, frames, |object_handeler| {
+            let mut target = display.draw();
+            target.clear_color(0.0, 0.0, 0.0, 1.0);
+            draw_scene(&mut target, &vertex_buffer, &index_buffer, shader_handler.get_program(), object_handeler, &gpu_storage, &camera);
+            target.finish().unwrap();
+            // waits for the gpu, otherwise only the time to queue the frame is measured
+            display.finish();
+        });
+        return;
+    }
 
     let mut should_quit = false;
-    let mut should_update_objects = false;
     let result = event_loop.run(move |event, target| {
 
         let start = Instant::now();
@@ -112,14 +209,29 @@ fn main() {
                 target.exit() // exit program/window
             }
 
+            // shader files edited on disk are compiled again, the error is shown until they compile
+            if shader_handler.reload_if_changed(&display) {
+                gui_handeler.set_shader_error(shader_handler.get_error().cloned());
+            }
+
             // change gui
-            gui_handeler.update_gui(&window, &mut object_handeler, &mut should_update_objects, camera);
+            gui_handeler.update_gui(&window, &mut object_handeler, camera);
 
-            if should_update_objects {
-                sphere_array = object_handeler.get_uniform_buffer_spheres(&display);
-                triangle_array = object_handeler.get_uniform_buffer_triangles(&display);
-                cube_array = object_handeler.get_uniform_buffer_cubes(&display);
-                menger_sponge_array = object_handeler.get_uniform_buffer_menger_sponges(&display);
+            // only the objects that changed since the last frame are written to the gpu
+            // if the scene does not fit on the gpu the old buffers are kept, and the gui shows why
+            match gpu_storage.update(&display, &mut object_handeler) {
+                Ok(()) => gui_handeler.set_gpu_error(None),
+                Err(err) => gui_handeler.set_gpu_error(Some(err.to_string())),
+            }
+
+            // the node graph replaces objectsDist in the fragment shader, if the new shader does not compile the old one is kept
+            if let Some(min_dist) = gui_handeler.take_graph_min_dist() {
+                let result = shader_handler.set_graph_min_dist(&display, min_dist);
+                gui_handeler.set_graph_error(result.err());
+            }
+            // a shader generated for the scene that does not compile is shown like the shader files
+            if shader_handler.update(&display, &object_handeler) {
+                gui_handeler.set_shader_error(shader_handler.get_error().cloned());
             }
 
             if should_quit {
@@ -127,56 +239,12 @@ fn main() {
             }
 
             {
-                use glium::Surface as _;
                 let mut target = display.draw();
 
                 let color = egui::Rgba::from_rgb(0.0, 0.0, 0.0);
                 target.clear_color(color[0], color[1], color[2], color[3]);
 
-                let u_resolution = [window.inner_size().width as f32, window.inner_size().height as f32];
-                let num_of_spheres = object_handeler.get_num_of_spheres() as i32;
-                let num_of_triangles = object_handeler.get_num_of_triangles() as i32;
-                let num_of_boxes = object_handeler.get_num_of_cubes() as i32;
-                let num_of_menger_sponges = object_handeler.get_num_of_menger_sponges() as i32;
-                let render_mode = object_handeler.get_render_mode() as i32;
-                let light_pos = [300.0f32, 100.0f32, 50.0f32];
-                let smoothness = 0.9 as f32;
-
-                // a bug requires us to have the matrix as a uniform, even when we dont need the matrix in the shader, which is really wierd
-                let matrix = [
-                    [1.0, 0.0, 0.0, 0.0],
-                    [0.0, 1.0, 0.0, 0.0],
-                    [0.0, 0.0, 1.0, 0.0],
-                    [0.0, 0.0, 0.0, 1.0f32]
-                ];
-
-                target.draw(
-                    &vertex_buffer, 
-                    &index_buffer, 
-                    &program, 
-
-                    // a bug requires us to have the matrix as a uniform, even when we dont need the matrix in the shader, which is really wierd
-                    &uniform! {
-                        // Format: name of uniform (in glsl) | resource/data
-                        matrix : matrix, 
-                        u_resolution : u_resolution, 
-                        numOfSpheres : num_of_spheres, 
-                        numOfTriangles : num_of_triangles, 
-                        numOfBoxes : num_of_boxes,
-                        numOfMengerSponges : num_of_menger_sponges,
-                        renderMode : render_mode,
-                        smoothness : smoothness,
-                        lightPos : light_pos,
-                        cameraPos : camera.pos,
-                        cameraRotationQuaternion : camera.get_rotation_quaternion(), 
-                        cameraFOV : camera.fov,
-                        sphere_array : &*sphere_array, 
-                        triangle_array : &*triangle_array,
-                        cube_array : &*cube_array,
-                        menger_sponge_array : &*menger_sponge_array,
-                    }, 
-                    &Default::default()
-                ).unwrap();
+                draw_scene(&mut target, &vertex_buffer, &index_buffer, shader_handler.get_program(), &object_handeler, &gpu_storage, camera);
 
                 // draw things behind egui here
                 gui_handeler.render(&display, &mut target);
@@ -209,8 +277,12 @@ fn main() {
                 }
                 
             }
-            event::Event::NewEvents(event::StartCause::ResumeTimeReached { .. }) => {
-                window.request_redraw();
+            event::Event::NewEvents(event::StartCause::Init | event::StartCause::ResumeTimeReached { .. }) => {
+                // a shader file has changed, or a shader generated for the scene is ready to be compiled
+                if shader_handler.needs_redraw() {
+                    window.request_redraw();
+                }
+                target.set_control_flow(ControlFlow::WaitUntil(Instant::now() + shader_handler::POLL_INTERVAL));
             }
             _ => (),
         }
@@ -221,6 +293,139 @@ fn main() {
     result.unwrap()
 }
 
+// draws the ray marched scene over the whole target
+fn draw_scene<S : Surface>(target : &mut S, vertex_buffer : &glium::VertexBuffer<impl glium::Vertex>, index_buffer : &glium::index::NoIndices,
+                           program : &glium::Program, object_handeler : &ObjectHandeler, gpu_storage : &GpuStorage, camera : &Camera) {
+
+    let (width, height) = target.get_dimensions();
+    let u_resolution = [width as f32, height as f32];
+    let num_of_spheres = gpu_storage.get_num_of(ObjectKind::Sphere) as i32;
+    let num_of_triangles = gpu_storage.get_num_of(ObjectKind::Triangle) as i32;
+    let num_of_boxes = gpu_storage.get_num_of(ObjectKind::Cube) as i32;
+    let num_of_menger_sponges = gpu_storage.get_num_of(ObjectKind::MengerSponge) as i32;
+    let num_of_meshes = gpu_storage.get_num_of(ObjectKind::Mesh) as i32;
+    let num_of_tori = gpu_storage.get_num_of(ObjectKind::Torus) as i32;
+    let num_of_capsules = gpu_storage.get_num_of(ObjectKind::Capsule) as i32;
+    let num_of_cylinders = gpu_storage.get_num_of(ObjectKind::Cylinder) as i32;
+    let num_of_cones = gpu_storage.get_num_of(ObjectKind::Cone) as i32;
+    let use_bvh = object_handeler.get_use_bvh();
+
+    // the objects from the ordered start on are not in the bvh, an outdated bvh is not used at all
+    let bvh = object_handeler.get_bvh();
+    let num_of_bvh_nodes = if bvh.is_some() { gpu_storage.get_num_of_bvh_nodes() as i32 } else { 0 };
+    let ordered_start = bvh.map_or(0, |bvh| bvh.get_ordered_start() as i32);
+    let num_of_objects = gpu_storage.get_num_of_objects() as i32;
+    let num_of_lights = gpu_storage.get_num_of_lights() as i32;
+    // the images repeat, and are filtered like the bilinear sampling of the cpu renderer
+    let texture_images = gpu_storage.texture_images.sampled()
+        .wrap_function(SamplerWrapFunction::Repeat)
+        .minify_filter(MinifySamplerFilter::Linear)
+        .magnify_filter(MagnifySamplerFilter::Linear);
+    let num_of_texture_images = gpu_storage.get_num_of_texture_images() as i32;
+    let use_shadows = object_handeler.get_use_shadows();
+    let shadow_softness = object_handeler.get_shadow_softness();
+    let ambient_occlusion = object_handeler.get_ambient_occlusion();
+    let max_bounces = object_handeler.get_max_bounces() as i32;
+    let sky = object_handeler.get_sky();
+    // an image that does not fit on the gpu is not shown, the gradient is used instead
+    let sky_kind = if gpu_storage.has_sky_images(sky.kind) { sky.kind } else { SkyKind::Gradient };
+    // the faces of the cubemap stop at their edges like on the cpu, panoramas go around from left to right
+    let sky_filter = |wrap_s| SamplerBehavior {
+        wrap_function : (wrap_s, SamplerWrapFunction::Clamp, SamplerWrapFunction::Clamp),
+        minify_filter : MinifySamplerFilter::Linear,
+        magnify_filter : MagnifySamplerFilter::Linear,
+        ..Default::default()
+    };
+    let sky_cubemap = Sampler(&gpu_storage.sky_cubemap, sky_filter(SamplerWrapFunction::Clamp));
+    let sky_panorama = Sampler(&gpu_storage.sky_panorama, sky_filter(SamplerWrapFunction::Repeat));
+    let sky_irradiance = object_handeler.get_sky_irradiance();
+    let ground = object_handeler.get_ground();
+    let (selected_type, selected_index) = object_handeler.get_selected_uniform();
+
+    // a bug requires us to have the matrix as a uniform, even when we dont need the matrix in the shader, which is really wierd
+    let matrix = [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0f32]
+    ];
+
+    target.draw(
+        vertex_buffer, 
+        index_buffer, 
+        program, 
+
+        // a bug requires us to have the matrix as a uniform, even when we dont need the matrix in the shader, which is really wierd
+        &uniform! {
+            // Format: name of uniform (in glsl) | resource/data
+            matrix : matrix, 
+            u_resolution : u_resolution, 
+            numOfSpheres : num_of_spheres, 
+            numOfTriangles : num_of_triangles, 
+            numOfBoxes : num_of_boxes,
+            numOfMengerSponges : num_of_menger_sponges,
+            numOfMeshes : num_of_meshes,
+            numOfTori : num_of_tori,
+            numOfCapsules : num_of_capsules,
+            numOfCylinders : num_of_cylinders,
+            numOfCones : num_of_cones,
+            numOfBvhNodes : num_of_bvh_nodes,
+            useBvh : use_bvh,
+            orderedStart : ordered_start,
+            numOfObjects : num_of_objects,
+            numOfLights : num_of_lights,
+            useShadows : use_shadows,
+            shadowSoftness : shadow_softness,
+            aoSteps : ambient_occlusion.active_steps() as i32,
+            aoStrength : ambient_occlusion.strength,
+            aoDebug : ambient_occlusion.debug_view,
+            maxBounces : max_bounces,
+            skyKind : sky_kind as i32,
+            skyZenith : sky.zenith,
+            skyHorizon : sky.horizon,
+            skyGround : sky.ground,
+            skyIntensity : sky.intensity,
+            skyAmbient : sky.ambient,
+            skyCubemap : sky_cubemap,
+            skyPanorama : sky_panorama,
+            skyIrradiance0 : [sky_irradiance[0], sky_irradiance[1], sky_irradiance[2]],
+            skyIrradiance1 : [sky_irradiance[3], sky_irradiance[4], sky_irradiance[5]],
+            skyIrradiance2 : [sky_irradiance[6], sky_irradiance[7], sky_irradiance[8]],
+            groundEnabled : ground.enabled,
+            groundNormal : ground.up(),
+            groundHeight : ground.height,
+            groundColor : ground.color,
+            groundSecondColor : ground.second_color,
+            groundMaterial : ground.material as i32,
+            groundPattern : [ground.pattern as i32, ground.image as i32],
+            groundTileSize : ground.tile_size,
+            groundFadeDistance : ground.fade_distance,
+            selectedType : selected_type,
+            selectedIndex : selected_index,
+            cameraPos : camera.pos,
+            cameraRotationQuaternion : camera.get_rotation_quaternion(), 
+            cameraFOV : camera.fov,
+            sphere_array : gpu_storage.spheres.get_buffer(), 
+            triangle_array : gpu_storage.triangles.get_buffer(),
+            cube_array : gpu_storage.cubes.get_buffer(),
+            menger_sponge_array : gpu_storage.menger_sponges.get_buffer(),
+            mesh_array : gpu_storage.meshes.get_buffer(),
+            mesh_triangle_array : gpu_storage.mesh_triangles.get_buffer(),
+            torus_array : gpu_storage.tori.get_buffer(),
+            capsule_array : gpu_storage.capsules.get_buffer(),
+            cylinder_array : gpu_storage.cylinders.get_buffer(),
+            cone_array : gpu_storage.cones.get_buffer(),
+            bvh_nodes : gpu_storage.bvh_nodes.get_buffer(),
+            object_order : gpu_storage.object_order.get_buffer(),
+            light_array : gpu_storage.lights.get_buffer(),
+            material_array : gpu_storage.materials.get_buffer(),
+            textureImages : texture_images,
+            numOfTextureImages : num_of_texture_images,
+        }, 
+        &Default::default()
+    ).unwrap();
+}
+
 // from demo code
 fn create_display(
     event_loop: &EventLoop<()>,
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+// how the surface of an object looks, the color comes from the object. Objects use a material from the library
+// in the object handeler, so that objects made of the same thing can be changed together
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Material {
+    // 0 is a sharp highlight and 1 spreads it over the whole side facing the light
+    pub roughness : f32,
+    // metals have no diffuse color, their highlights get the color of the object instead
+    pub metallic : f32,
+    // how bright the highlights of non metals are, 0.5 is what most materials have
+    pub specular : f32,
+    // light that the surface gives off by itself, in its own color
+    pub emissive : f32,
+    // how much of the light is reflected like in a mirror, from 0 to 1
+    pub reflectivity : f32,
+    // how much of the light that is not reflected goes through the object, tinted by its color
+    pub transparency : f32,
+    // index of refraction, how much rays bend when they go into the object
+    pub ior : f32,
+}
+
+impl Material {
+    pub const DEFAULT : Material = Material {
+        roughness : 0.5, metallic : 0.0, specular : 0.5, emissive : 0.0,
+        reflectivity : 0.0, transparency : 0.0, ior : 1.5,
+    };
+
+    // what the distance functions start from before the first object, like the color
+    pub const ZERO : Material = Material {
+        roughness : 0.0, metallic : 0.0, specular : 0.0, emissive : 0.0,
+        reflectivity : 0.0, transparency : 0.0, ior : 0.0,
+    };
+
+    // glsl mix of every value, h is how much of other is used
+    pub fn mix(&self, other : &Material, h : f32) -> Material {
+        let mix = |a : f32, b : f32| a + (b - a) * h;
+        Material {
+            roughness : mix(self.roughness, other.roughness),
+            metallic : mix(self.metallic, other.metallic),
+            specular : mix(self.specular, other.specular),
+            emissive : mix(self.emissive, other.emissive),
+            reflectivity : mix(self.reflectivity, other.reflectivity),
+            transparency : mix(self.transparency, other.transparency),
+            ior : mix(self.ior, other.ior),
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::DEFAULT
+    }
+}
+
+// a material in the library, objects refer to it by its index and scene files by its name, which no other material in the library has
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryMaterial {
+    pub name : String,
+    pub material : Material,
+}
+
+impl LibraryMaterial {
+    pub fn new(name : impl Into<String>, material : Material) -> Self {
+        LibraryMaterial { name : name.into(), material }
+    }
+}
+
+// the library of a new scene, the first material is used by new objects and can not be removed
+pub fn default_library() -> Vec<LibraryMaterial> {
+    vec![LibraryMaterial::new("Default", Material::DEFAULT)]
+}
+
+// the name, or the name with a number after it when another material than the one at `index` has it
+pub fn unique_material_name(materials : &[LibraryMaterial], index : usize, name : &str) -> String {
+    let taken = |name : &str| materials.iter().enumerate().any(|(i, material)| i != index && material.name == name);
+    if !taken(name) {
+        return name.to_string();
+    }
+    (2..).map(|n| format!("{} ({})", name, n)).find(|name| !taken(name)).unwrap()
+}
+
+// a name that more than one material in the library has, scene files can not refer to those
+pub fn duplicate_material_name(materials : &[LibraryMaterial]) -> Option<&str> {
+    materials.iter().enumerate()
+        .find(|(i, material)| materials[..*i].iter().any(|other| other.name == material.name))
+        .map(|(_, material)| material.name.as_str())
+}
@@ -0,0 +1,397 @@
+// a graph of sdf nodes that is compiled to glsl, the compiled objectsDist replaces the one in the fragment shader
+// every node except the output gives a distance and a color, like objectsDist does, and a material
+
+use std::collections::HashMap;
+use std::fmt;
+
+use egui_snarl::{InPinId, NodeId, Snarl};
+
+use crate::shader_gen::{float, vec3, vec4};
+use crate::shapes::Combine;
+use crate::vec_util::quaternion_from_euler;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SdfNode {
+    // the distance that is rendered, a graph has exactly one
+    Output,
+    // the objects from the side panel, combined the same way as without the graph
+    Scene,
+    Sphere { radius : f32, color : [f32; 3] },
+    Cube { size : [f32; 3], color : [f32; 3] },
+    Torus { radius : f32, thickness : f32, color : [f32; 3] },
+    Capsule { half_height : f32, radius : f32, color : [f32; 3] },
+    Cylinder { half_height : f32, radius : f32, color : [f32; 3] },
+    Translate { offset : [f32; 3] },
+    // in degrees, applied in the same order as the object rotations
+    Rotate { angles : [f32; 3] },
+    Scale { factor : f32 },
+    // axes with a spacing of 0 are not repeated
+    Repeat { spacing : [f32; 3] },
+    Mirror { axes : [bool; 3] },
+    // turns the input around the y axis, amount is in radians per unit of height
+    Twist { amount : f32 },
+    Round { radius : f32 },
+    // the second input is combined with the first one, like an object with the objects before it
+    Combine(Combine),
+}
+
+impl SdfNode {
+    // one of each node that can be added to a graph, with default values
+    pub fn addable() -> Vec<SdfNode> {
+        let color = [0.8078, 0.1647, 0.3569];
+        vec![
+            SdfNode::Scene,
+            SdfNode::Sphere { radius : 0.5, color },
+            SdfNode::Cube { size : [0.5; 3], color },
+            SdfNode::Torus { radius : 0.5, thickness : 0.15, color },
+            SdfNode::Capsule { half_height : 0.5, radius : 0.25, color },
+            SdfNode::Cylinder { half_height : 0.5, radius : 0.4, color },
+            SdfNode::Translate { offset : [0.0; 3] },
+            SdfNode::Rotate { angles : [0.0; 3] },
+            SdfNode::Scale { factor : 1.0 },
+            SdfNode::Repeat { spacing : [2.0, 0.0, 2.0] },
+            SdfNode::Mirror { axes : [true, false, false] },
+            SdfNode::Twist { amount : 1.0 },
+            SdfNode::Round { radius : 0.1 },
+            SdfNode::Combine(Combine::UNION),
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SdfNode::Output => "Output",
+            SdfNode::Scene => "Scene objects",
+            SdfNode::Sphere { .. } => "Sphere",
+            SdfNode::Cube { .. } => "Cube",
+            SdfNode::Torus { .. } => "Torus",
+            SdfNode::Capsule { .. } => "Capsule",
+            SdfNode::Cylinder { .. } => "Cylinder",
+            SdfNode::Translate { .. } => "Translate",
+            SdfNode::Rotate { .. } => "Rotate",
+            SdfNode::Scale { .. } => "Scale",
+            SdfNode::Repeat { .. } => "Repeat",
+            SdfNode::Mirror { .. } => "Mirror",
+            SdfNode::Twist { .. } => "Twist",
+            SdfNode::Round { .. } => "Round",
+            SdfNode::Combine(_) => "Combine",
+        }
+    }
+
+    pub fn input_names(&self) -> &'static [&'static str] {
+        match self {
+            SdfNode::Output => &["Distance"],
+            SdfNode::Scene | SdfNode::Sphere { .. } | SdfNode::Cube { .. } | SdfNode::Torus { .. } | SdfNode::Capsule { .. } | SdfNode::Cylinder { .. } => &[],
+            SdfNode::Combine(_) => &["A", "B"],
+            _ => &["Input"],
+        }
+    }
+
+    pub fn num_of_outputs(&self) -> usize {
+        match self {
+            SdfNode::Output => 0,
+            _ => 1,
+        }
+    }
+}
+
+// a graph with the scene objects connected to the output, which renders the same as the shader without a graph
+pub fn default_graph() -> Snarl<SdfNode> {
+    let mut graph = Snarl::new();
+    let scene = graph.insert_node(egui::pos2(0.0, 0.0), SdfNode::Scene);
+    let output = graph.insert_node(egui::pos2(250.0, 0.0), SdfNode::Output);
+    graph.connect(egui_snarl::OutPinId { node : scene, output : 0 }, InPinId { node : output, input : 0 });
+    graph
+}
+
+#[derive(Debug)]
+pub enum GraphError {
+    NoOutput,
+    NotConnected { node : &'static str, input : &'static str },
+    Cycle,
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::NoOutput => write!(f, "the graph has no output node"),
+            GraphError::NotConnected { node, input } => write!(f, "the {} input of a {} node is not connected", input, node),
+            GraphError::Cycle => write!(f, "the graph has a cycle"),
+        }
+    }
+}
+
+// the glsl of the objectsDist functions that give the distance of the node connected to the output, the ground is added to it in the shader.
+// The scene objects keep their materials and textures, the primitives of the graph have the default material and no texture
+pub fn compile(graph : &Snarl<SdfNode>) -> Result<String, GraphError> {
+    let (output, _) = graph.node_ids().find(|(_, node)| **node == SdfNode::Output).ok_or(GraphError::NoOutput)?;
+
+    let mut compiler = Compiler { graph, code : String::new(), num_of_vars : 0, visiting : Vec::new(), compiled : HashMap::new() };
+    let result = compiler.node(output, "pos")?;
+
+    Ok(format!("vec4 objectsDist(vec3 pos, vec3 norm, out Material material) {{\n{}    material = {};\n    return {};\n}}\n\n\
+        vec4 objectsDist(vec3 pos) {{\n    Material material;\n    return objectsDist(pos, vec3(0.0), material);\n}}\n", compiler.code, result.material, result.d))
+}
+
+// the variable with the distance and color of a compiled node, and the material at that distance
+#[derive(Clone)]
+struct NodeDist {
+    d : String,
+    material : String,
+}
+
+// a node used by more than one input is compiled once for every position it is used at, the positions can be different
+struct Compiler<'a> {
+    graph : &'a Snarl<SdfNode>,
+    code : String,
+    num_of_vars : usize,
+    // the nodes between the output and the current node, to find cycles
+    visiting : Vec<NodeId>,
+    // the variables of every node that has been compiled, by the variable of the position it was compiled at.
+    // all variables are declared at the top level of the function, so they can be used again anywhere after it
+    compiled : HashMap<(NodeId, String), NodeDist>,
+}
+
+impl Compiler<'_> {
+    fn var(&mut self, name : &str) -> String {
+        self.num_of_vars += 1;
+        format!("{}{}", name, self.num_of_vars)
+    }
+
+    fn line(&mut self, line : String) {
+        self.code.push_str("    ");
+        self.code.push_str(&line);
+        self.code.push('\n');
+    }
+
+    // the variables with the distance, color and material of the node at pos
+    fn node(&mut self, id : NodeId, pos : &str) -> Result<NodeDist, GraphError> {
+        if self.visiting.contains(&id) {
+            return Err(GraphError::Cycle);
+        }
+        if let Some(d) = self.compiled.get(&(id, pos.to_string())) {
+            return Ok(d.clone());
+        }
+
+        self.visiting.push(id);
+        let result = self.node_code(id, pos);
+        self.visiting.pop();
+
+        let d = result?;
+        self.compiled.insert((id, pos.to_string()), d.clone());
+        Ok(d)
+    }
+
+    fn input(&mut self, id : NodeId, input : usize, pos : &str) -> Result<NodeDist, GraphError> {
+        let node = &self.graph[id];
+        let remote = self.graph.in_pin(InPinId { node : id, input }).remotes.first().copied()
+            .ok_or(GraphError::NotConnected { node : node.name(), input : node.input_names()[input] })?;
+
+        self.node(remote.node, pos)
+    }
+
+    fn node_code(&mut self, id : NodeId, pos : &str) -> Result<NodeDist, GraphError> {
+        let node = self.graph[id].clone();
+
+        // primitives are placed at the origin, and moved with the transform nodes
+        let shape = |this : &mut Self, dist : String, color : [f32; 3]| {
+            let d = this.var("d");
+            this.line(format!("vec4 {} = vec4({}, {});", d, dist, vec3(color)));
+            NodeDist { d, material : String::from("DEFAULT_MATERIAL") }
+        };
+
+        let d = match node {
+            SdfNode::Output => self.input(id, 0, pos)?,
+            // the textures are projected at the moved position, with the normal of the surface where the ray is
+            SdfNode::Scene => {
+                let material = self.var("material");
+                let d = self.var("d");
+                self.line(format!("Material {};", material));
+                self.line(format!("vec4 {} = sceneDist({}, norm, {});", d, pos, material));
+                NodeDist { d, material }
+            },
+            SdfNode::Sphere { radius, color } => {
+                shape(self, format!("sphereDist(Sphere(vec3(0.0), {}, {}, IDENTITY_TRANSFORM), {})", vec3(color), float(radius), pos), color)
+            },
+            SdfNode::Cube { size, color } => {
+                shape(self, format!("cubeDist(Cube(vec3(0.0), {}, {}, IDENTITY_TRANSFORM), {})", vec3(size), vec3(color), pos), color)
+            },
+            SdfNode::Torus { radius, thickness, color } => {
+                shape(self, format!("torusDist(Torus(vec3(0.0), {}, {}, {}, IDENTITY_TRANSFORM), {})", float(radius), float(thickness), vec3(color), pos), color)
+            },
+            SdfNode::Capsule { half_height, radius, color } => {
+                shape(self, format!("capsuleDist(Capsule(vec3(0.0), {}, {}, {}, IDENTITY_TRANSFORM), {})", float(half_height), float(radius), vec3(color), pos), color)
+            },
+            SdfNode::Cylinder { half_height, radius, color } => {
+                shape(self, format!("cylinderDist(Cylinder(vec3(0.0), {}, {}, {}, IDENTITY_TRANSFORM), {})", float(half_height), float(radius), vec3(color), pos), color)
+            },
+            SdfNode::Translate { offset } => {
+                let p = self.var("p");
+                self.line(format!("vec3 {} = {} - {};", p, pos, vec3(offset)));
+                self.input(id, 0, &p)?
+            },
+            SdfNode::Rotate { angles } => {
+                let rotation = quaternion_from_euler(angles.map(f32::to_radians));
+                let p = self.var("p");
+                self.line(format!("vec3 {} = untransform(Transform({}, vec3(1.0)), vec3(0.0), {});", p, vec4(rotation), pos));
+                self.input(id, 0, &p)?
+            },
+            SdfNode::Scale { factor } => {
+                let p = self.var("p");
+                self.line(format!("vec3 {} = {} / {};", p, pos, float(factor)));
+                let input = self.input(id, 0, &p)?;
+
+                let d = self.var("d");
+                self.line(format!("vec4 {} = vec4({}.x * {}, {}.yzw);", d, input.d, float(factor.abs()), input.d));
+                NodeDist { d, material : input.material }
+            },
+            SdfNode::Repeat { spacing } => {
+                let p = self.var("p");
+                self.line(format!("vec3 {} = {};", p, pos));
+                for (axis, spacing) in ["x", "y", "z"].iter().zip(spacing) {
+                    if spacing > 0.0 {
+                        self.line(format!("{}.{} -= {} * round({}.{} / {});", p, axis, float(spacing), p, axis, float(spacing)));
+                    }
+                }
+                self.input(id, 0, &p)?
+            },
+            SdfNode::Mirror { axes } => {
+                let p = self.var("p");
+                self.line(format!("vec3 {} = {};", p, pos));
+                for (axis, mirrored) in ["x", "y", "z"].iter().zip(axes) {
+                    if mirrored {
+                        self.line(format!("{}.{} = abs({}.{});", p, axis, p, axis));
+                    }
+                }
+                self.input(id, 0, &p)?
+            },
+            SdfNode::Twist { amount } => {
+                let angle = self.var("angle");
+                let p = self.var("p");
+                self.line(format!("float {} = {} * {}.y;", angle, float(amount), pos));
+                self.line(format!("vec3 {p} = vec3(cos({a}) * {pos}.x - sin({a}) * {pos}.z, {pos}.y, sin({a}) * {pos}.x + cos({a}) * {pos}.z);", p = p, a = angle, pos = pos));
+                let input = self.input(id, 0, &p)?;
+
+                // the twist stretches the space by up to sqrt(1 + (amount * r)^2) at a distance r from the axis
+                let d = self.var("d");
+                self.line(format!("vec4 {} = vec4({}.x / sqrt(1.0 + {} * dot({}.xz, {}.xz)), {}.yzw);", d, input.d, float(amount * amount), pos, pos, input.d));
+                NodeDist { d, material : input.material }
+            },
+            SdfNode::Round { radius } => {
+                let input = self.input(id, 0, pos)?;
+                let d = self.var("d");
+                self.line(format!("vec4 {} = vec4({}.x - {}, {}.yzw);", d, input.d, float(radius), input.d));
+                NodeDist { d, material : input.material }
+            },
+            SdfNode::Combine(combine) => {
+                let a = self.input(id, 0, pos)?;
+                let b = self.input(id, 1, pos)?;
+
+                let dst = self.var("dst");
+                let clr = self.var("clr");
+                let material = self.var("material");
+                let d = self.var("d");
+                self.line(format!("float {} = {}.x;", dst, a.d));
+                self.line(format!("vec3 {} = {}.yzw;", clr, a.d));
+                self.line(format!("Material {} = {};", material, a.material));
+                self.line(format!("combine(vec2({}, {}), {}.x, {}.yzw, {}, {}, {}, {});",
+                    float(combine.operation as i32 as f32), float(combine.blend_radius), b.d, b.d, b.material, dst, clr, material));
+                self.line(format!("vec4 {} = vec4({}, {});", d, dst, clr));
+                NodeDist { d, material }
+            },
+        };
+
+        Ok(d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui_snarl::OutPinId;
+    use super::*;
+
+    fn add(graph : &mut Snarl<SdfNode>, node : SdfNode) -> NodeId {
+        graph.insert_node(egui::pos2(0.0, 0.0), node)
+    }
+
+    fn connect(graph : &mut Snarl<SdfNode>, from : NodeId, to : NodeId, input : usize) {
+        graph.connect(OutPinId { node : from, output : 0 }, InPinId { node : to, input });
+    }
+
+    fn sphere() -> SdfNode {
+        SdfNode::Sphere { radius : 0.5, color : [1.0; 3] }
+    }
+
+    // an output with a combine node connected to it, the inputs of the combine are not connected yet
+    fn combine_graph() -> (Snarl<SdfNode>, NodeId) {
+        let mut graph = Snarl::new();
+        let output = add(&mut graph, SdfNode::Output);
+        let combine = add(&mut graph, SdfNode::Combine(Combine::UNION));
+        connect(&mut graph, combine, output, 0);
+        (graph, combine)
+    }
+
+    #[test]
+    fn default_graph_forwards_norm_and_material() {
+        let code = compile(&default_graph()).unwrap();
+        assert!(code.contains("vec4 d2 = sceneDist(pos, norm, material1);\n"), "{}", code);
+        assert!(code.contains("material = material1;\n    return d2;\n"), "{}", code);
+        assert!(code.contains("vec4 objectsDist(vec3 pos) {\n    Material material;\n    return objectsDist(pos, vec3(0.0), material);\n}"), "{}", code);
+    }
+
+    #[test]
+    fn combine_carries_the_materials() {
+        let (mut graph, combine) = combine_graph();
+        let scene = add(&mut graph, SdfNode::Scene);
+        let sphere = add(&mut graph, sphere());
+        connect(&mut graph, scene, combine, 0);
+        connect(&mut graph, sphere, combine, 1);
+
+        let code = compile(&graph).unwrap();
+        assert!(code.contains("Material material6 = material1;\n"), "{}", code);
+        assert!(code.contains("combine(vec2(0.0, 0.5), d3.x, d3.yzw, DEFAULT_MATERIAL, dst4, clr5, material6);\n"), "{}", code);
+        assert!(code.contains("material = material6;\n    return d7;\n"), "{}", code);
+    }
+
+    #[test]
+    fn shared_node_is_compiled_once_per_position() {
+        let (mut graph, combine) = combine_graph();
+        let sphere = add(&mut graph, sphere());
+        connect(&mut graph, sphere, combine, 0);
+        connect(&mut graph, sphere, combine, 1);
+
+        let code = compile(&graph).unwrap();
+        assert_eq!(code.matches("sphereDist(").count(), 1, "{}", code);
+
+        // moved it is at another position, so it has to be compiled again
+        let translate = add(&mut graph, SdfNode::Translate { offset : [1.0, 0.0, 0.0] });
+        graph.drop_inputs(InPinId { node : combine, input : 1 });
+        connect(&mut graph, sphere, translate, 0);
+        connect(&mut graph, translate, combine, 1);
+
+        let code = compile(&graph).unwrap();
+        assert_eq!(code.matches("sphereDist(").count(), 2, "{}", code);
+    }
+
+    #[test]
+    fn cycle_is_an_error() {
+        let mut graph = Snarl::new();
+        let output = add(&mut graph, SdfNode::Output);
+        let translate = add(&mut graph, SdfNode::Translate { offset : [1.0, 0.0, 0.0] });
+        let round = add(&mut graph, SdfNode::Round { radius : 0.1 });
+        connect(&mut graph, translate, output, 0);
+        connect(&mut graph, round, translate, 0);
+        connect(&mut graph, translate, round, 0);
+
+        assert!(matches!(compile(&graph), Err(GraphError::Cycle)));
+    }
+
+    #[test]
+    fn unconnected_input_is_an_error() {
+        let (mut graph, combine) = combine_graph();
+        let sphere = add(&mut graph, sphere());
+        connect(&mut graph, sphere, combine, 0);
+
+        assert!(matches!(compile(&graph), Err(GraphError::NotConnected { node : "Combine", input : "B" })));
+        assert!(matches!(compile(&Snarl::new()), Err(GraphError::NoOutput)));
+    }
+}
@@ -0,0 +1,191 @@
+// reads the triangles from wavefront .obj files, only the vertex positions and faces are used
+// https://paulbourke.net/dataformats/obj/
+
+use std::{fmt, fs, path::Path};
+
+use crate::bvh::Aabb;
+use crate::object_handler::{ObjectHandeler, ObjectId, SceneObject};
+use crate::sdf::triangle_normal;
+use crate::shapes::{Mesh, Triangle};
+use crate::vec_util::vec_add;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(std::io::Error),
+    Parse { line : usize, message : String },
+    NoTriangles,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "could not read obj file: {}", err),
+            ObjError::Parse { line, message } => write!(f, "invalid obj file, line {}: {}", line, message),
+            ObjError::NoTriangles => write!(f, "the obj file has no faces"),
+        }
+    }
+}
+
+impl From<std::io::Error> for ObjError {
+    fn from(err : std::io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+// the triangles of every face in the file, faces with more than three vertices are split into triangles
+pub fn parse(source : &str) -> Result<Vec<Triangle>, ObjError> {
+    let mut vertices : Vec<[f32; 3]> = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let error = |message : String| ObjError::Parse { line : line_number, message };
+
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => {
+                let mut vertex = [0.0; 3];
+                for coord in vertex.iter_mut() {
+                    let word = words.next().ok_or_else(|| error(String::from("a vertex needs three coordinates")))?;
+                    *coord = word.parse().map_err(|_| error(format!("{} is not a number", word)))?;
+                }
+                vertices.push(vertex);
+            },
+            Some("f") => {
+                let mut face = Vec::new();
+                for word in words {
+                    // a face vertex is v, v/vt, v//vn or v/vt/vn, only v is needed
+                    let index_word = word.split('/').next().unwrap_or(word);
+                    let index : i64 = index_word.parse().map_err(|_| error(format!("{} is not a vertex index", word)))?;
+
+                    // indices start at 1, negative ones count back from the last vertex
+                    let resolved = if index < 0 { vertices.len() as i64 + index } else { index - 1 };
+                    if resolved < 0 || resolved >= vertices.len() as i64 {
+                        return Err(error(format!("vertex {} does not exist", index)));
+                    }
+                    face.push(vertices[resolved as usize]);
+                }
+
+                if face.len() < 3 {
+                    return Err(error(String::from("a face needs at least three vertices")));
+                }
+
+                for j in 1..face.len() - 1 {
+                    let triangle = Triangle::new(face[0], face[j], face[j + 1], [1.0; 3]);
+
+                    // triangles without an area can not be seen, and they give nan in the distance function
+                    if triangle_normal(&triangle) != [0.0; 3] {
+                        triangles.push(triangle);
+                    }
+                }
+            },
+            // normals, texture coordinates, groups, materials and comments
+            _ => {},
+        }
+    }
+
+    Ok(triangles)
+}
+
+pub fn load(path : impl AsRef<Path>) -> Result<Vec<Triangle>, ObjError> {
+    parse(&fs::read_to_string(path)?)
+}
+
+// adds the triangles as a mesh, placed at the center of its vertices so that moving, rotating and scaling happens around the center
+pub fn add_mesh(object_handeler : &mut ObjectHandeler, mut triangles : Vec<Triangle>, color : [f32; 3]) -> Result<ObjectId, ObjError> {
+    if triangles.is_empty() {
+        return Err(ObjError::NoTriangles);
+    }
+
+    let center = Aabb::around_triangles(&triangles).center();
+
+    for triangle in triangles.iter_mut() {
+        triangle.v1 = vec_add(triangle.v1, center, -1.0);
+        triangle.v2 = vec_add(triangle.v2, center, -1.0);
+        triangle.v3 = vec_add(triangle.v3, center, -1.0);
+    }
+
+    let range = object_handeler.add_mesh_triangles(triangles);
+    let mesh = Mesh::new(center, color, range.start, range.len());
+    Ok(object_handeler.add_object(SceneObject::Mesh(mesh)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a square in the xy plane, with the normals and texture coordinates that exported files have
+    const SQUARE : &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vn 0 0 1
+";
+
+    fn vertices(triangle : &Triangle) -> [[f32; 3]; 3] {
+        [triangle.v1, triangle.v2, triangle.v3]
+    }
+
+    fn parse_error_line(source : &str) -> usize {
+        match parse(source) {
+            Err(ObjError::Parse { line, .. }) => line,
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn quad_is_split_into_a_fan() {
+        let triangles = parse(&format!("{}f 1 2 3 4\n", SQUARE)).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(vertices(&triangles[0]), [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+        assert_eq!(vertices(&triangles[1]), [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn face_vertices_with_texture_coordinates_and_normals() {
+        let plain = parse(&format!("{}f 1 2 3\n", SQUARE)).unwrap();
+        for face in ["f 1/1 2/1 3/1", "f 1//1 2//1 3//1", "f 1/1/1 2/1/1 3/1/1"] {
+            let triangles = parse(&format!("{}{}\n", SQUARE, face)).unwrap();
+            assert_eq!(triangles, plain, "{}", face);
+        }
+    }
+
+    #[test]
+    fn negative_indices_count_back_from_the_last_vertex() {
+        let triangles = parse(&format!("{}f -4 -3 -2\n", SQUARE)).unwrap();
+        assert_eq!(triangles, parse(&format!("{}f 1 2 3\n", SQUARE)).unwrap());
+
+        // only the vertices before the face count
+        let triangles = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\nv 5 5 5\n").unwrap();
+        assert_eq!(vertices(&triangles[0]), [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn degenerate_triangles_are_skipped() {
+        // the first triangle of the fan has all its vertices on a line
+        let triangles = parse("v 0 0 0\nv 1 0 0\nv 2 0 0\nv 0 1 0\nf 1 2 3 4\nf 1 1 2\n").unwrap();
+        assert_eq!(triangles.len(), 1);
+        assert_eq!(vertices(&triangles[0]), [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn invalid_faces_and_vertices() {
+        // line 7 is the face after the square
+        assert_eq!(parse_error_line(&format!("{}f 1 2 5\n", SQUARE)), 7);
+        assert_eq!(parse_error_line(&format!("{}f 0 1 2\n", SQUARE)), 7);
+        assert_eq!(parse_error_line(&format!("{}f -5 1 2\n", SQUARE)), 7);
+        assert_eq!(parse_error_line(&format!("{}f 1 2\n", SQUARE)), 7);
+        assert_eq!(parse_error_line(&format!("{}f 1 a 2\n", SQUARE)), 7);
+        assert_eq!(parse_error_line("v 0 0\n"), 1);
+        assert_eq!(parse_error_line("v 0 x 0\n"), 1);
+    }
+
+    #[test]
+    fn file_without_faces_is_not_a_mesh() {
+        let triangles = parse(SQUARE).unwrap();
+        assert!(triangles.is_empty());
+        assert!(matches!(add_mesh(&mut ObjectHandeler::new(), triangles, [1.0; 3]), Err(ObjError::NoTriangles)));
+    }
+}
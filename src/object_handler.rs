@@ -1,278 +1,982 @@
-use glium::{glutin::surface::WindowSurface, implement_uniform_block};
-
-use crate::shapes::{Sphere, Triangle, Cube, MengerSponge};
-
-#[repr(C)]
-#[derive(Clone, Copy)]
-pub struct SphereArray { // It seems to be hard to directly input a array of struct as in the older demo, so I temporarily chose to do it this way instead
-    number_of_objects: f32,
-    _padding: [f32; 3],
-    positions: [[f32; 4]; 128],   // all of this should be improved, but it seeems as if that the alignment is tricky, so I was pragmatic
-    colors: [[f32; 4]; 128],
-    radius: [[f32; 4]; 128], // should be a float array
+use std::ops::Range;
+use serde::{Deserialize, Serialize};
+
+use crate::bvh::{Aabb, Bvh};
+use crate::camera::CameraBookmark;
+use crate::light::{AmbientOcclusion, Light};
+use crate::material::{default_library, LibraryMaterial};
+use crate::texture::{Texture, TextureError, TextureImage, MAX_TEXTURE_IMAGES};
+use crate::sky::{Sky, SkyImages, SkyIrradiance, SkyKind};
+use crate::ground::Ground;
+use crate::shapes::{Sphere, Triangle, Cube, MengerSponge, Mesh, Torus, Capsule, Cylinder, Cone, Transform, Combine};
+use crate::vec_util::{vec_add, vec_scale};
+
+// identifies an object in the scene by its type and index in the list of that type
+// the numbers are the ones used for the type in the shader
+// the order of the variants is the order new scenes list the kinds of objects in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ObjectKind {
+    Sphere = 0,
+    Cube = 1,
+    Triangle = 2,
+    MengerSponge = 3,
+    Mesh = 4,
+    Torus = 5,
+    Capsule = 6,
+    Cylinder = 7,
+    Cone = 8,
 }
 
-#[repr(C)]
-#[derive(Clone, Copy)]
-pub struct TriangleArray { 
-    v1: [[f32; 4]; 128],   
-    v2: [[f32; 4]; 128], 
-    v3: [[f32; 4]; 128], 
-    norm: [[f32; 4]; 128],
-    color_triangles: [[f32; 4]; 128],
+impl ObjectKind {
+    pub const ALL : [ObjectKind; 9] = [
+        ObjectKind::Sphere, ObjectKind::Cube, ObjectKind::Triangle, ObjectKind::MengerSponge, ObjectKind::Mesh,
+        ObjectKind::Torus, ObjectKind::Capsule, ObjectKind::Cylinder, ObjectKind::Cone,
+    ];
 }
 
-#[repr(C)]
-#[derive(Clone, Copy)]
-pub struct CubesArray { 
-    pos_cubes: [[f32; 4]; 128],   
-    dim_cubes: [[f32; 4]; 128], 
-    color_cubes: [[f32; 4]; 128],
+// what uses a material of the library
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaterialUsers {
+    pub objects : Vec<ObjectId>,
+    pub ground : bool,
 }
 
-#[repr(C)]
-#[derive(Clone, Copy)]
-pub struct MengerSpongeArray { 
-    pos_menger_sponges: [[f32; 4]; 128],   
-    iterations_menger_sponges: [[f32; 4]; 128], 
-    color_menger_sponges: [[f32; 4]; 128],
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ObjectId {
+    pub kind : ObjectKind,
+    pub index : usize,
 }
 
-pub struct ObjectHandeler{
+impl ObjectId {
+    pub fn new(kind : ObjectKind, index : usize) -> ObjectId {
+        ObjectId { kind, index }
+    }
+}
+
+// a copy of any kind of object, used when objects are handled without caring about their type
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneObject {
+    Sphere(Sphere),
+    Cube(Cube),
+    Triangle(Triangle),
+    MengerSponge(MengerSponge),
+    Mesh(Mesh),
+    Torus(Torus),
+    Capsule(Capsule),
+    Cylinder(Cylinder),
+    Cone(Cone),
+}
+
+impl SceneObject {
+    pub fn kind(&self) -> ObjectKind {
+        match self {
+            SceneObject::Sphere(_) => ObjectKind::Sphere,
+            SceneObject::Cube(_) => ObjectKind::Cube,
+            SceneObject::Triangle(_) => ObjectKind::Triangle,
+            SceneObject::MengerSponge(_) => ObjectKind::MengerSponge,
+            SceneObject::Mesh(_) => ObjectKind::Mesh,
+            SceneObject::Torus(_) => ObjectKind::Torus,
+            SceneObject::Capsule(_) => ObjectKind::Capsule,
+            SceneObject::Cylinder(_) => ObjectKind::Cylinder,
+            SceneObject::Cone(_) => ObjectKind::Cone,
+        }
+    }
+
+    pub fn combine(&self) -> Combine {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.combine,
+            SceneObject::Cube(cube) => cube.combine,
+            SceneObject::Triangle(triangle) => triangle.combine,
+            SceneObject::MengerSponge(menger_sponge) => menger_sponge.combine,
+            SceneObject::Mesh(mesh) => mesh.combine,
+            SceneObject::Torus(torus) => torus.combine,
+            SceneObject::Capsule(capsule) => capsule.combine,
+            SceneObject::Cylinder(cylinder) => cylinder.combine,
+            SceneObject::Cone(cone) => cone.combine,
+        }
+    }
+
+    pub fn combine_mut(&mut self) -> &mut Combine {
+        match self {
+            SceneObject::Sphere(sphere) => &mut sphere.combine,
+            SceneObject::Cube(cube) => &mut cube.combine,
+            SceneObject::Triangle(triangle) => &mut triangle.combine,
+            SceneObject::MengerSponge(menger_sponge) => &mut menger_sponge.combine,
+            SceneObject::Mesh(mesh) => &mut mesh.combine,
+            SceneObject::Torus(torus) => &mut torus.combine,
+            SceneObject::Capsule(capsule) => &mut capsule.combine,
+            SceneObject::Cylinder(cylinder) => &mut cylinder.combine,
+            SceneObject::Cone(cone) => &mut cone.combine,
+        }
+    }
+
+    pub fn material(&self) -> usize {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.material,
+            SceneObject::Cube(cube) => cube.material,
+            SceneObject::Triangle(triangle) => triangle.material,
+            SceneObject::MengerSponge(menger_sponge) => menger_sponge.material,
+            SceneObject::Mesh(mesh) => mesh.material,
+            SceneObject::Torus(torus) => torus.material,
+            SceneObject::Capsule(capsule) => capsule.material,
+            SceneObject::Cylinder(cylinder) => cylinder.material,
+            SceneObject::Cone(cone) => cone.material,
+        }
+    }
+
+    pub fn material_mut(&mut self) -> &mut usize {
+        match self {
+            SceneObject::Sphere(sphere) => &mut sphere.material,
+            SceneObject::Cube(cube) => &mut cube.material,
+            SceneObject::Triangle(triangle) => &mut triangle.material,
+            SceneObject::MengerSponge(menger_sponge) => &mut menger_sponge.material,
+            SceneObject::Mesh(mesh) => &mut mesh.material,
+            SceneObject::Torus(torus) => &mut torus.material,
+            SceneObject::Capsule(capsule) => &mut capsule.material,
+            SceneObject::Cylinder(cylinder) => &mut cylinder.material,
+            SceneObject::Cone(cone) => &mut cone.material,
+        }
+    }
+
+    pub fn texture(&self) -> Texture {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.texture,
+            SceneObject::Cube(cube) => cube.texture,
+            SceneObject::Triangle(triangle) => triangle.texture,
+            SceneObject::MengerSponge(menger_sponge) => menger_sponge.texture,
+            SceneObject::Mesh(mesh) => mesh.texture,
+            SceneObject::Torus(torus) => torus.texture,
+            SceneObject::Capsule(capsule) => capsule.texture,
+            SceneObject::Cylinder(cylinder) => cylinder.texture,
+            SceneObject::Cone(cone) => cone.texture,
+        }
+    }
+
+    pub fn texture_mut(&mut self) -> &mut Texture {
+        match self {
+            SceneObject::Sphere(sphere) => &mut sphere.texture,
+            SceneObject::Cube(cube) => &mut cube.texture,
+            SceneObject::Triangle(triangle) => &mut triangle.texture,
+            SceneObject::MengerSponge(menger_sponge) => &mut menger_sponge.texture,
+            SceneObject::Mesh(mesh) => &mut mesh.texture,
+            SceneObject::Torus(torus) => &mut torus.texture,
+            SceneObject::Capsule(capsule) => &mut capsule.texture,
+            SceneObject::Cylinder(cylinder) => &mut cylinder.texture,
+            SceneObject::Cone(cone) => &mut cone.texture,
+        }
+    }
 
-    // ssbo for gpu storage
-    //ssbo_triangles : Ssbo,
-    //ssbo_spheres : Ssbo,
+    // the center and transform that the texture follows, triangles are textured in world space
+    pub fn texture_space(&self) -> ([f32; 3], Transform) {
+        match self {
+            SceneObject::Sphere(sphere) => (sphere.pos, sphere.transform),
+            SceneObject::Cube(cube) => (cube.pos, cube.transform),
+            SceneObject::Triangle(_) => ([0.0; 3], Transform::IDENTITY),
+            SceneObject::MengerSponge(menger_sponge) => (menger_sponge.pos, menger_sponge.transform),
+            SceneObject::Mesh(mesh) => (mesh.pos, mesh.transform),
+            SceneObject::Torus(torus) => (torus.pos, torus.transform),
+            SceneObject::Capsule(capsule) => (capsule.pos, capsule.transform),
+            SceneObject::Cylinder(cylinder) => (cylinder.pos, cylinder.transform),
+            SceneObject::Cone(cone) => (cone.pos, cone.transform),
+        }
+    }
+
+    // triangles have no transform
+    pub fn transform_mut(&mut self) -> Option<&mut Transform> {
+        match self {
+            SceneObject::Sphere(sphere) => Some(&mut sphere.transform),
+            SceneObject::Cube(cube) => Some(&mut cube.transform),
+            SceneObject::Triangle(_) => None,
+            SceneObject::MengerSponge(menger_sponge) => Some(&mut menger_sponge.transform),
+            SceneObject::Mesh(mesh) => Some(&mut mesh.transform),
+            SceneObject::Torus(torus) => Some(&mut torus.transform),
+            SceneObject::Capsule(capsule) => Some(&mut capsule.transform),
+            SceneObject::Cylinder(cylinder) => Some(&mut cylinder.transform),
+            SceneObject::Cone(cone) => Some(&mut cone.transform),
+        }
+    }
+}
+
+pub struct ObjectHandeler{
 
     // for cpu storage
     cpu_triangles : Vec<Triangle>,
     cpu_spheres : Vec<Sphere>,
     cpu_cubes : Vec<Cube>,
     cpu_menger_sponges : Vec<MengerSponge>,
+    cpu_meshes : Vec<Mesh>,
+    cpu_tori : Vec<Torus>,
+    cpu_capsules : Vec<Capsule>,
+    cpu_cylinders : Vec<Cylinder>,
+    cpu_cones : Vec<Cone>,
+
+    // the triangles of every mesh, relative to the position of the mesh. triangles are only added here,
+    // so that a removed mesh can be brought back by undo
+    mesh_triangles : Vec<Triangle>,
+
+    // the objects of each kind that changed since the gpu buffers were updated, indexed by ObjectKind
+    modified : [Option<Range<usize>>; 9],
+    mesh_triangles_modified : Option<Range<usize>>,
+
+    // the list order of the objects, every object is in it once, see object_ids
+    order : Vec<ObjectId>,
+    order_modified : bool,
+
+    // rebuilt by update_bvh after objects have changed, bvh_modified tells the gpu storage to upload it again
+    bvh : Bvh,
+    bvh_outdated : bool,
+    bvh_modified : bool,
+    use_bvh : bool,
+
+    // shadows cost a march towards every light for every pixel, so they can be turned off
+    use_shadows : bool,
+    shadow_softness : f32,
+    ambient_occlusion : AmbientOcclusion,
+    // how many times a ray can be reflected or refracted, every bounce can double the rays of a pixel
+    max_bounces : u32,
+
+    // lights_modified tells the gpu storage to upload them again
+    lights : Vec<Light>,
+    lights_modified : bool,
+
+    // the objects refer to these by index, the first one is never removed
+    materials : Vec<LibraryMaterial>,
+    materials_modified : bool,
+
+    // the images that objects can use as their texture, they are only added
+    textures : Vec<TextureImage>,
+    textures_modified : bool,
+
+    // the irradiance is worked out again whenever the sky or its images change, sky_images_modified tells the gpu storage to upload the images
+    sky : Sky,
+    sky_images : SkyImages,
+    sky_irradiance : SkyIrradiance,
+    sky_images_modified : bool,
+
+    // not one of the objects, it is only a few uniforms in the shader
+    ground : Ground,
 
     // other stuff
-    data_is_modified : bool,
-    render_mode : u8,
+    selected : Option<ObjectId>,
+    camera_bookmarks : Vec<CameraBookmark>,
 }
 
 impl ObjectHandeler{
 
     pub fn new() -> ObjectHandeler{
-        let mut handeler = ObjectHandeler{
-            //ssbo_triangles : Ssbo::new(11),
-            //ssbo_spheres : Ssbo::new(10),
+        ObjectHandeler{
             cpu_triangles : Vec::new(),
             cpu_spheres : Vec::new(),
             cpu_cubes : Vec::new(),
             cpu_menger_sponges : Vec::new(),
-            data_is_modified : false,
-            render_mode : 0
-        };
-        handeler.initiate(); // initiate sphere and triangle struct for glinum
-        return handeler;
+            cpu_meshes : Vec::new(),
+            cpu_tori : Vec::new(),
+            cpu_capsules : Vec::new(),
+            cpu_cylinders : Vec::new(),
+            cpu_cones : Vec::new(),
+            mesh_triangles : Vec::new(),
+            modified : Default::default(),
+            mesh_triangles_modified : None,
+            order : Vec::new(),
+            order_modified : false,
+            bvh : Bvh::new(),
+            bvh_outdated : false,
+            bvh_modified : false,
+            use_bvh : true,
+            use_shadows : true,
+            shadow_softness : 0.1,
+            ambient_occlusion : AmbientOcclusion::DEFAULT,
+            max_bounces : 3,
+            lights : vec![Light::point([300.0, 100.0, 50.0])],
+            lights_modified : false,
+            materials : default_library(),
+            materials_modified : false,
+            textures : Vec::new(),
+            textures_modified : false,
+            sky : Sky::DEFAULT,
+            sky_images : SkyImages::default(),
+            sky_irradiance : Sky::DEFAULT.irradiance(&SkyImages::default()),
+            sky_images_modified : false,
+            ground : Ground::DEFAULT,
+            selected : None,
+            camera_bookmarks : Vec::new()
+        }
+    }
+
+    pub fn get_lights(&self) -> &Vec<Light> {
+        &self.lights
+    }
+
+    pub fn set_lights(&mut self, lights : Vec<Light>) {
+        self.lights = lights;
+        self.lights_modified = true;
+    }
+
+    pub fn insert_light(&mut self, index : usize, light : Light) {
+        self.lights.insert(index, light);
+        self.lights_modified = true;
+    }
+
+    pub fn remove_light(&mut self, index : usize) -> Light {
+        self.lights_modified = true;
+        self.lights.remove(index)
+    }
+
+    pub fn set_light(&mut self, index : usize, light : Light) {
+        self.lights[index] = light;
+        self.lights_modified = true;
+    }
+
+    pub fn is_lights_modified(&self) -> bool {
+        self.lights_modified
+    }
+
+    pub fn clear_lights_modified(&mut self) {
+        self.lights_modified = false;
+    }
+
+    pub fn get_materials(&self) -> &Vec<LibraryMaterial> {
+        &self.materials
     }
 
-    fn initiate(&mut self){
-        
-        // this is something that glium needs, so that we can pass these resources as uniforms
-        implement_uniform_block!(SphereArray, number_of_objects, positions, colors, radius);
-        implement_uniform_block!(TriangleArray, v1, v2, v3, norm, color_triangles);
-        implement_uniform_block!(CubesArray, pos_cubes, dim_cubes, color_cubes);
-        implement_uniform_block!(MengerSpongeArray, pos_menger_sponges, iterations_menger_sponges, color_menger_sponges);
+    pub fn set_materials(&mut self, materials : Vec<LibraryMaterial>) {
+        self.materials = materials;
+        self.materials_modified = true;
     }
 
-    pub fn get_num_of_triangles(&self) -> usize{self.cpu_triangles.len()}
-    pub fn get_num_of_spheres(&self) -> usize{self.cpu_spheres.len()}
-    pub fn get_num_of_cubes(&self) -> usize{self.cpu_cubes.len()}
-    pub fn get_num_of_menger_sponges(&self) -> usize{self.cpu_menger_sponges.len()}
+    // adds a material to the library, the objects keep the materials they had
+    pub fn insert_material(&mut self, index : usize, material : LibraryMaterial) {
+        self.materials.insert(index, material);
+        self.materials_modified = true;
+        self.remap_materials(|i| if i >= index { i + 1 } else { i });
+    }
+
+    // removes a material from the library, the objects that used it get the first material.
+    // Returns the material and what used it, so that undo can give it back to them
+    pub fn remove_material(&mut self, index : usize) -> (LibraryMaterial, MaterialUsers) {
+        let users = self.material_users(index);
+        self.materials_modified = true;
+        self.remap_materials(|i| if i > index { i - 1 } else if i == index { 0 } else { i });
+        (self.materials.remove(index), users)
+    }
+
+    // gives the material at index to the objects and the ground in users
+    pub fn set_material_users(&mut self, index : usize, users : &MaterialUsers) {
+        for id in &users.objects {
+            let mut object = self.get_object(*id);
+            *object.material_mut() = index;
+            self.set_object(*id, object);
+        }
+        if users.ground {
+            self.ground.material = index;
+        }
+    }
+
+    pub fn set_material(&mut self, index : usize, material : LibraryMaterial) {
+        self.materials[index] = material;
+        self.materials_modified = true;
+    }
+
+    // the objects that use the material, and whether the ground does
+    pub fn material_users(&self, index : usize) -> MaterialUsers {
+        MaterialUsers {
+            objects : self.object_ids().filter(|id| self.get_object(*id).material() == index).collect(),
+            ground : self.ground.material == index,
+        }
+    }
+
+    // changes the material index of every object and the ground, after materials have been added or removed
+    fn remap_materials(&mut self, remap : impl Fn(usize) -> usize) {
+        self.ground.material = remap(self.ground.material);
+
+        let ids : Vec<ObjectId> = self.object_ids().collect();
+        for id in ids {
+            let mut object = self.get_object(id);
+            let material = remap(object.material());
+            if material != object.material() {
+                *object.material_mut() = material;
+                self.set_object(id, object);
+            }
+        }
+    }
+
+    pub fn is_materials_modified(&self) -> bool {
+        self.materials_modified
+    }
+
+    pub fn clear_materials_modified(&mut self) {
+        self.materials_modified = false;
+    }
+
+    pub fn get_textures(&self) -> &Vec<TextureImage> {
+        &self.textures
+    }
+
+    pub fn set_textures(&mut self, textures : Vec<TextureImage>) {
+        self.textures = textures;
+        self.textures_modified = true;
+    }
+
+    // adds an image that objects can use as their texture and returns its index, every image is a layer of one texture array on the gpu
+    pub fn add_texture(&mut self, texture : TextureImage) -> Result<usize, TextureError> {
+        if self.textures.len() >= MAX_TEXTURE_IMAGES {
+            return Err(TextureError::TooManyImages);
+        }
+
+        self.textures.push(texture);
+        self.textures_modified = true;
+        Ok(self.textures.len() - 1)
+    }
+
+    pub fn is_textures_modified(&self) -> bool {
+        self.textures_modified
+    }
+
+    pub fn clear_textures_modified(&mut self) {
+        self.textures_modified = false;
+    }
+
+    pub fn get_sky(&self) -> Sky {
+        self.sky
+    }
 
-    pub fn set_render_mode(&mut self, mode : u8) {
-        if mode == 0 || mode == 1 || mode == 2 || mode == 3{
-            self.render_mode = mode;
+    pub fn set_sky(&mut self, sky : Sky) {
+        self.sky = sky;
+        self.sky_irradiance = sky.irradiance(&self.sky_images);
+    }
+
+    pub fn get_sky_images(&self) -> &SkyImages {
+        &self.sky_images
+    }
+
+    // the kind of the sky goes back to the gradient if its images are gone
+    pub fn set_sky_images(&mut self, sky_images : SkyImages) {
+        self.sky_images = sky_images;
+        self.sky_images_modified = true;
+
+        let mut sky = self.sky;
+        if !self.sky_images.has(sky.kind) {
+            sky.kind = SkyKind::Gradient;
         }
+        self.set_sky(sky);
+    }
+
+    pub fn get_sky_irradiance(&self) -> &SkyIrradiance {
+        &self.sky_irradiance
+    }
+
+    pub fn is_sky_images_modified(&self) -> bool {
+        self.sky_images_modified
+    }
+
+    pub fn clear_sky_images_modified(&mut self) {
+        self.sky_images_modified = false;
+    }
+
+    pub fn get_ground(&self) -> Ground {
+        self.ground
     }
 
-    pub fn get_render_mode(&self) -> u8 {
-        self.render_mode
+    pub fn set_ground(&mut self, ground : Ground) {
+        self.ground = ground;
     }
 
-    pub fn get_spheres_reference(&mut self) -> &mut Vec<Sphere>{
-        &mut self.cpu_spheres
+    pub fn get_selected(&self) -> Option<ObjectId> {
+        self.selected
     }
 
-    pub fn get_cubes_reference(&mut self) -> &mut Vec<Cube>{
-        &mut self.cpu_cubes
+    pub fn set_selected(&mut self, selected : Option<ObjectId>) {
+        self.selected = selected.filter(|id| id.index < self.get_num_of(id.kind));
+    }
+
+    // (type, index) of the selected object for the shader, -1 when nothing is selected
+    pub fn get_selected_uniform(&self) -> (i32, i32) {
+        match self.selected {
+            Some(id) => (id.kind as i32, id.index as i32),
+            None => (-1, -1)
+        }
+    }
+
+    pub fn get_num_of(&self, kind : ObjectKind) -> usize {
+        match kind {
+            ObjectKind::Sphere => self.cpu_spheres.len(),
+            ObjectKind::Cube => self.cpu_cubes.len(),
+            ObjectKind::Triangle => self.cpu_triangles.len(),
+            ObjectKind::MengerSponge => self.cpu_menger_sponges.len(),
+            ObjectKind::Mesh => self.cpu_meshes.len(),
+            ObjectKind::Torus => self.cpu_tori.len(),
+            ObjectKind::Capsule => self.cpu_capsules.len(),
+            ObjectKind::Cylinder => self.cpu_cylinders.len(),
+            ObjectKind::Cone => self.cpu_cones.len(),
+        }
     }
 
-    pub fn get_menger_sponges_reference(&mut self) -> &mut Vec<MengerSponge>{
-        &mut self.cpu_menger_sponges
+    // every object in list order, the order the side panel shows them in and the order their operations are applied in
+    pub fn object_ids(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        self.order.iter().copied()
     }
 
-    pub fn add_sphere(&mut self, render_object : Sphere){
-        self.cpu_spheres.push(render_object);
-        self.data_is_modified = true;
+    pub fn get_order(&self) -> &Vec<ObjectId> {
+        &self.order
     }
 
-    pub fn add_cube(&mut self, render_object : Cube){
-        self.cpu_cubes.push(render_object);
-        self.data_is_modified = true;
+    // the order has to have every object exactly once, scene files are checked for that when they are read
+    pub fn set_order(&mut self, order : Vec<ObjectId>) {
+        self.order = order;
+        self.order_modified = true;
+        self.bvh_outdated = true;
     }
 
-    pub fn add_menger_sponge(&mut self, render_object : MengerSponge){
-        self.cpu_menger_sponges.push(render_object);
-        self.data_is_modified = true;
+    // where the object is in the list order
+    pub fn order_position(&self, id : ObjectId) -> usize {
+        self.order.iter().position(|other| *other == id).unwrap()
+    }
+
+    // moves the object to the position in the list order, the objects in between move one step towards where it was
+    pub fn move_object(&mut self, id : ObjectId, position : usize) {
+        let old_position = self.order_position(id);
+        self.order.remove(old_position);
+        self.order.insert(position, id);
+        self.order_modified = true;
+        self.bvh_outdated = true;
+    }
+
+    pub fn is_order_modified(&self) -> bool {
+        self.order_modified
+    }
+
+    pub fn clear_order_modified(&mut self) {
+        self.order_modified = false;
+    }
+
+    // the position of an object, for triangles this is the center of the vertices
+    pub fn get_object_pos(&self, id : ObjectId) -> [f32;3] {
+        match id.kind {
+            ObjectKind::Sphere => self.cpu_spheres[id.index].pos,
+            ObjectKind::Cube => self.cpu_cubes[id.index].pos,
+            ObjectKind::Triangle => {
+                let t = &self.cpu_triangles[id.index];
+                vec_scale(vec_add(vec_add(t.v1, t.v2, 1.0), t.v3, 1.0), 1.0 / 3.0)
+            },
+            ObjectKind::MengerSponge => self.cpu_menger_sponges[id.index].pos,
+            ObjectKind::Mesh => self.cpu_meshes[id.index].pos,
+            ObjectKind::Torus => self.cpu_tori[id.index].pos,
+            ObjectKind::Capsule => self.cpu_capsules[id.index].pos,
+            ObjectKind::Cylinder => self.cpu_cylinders[id.index].pos,
+            ObjectKind::Cone => self.cpu_cones[id.index].pos,
+        }
+    }
+
+    pub fn set_object_pos(&mut self, id : ObjectId, pos : [f32;3]) {
+        match id.kind {
+            ObjectKind::Sphere => self.cpu_spheres[id.index].pos = pos,
+            ObjectKind::Cube => self.cpu_cubes[id.index].pos = pos,
+            ObjectKind::Triangle => {
+                let offset = vec_add(pos, self.get_object_pos(id), -1.0);
+                let t = &mut self.cpu_triangles[id.index];
+                t.v1 = vec_add(t.v1, offset, 1.0);
+                t.v2 = vec_add(t.v2, offset, 1.0);
+                t.v3 = vec_add(t.v3, offset, 1.0);
+            },
+            ObjectKind::MengerSponge => self.cpu_menger_sponges[id.index].pos = pos,
+            ObjectKind::Mesh => self.cpu_meshes[id.index].pos = pos,
+            ObjectKind::Torus => self.cpu_tori[id.index].pos = pos,
+            ObjectKind::Capsule => self.cpu_capsules[id.index].pos = pos,
+            ObjectKind::Cylinder => self.cpu_cylinders[id.index].pos = pos,
+            ObjectKind::Cone => self.cpu_cones[id.index].pos = pos,
+        }
+        self.mark_modified(id.kind, id.index..id.index + 1);
+    }
+
+    pub fn get_spheres(&self) -> &Vec<Sphere>{
+        &self.cpu_spheres
+    }
+
+    pub fn get_triangles(&self) -> &Vec<Triangle>{
+        &self.cpu_triangles
+    }
+
+    pub fn get_cubes(&self) -> &Vec<Cube>{
+        &self.cpu_cubes
+    }
+
+    pub fn get_menger_sponges(&self) -> &Vec<MengerSponge>{
+        &self.cpu_menger_sponges
+    }
+
+    pub fn get_meshes(&self) -> &Vec<Mesh>{
+        &self.cpu_meshes
+    }
+
+    pub fn get_tori(&self) -> &Vec<Torus>{
+        &self.cpu_tori
+    }
+
+    pub fn get_capsules(&self) -> &Vec<Capsule>{
+        &self.cpu_capsules
+    }
+
+    pub fn get_cylinders(&self) -> &Vec<Cylinder>{
+        &self.cpu_cylinders
+    }
+
+    pub fn get_cones(&self) -> &Vec<Cone>{
+        &self.cpu_cones
+    }
+
+    pub fn get_mesh_triangles(&self) -> &Vec<Triangle>{
+        &self.mesh_triangles
+    }
+
+    // removes every object from the scene, used when a scene file is opened
+    pub fn clear(&mut self){
+        self.cpu_triangles.clear();
+        self.cpu_spheres.clear();
+        self.cpu_cubes.clear();
+        self.cpu_menger_sponges.clear();
+        self.cpu_meshes.clear();
+        self.cpu_tori.clear();
+        self.cpu_capsules.clear();
+        self.cpu_cylinders.clear();
+        self.cpu_cones.clear();
+        self.mesh_triangles.clear();
+        self.order.clear();
+        self.order_modified = true;
+        self.selected = None;
+        self.bvh_outdated = true;
+        // nothing has to be written, the buffers just get shorter
+    }
+
+    pub fn get_object(&self, id : ObjectId) -> SceneObject {
+        match id.kind {
+            ObjectKind::Sphere => SceneObject::Sphere(self.cpu_spheres[id.index]),
+            ObjectKind::Cube => SceneObject::Cube(self.cpu_cubes[id.index]),
+            ObjectKind::Triangle => SceneObject::Triangle(self.cpu_triangles[id.index]),
+            ObjectKind::MengerSponge => SceneObject::MengerSponge(self.cpu_menger_sponges[id.index]),
+            ObjectKind::Mesh => SceneObject::Mesh(self.cpu_meshes[id.index]),
+            ObjectKind::Torus => SceneObject::Torus(self.cpu_tori[id.index]),
+            ObjectKind::Capsule => SceneObject::Capsule(self.cpu_capsules[id.index]),
+            ObjectKind::Cylinder => SceneObject::Cylinder(self.cpu_cylinders[id.index]),
+            ObjectKind::Cone => SceneObject::Cone(self.cpu_cones[id.index]),
+        }
+    }
+
+    // replaces the object at id, the object has to be of the same kind as id
+    pub fn set_object(&mut self, id : ObjectId, object : SceneObject){
+        match object {
+            SceneObject::Sphere(sphere) => self.cpu_spheres[id.index] = sphere,
+            SceneObject::Cube(cube) => self.cpu_cubes[id.index] = cube,
+            SceneObject::Triangle(triangle) => self.cpu_triangles[id.index] = triangle,
+            SceneObject::MengerSponge(menger_sponge) => self.cpu_menger_sponges[id.index] = menger_sponge,
+            SceneObject::Mesh(mesh) => self.cpu_meshes[id.index] = mesh,
+            SceneObject::Torus(torus) => self.cpu_tori[id.index] = torus,
+            SceneObject::Capsule(capsule) => self.cpu_capsules[id.index] = capsule,
+            SceneObject::Cylinder(cylinder) => self.cpu_cylinders[id.index] = cylinder,
+            SceneObject::Cone(cone) => self.cpu_cones[id.index] = cone,
+        }
+        self.mark_modified(id.kind, id.index..id.index + 1);
+    }
+
+    // adds the object last in the list of its kind and last in the list order
+    pub fn add_object(&mut self, object : SceneObject) -> ObjectId {
+        let id = ObjectId::new(object.kind(), self.get_num_of(object.kind()));
+        self.insert_object(id, object, self.order.len());
+        id
+    }
+
+    // inserts the object so that it gets the given id and is at the position in the list order, the objects of its kind after it move up one step
+    pub fn insert_object(&mut self, id : ObjectId, object : SceneObject, position : usize){
+        match object {
+            SceneObject::Sphere(sphere) => self.cpu_spheres.insert(id.index, sphere),
+            SceneObject::Cube(cube) => self.cpu_cubes.insert(id.index, cube),
+            SceneObject::Triangle(triangle) => self.cpu_triangles.insert(id.index, triangle),
+            SceneObject::MengerSponge(menger_sponge) => self.cpu_menger_sponges.insert(id.index, menger_sponge),
+            SceneObject::Mesh(mesh) => self.cpu_meshes.insert(id.index, self.with_bounds(mesh)),
+            SceneObject::Torus(torus) => self.cpu_tori.insert(id.index, torus),
+            SceneObject::Capsule(capsule) => self.cpu_capsules.insert(id.index, capsule),
+            SceneObject::Cylinder(cylinder) => self.cpu_cylinders.insert(id.index, cylinder),
+            SceneObject::Cone(cone) => self.cpu_cones.insert(id.index, cone),
+        }
+
+        if let Some(selected) = self.selected {
+            if selected.kind == id.kind && selected.index >= id.index {
+                self.selected = Some(ObjectId::new(selected.kind, selected.index + 1));
+            }
+        }
+        for other in self.order.iter_mut() {
+            if other.kind == id.kind && other.index >= id.index {
+                other.index += 1;
+            }
+        }
+        self.order.insert(position, id);
+        self.order_modified = true;
+        // every object after the inserted one has moved
+        self.mark_modified(id.kind, id.index..self.get_num_of(id.kind));
+    }
+
+    pub fn remove_object(&mut self, id : ObjectId) -> SceneObject{
+        let object = self.get_object(id);
+
+        match id.kind {
+            ObjectKind::Sphere => {self.cpu_spheres.remove(id.index);},
+            ObjectKind::Cube => {self.cpu_cubes.remove(id.index);},
+            ObjectKind::Triangle => {self.cpu_triangles.remove(id.index);},
+            ObjectKind::MengerSponge => {self.cpu_menger_sponges.remove(id.index);},
+            ObjectKind::Mesh => {self.cpu_meshes.remove(id.index);},
+            ObjectKind::Torus => {self.cpu_tori.remove(id.index);},
+            ObjectKind::Capsule => {self.cpu_capsules.remove(id.index);},
+            ObjectKind::Cylinder => {self.cpu_cylinders.remove(id.index);},
+            ObjectKind::Cone => {self.cpu_cones.remove(id.index);},
+        }
+
+        // the objects after the removed one move down one step, so the selection has to follow
+        if let Some(selected) = self.selected {
+            if selected == id {
+                self.selected = None;
+            } else if selected.kind == id.kind && selected.index > id.index {
+                self.selected = Some(ObjectId::new(selected.kind, selected.index - 1));
+            }
+        }
+        self.order.retain(|other| *other != id);
+        for other in self.order.iter_mut() {
+            if other.kind == id.kind && other.index > id.index {
+                other.index -= 1;
+            }
+        }
+        self.order_modified = true;
+        self.mark_modified(id.kind, id.index..self.get_num_of(id.kind));
+
+        object
+    }
+
+    pub fn get_camera_bookmarks(&self) -> &Vec<CameraBookmark> {
+        &self.camera_bookmarks
+    }
+
+    pub fn get_camera_bookmarks_reference(&mut self) -> &mut Vec<CameraBookmark> {
+        &mut self.camera_bookmarks
     }
 
     pub fn add_triangles_from(&mut self, mut render_objects : Vec<Triangle>){
+        let start = self.cpu_triangles.len();
         self.cpu_triangles.append(&mut render_objects);
-        self.data_is_modified = true;
+        self.mark_modified(ObjectKind::Triangle, start..self.cpu_triangles.len());
+        self.add_to_order(ObjectKind::Triangle, start..self.cpu_triangles.len());
     }
 
     pub fn add_spheres_from(&mut self, mut render_objects : Vec<Sphere>){
+        let start = self.cpu_spheres.len();
         self.cpu_spheres.append(&mut render_objects);
-        self.data_is_modified = true;
-        println!("{:?}", self.cpu_spheres);
+        self.mark_modified(ObjectKind::Sphere, start..self.cpu_spheres.len());
+        self.add_to_order(ObjectKind::Sphere, start..self.cpu_spheres.len());
     }
 
     pub fn add_cubes_from(&mut self, mut render_objects : Vec<Cube>){
+        let start = self.cpu_cubes.len();
         self.cpu_cubes.append(&mut render_objects);
-        self.data_is_modified = true;
+        self.mark_modified(ObjectKind::Cube, start..self.cpu_cubes.len());
+        self.add_to_order(ObjectKind::Cube, start..self.cpu_cubes.len());
     }
 
     pub fn add_menger_sponges_from(&mut self, mut render_objects : Vec<MengerSponge>){
+        let start = self.cpu_menger_sponges.len();
         self.cpu_menger_sponges.append(&mut render_objects);
-        self.data_is_modified = true;
+        self.mark_modified(ObjectKind::MengerSponge, start..self.cpu_menger_sponges.len());
+        self.add_to_order(ObjectKind::MengerSponge, start..self.cpu_menger_sponges.len());
     }
 
-    // temp solution until I get ssbo to work with glinum
-    pub fn get_uniform_buffer_spheres(&mut self, display : &glium::Display<WindowSurface>) -> glium::uniforms::UniformBuffer<SphereArray>{
-        
-        let mut sphere_array: glium::uniforms::UniformBuffer<SphereArray> = glium::uniforms::UniformBuffer::empty(display).unwrap();
-
-        {
-            let mut mapping = sphere_array.map();
-            let mut counter : usize = 0;
-            self.cpu_spheres.iter_mut().for_each(|sphere|{
-                let mut position = [0.0f32; 4];
-                position[..3].copy_from_slice(&sphere.pos);
-                mapping.positions[counter] = position;
-
-                let mut color = [0.0f32; 4];
-                color[..3].copy_from_slice(&sphere.color);
-                mapping.colors[counter] = color;
-
-                let mut radius = [0.0f32; 4];
-                radius[0] = sphere.radius;
-                mapping.radius[counter] = radius;
-                counter += 1;
-            });
-            
-        }
-        
-        return sphere_array;
+    pub fn add_meshes_from(&mut self, render_objects : Vec<Mesh>){
+        let mut render_objects = render_objects.into_iter().map(|mesh| self.with_bounds(mesh)).collect();
+        let start = self.cpu_meshes.len();
+        self.cpu_meshes.append(&mut render_objects);
+        self.mark_modified(ObjectKind::Mesh, start..self.cpu_meshes.len());
+        self.add_to_order(ObjectKind::Mesh, start..self.cpu_meshes.len());
+    }
+
+    pub fn add_tori_from(&mut self, mut render_objects : Vec<Torus>){
+        let start = self.cpu_tori.len();
+        self.cpu_tori.append(&mut render_objects);
+        self.mark_modified(ObjectKind::Torus, start..self.cpu_tori.len());
+        self.add_to_order(ObjectKind::Torus, start..self.cpu_tori.len());
     }
 
-    // temp solution until I get ssbo to work with glinum
-    pub fn get_uniform_buffer_triangles(&mut self, display : &glium::Display<WindowSurface>) -> glium::uniforms::UniformBuffer<TriangleArray>{
-        
-        let mut triangle_array: glium::uniforms::UniformBuffer<TriangleArray> = glium::uniforms::UniformBuffer::empty(display).unwrap();
+    pub fn add_capsules_from(&mut self, mut render_objects : Vec<Capsule>){
+        let start = self.cpu_capsules.len();
+        self.cpu_capsules.append(&mut render_objects);
+        self.mark_modified(ObjectKind::Capsule, start..self.cpu_capsules.len());
+        self.add_to_order(ObjectKind::Capsule, start..self.cpu_capsules.len());
+    }
 
-        {
-            let mut mapping = triangle_array.map();
-            let mut counter : usize = 0;
-            self.cpu_triangles.iter_mut().for_each(|triangle|{
-                let mut v1 = [0.0f32; 4];
-                v1[..3].copy_from_slice(&triangle.v1);
-                mapping.v1[counter] = v1;
+    pub fn add_cylinders_from(&mut self, mut render_objects : Vec<Cylinder>){
+        let start = self.cpu_cylinders.len();
+        self.cpu_cylinders.append(&mut render_objects);
+        self.mark_modified(ObjectKind::Cylinder, start..self.cpu_cylinders.len());
+        self.add_to_order(ObjectKind::Cylinder, start..self.cpu_cylinders.len());
+    }
 
-                let mut v2 = [0.0f32; 4];
-                v2[..3].copy_from_slice(&triangle.v2);
-                mapping.v2[counter] = v2;
+    pub fn add_cones_from(&mut self, mut render_objects : Vec<Cone>){
+        let start = self.cpu_cones.len();
+        self.cpu_cones.append(&mut render_objects);
+        self.mark_modified(ObjectKind::Cone, start..self.cpu_cones.len());
+        self.add_to_order(ObjectKind::Cone, start..self.cpu_cones.len());
+    }
 
-                let mut v3 = [0.0f32; 4];
-                v3[..3].copy_from_slice(&triangle.v3);
-                mapping.v3[counter] = v1;
+    // puts the objects last in the list order
+    fn add_to_order(&mut self, kind : ObjectKind, range : Range<usize>){
+        self.order.extend(range.map(|index| ObjectId::new(kind, index)));
+        self.order_modified = true;
+    }
 
-                // seems as if this field is redundant in older dev_0.1??
-                let norm = [0.0f32; 4];
-                //norm[..3].copy_from_slice(&triangle.);
-                mapping.norm[counter] = norm;
+    // stores the triangles of a new mesh and returns where they are, the mesh itself is added with add_object
+    pub fn add_mesh_triangles(&mut self, mut triangles : Vec<Triangle>) -> Range<usize> {
+        let start = self.mesh_triangles.len();
+        self.mesh_triangles.append(&mut triangles);
 
-                let mut color = [0.0f32; 4];
-                color[..3].copy_from_slice(&triangle.color);
-                mapping.color_triangles[counter] = color;
+        let range = start..self.mesh_triangles.len();
+        self.mesh_triangles_modified = match self.mesh_triangles_modified.take() {
+            Some(old) => Some(old.start.min(range.start)..old.end.max(range.end)),
+            None => Some(range.clone()),
+        };
+        range
+    }
 
-                counter += 1;
-            });
-            
+    // the mesh with the box around its triangles, which lets the distance skip the triangles far away from the mesh
+    fn with_bounds(&self, mut mesh : Mesh) -> Mesh {
+        let triangles = &self.mesh_triangles[mesh.triangles()];
+        if !triangles.is_empty() {
+            let bounds = Aabb::around_triangles(triangles);
+            mesh.bounds_min = bounds.min;
+            mesh.bounds_max = bounds.max;
         }
-        return triangle_array;
+        mesh
     }
 
-    // temp solution until I get ssbo to work with glinum
-    pub fn get_uniform_buffer_cubes(&mut self, display : &glium::Display<WindowSurface>) -> glium::uniforms::UniformBuffer<CubesArray>{
-        
-        let mut cube_array: glium::uniforms::UniformBuffer<CubesArray> = glium::uniforms::UniformBuffer::empty(display).unwrap();
+    // drops the mesh triangles that are not used by a mesh in the scene or by one of the kept meshes, and moves the rest together.
+    // The kept meshes are copies that are not in the scene, like the ones in the history, they are moved with their triangles
+    pub fn compact_mesh_triangles(&mut self, kept : &mut [&mut Mesh]) {
+        let mut ranges : Vec<Range<usize>> = self.cpu_meshes.iter().chain(kept.iter().map(|mesh| &**mesh)).map(|mesh| mesh.triangles()).collect();
+        ranges.sort_by_key(|range| range.start);
+
+        // copies of a mesh have the same range, ranges that overlap or touch are kept as one span
+        let mut spans : Vec<Range<usize>> = Vec::new();
+        for range in ranges {
+            match spans.last_mut() {
+                Some(span) if range.start <= span.end => span.end = span.end.max(range.end),
+                _ => spans.push(range),
+            }
+        }
 
-        {
-            let mut mapping = cube_array.map();
-            let mut counter : usize = 0;
-            self.cpu_cubes.iter_mut().for_each(|cube|{
-                let mut pos = [0.0f32; 4];
-                pos[..3].copy_from_slice(&cube.pos);
-                mapping.pos_cubes[counter] = pos;
+        // where every span starts after the triangles have been moved together
+        let mut triangles = Vec::new();
+        let mut span_starts = Vec::new();
+        for span in spans.iter() {
+            span_starts.push(triangles.len());
+            triangles.extend_from_slice(&self.mesh_triangles[span.clone()]);
+        }
 
-                let mut dim = [0.0f32; 4];
-                dim[..3].copy_from_slice(&cube.dim);
-                mapping.dim_cubes[counter] = dim;
+        // every mesh is in the last span that starts before its first triangle
+        let move_mesh = |mesh : &mut Mesh| {
+            let i = spans.partition_point(|span| span.start <= mesh.first_triangle) - 1;
+            mesh.first_triangle = span_starts[i] + mesh.first_triangle - spans[i].start;
+        };
+        self.cpu_meshes.iter_mut().for_each(move_mesh);
+        kept.iter_mut().for_each(|mesh| move_mesh(mesh));
+        self.mark_modified(ObjectKind::Mesh, 0..self.cpu_meshes.len());
 
-                let mut color = [0.0f32; 4];
-                color[..3].copy_from_slice(&cube.color);
-                mapping.color_cubes[counter] = color;
+        // every triangle may have moved, so all of them are written again
+        self.mesh_triangles = triangles;
+        self.mesh_triangles_modified = Some(0..self.mesh_triangles.len());
+    }
 
-                counter += 1;
-            });
-            
-        }
-        return cube_array;
+    // the mesh triangles that have been added since clear_mesh_triangles_modified was last called
+    pub fn get_mesh_triangles_modified(&self) -> Option<Range<usize>> {
+        self.mesh_triangles_modified.clone()
     }
 
-    pub fn get_uniform_buffer_menger_sponges(&mut self, display : &glium::Display<WindowSurface>) -> glium::uniforms::UniformBuffer<MengerSpongeArray>{
-        
-        let mut menger_sponge_array: glium::uniforms::UniformBuffer<MengerSpongeArray> = glium::uniforms::UniformBuffer::empty(display).unwrap();
+    pub fn clear_mesh_triangles_modified(&mut self){
+        self.mesh_triangles_modified = None;
+    }
 
-        {
-            let mut mapping = menger_sponge_array.map();
-            let mut counter : usize = 0;
-            self.cpu_menger_sponges.iter_mut().for_each(|menger_sponge|{
-                let mut pos = [0.0f32; 4];
-                pos[..3].copy_from_slice(&menger_sponge.pos);
-                mapping.pos_menger_sponges[counter] = pos;
+    fn mark_modified(&mut self, kind : ObjectKind, range : Range<usize>){
+        let modified = &mut self.modified[kind as usize];
+        *modified = match modified.take() {
+            Some(old) => Some(old.start.min(range.start)..old.end.max(range.end)),
+            None => Some(range),
+        };
+        self.bvh_outdated = true;
+    }
 
-                let mut iterations = [0.0f32; 4];
-                iterations[0] = menger_sponge.iterations;
-                mapping.iterations_menger_sponges[counter] = iterations;
+    // the range of objects of this kind that changed since clear_modified was last called
+    pub fn get_modified(&self, kind : ObjectKind) -> Option<Range<usize>> {
+        self.modified[kind as usize].clone()
+    }
 
-                let mut color = [0.0f32; 4];
-                color[..3].copy_from_slice(&menger_sponge.color);
-                mapping.color_menger_sponges[counter] = color;
+    pub fn clear_modified(&mut self, kind : ObjectKind){
+        self.modified[kind as usize] = None;
+    }
 
-                counter += 1;
-            });
-            
+    // rebuilds the bvh if any object has changed since it was last built
+    pub fn update_bvh(&mut self){
+        if self.bvh_outdated {
+            self.bvh = Bvh::build(self);
+            self.bvh_outdated = false;
+            self.bvh_modified = true;
         }
-        return menger_sponge_array;
     }
 
-}
+    // None while objects have changed and update_bvh has not been called yet, the old tree could miss objects
+    pub fn get_bvh(&self) -> Option<&Bvh> {
+        match self.bvh_outdated {
+            true => None,
+            false => Some(&self.bvh),
+        }
+    }
 
-    /* 
-}
+    pub fn is_bvh_modified(&self) -> bool {
+        self.bvh_modified
+    }
 
+    pub fn clear_bvh_modified(&mut self){
+        self.bvh_modified = false;
+    }
 
-    fn transfer_cpu_data_to_gpu(&mut self){
-        // TODO: should be optimized in the future, not really neccasary at all times to redraw/resend all data, all data has probobly not been modified
-        self.ssbo_triangles.initalize(self.cpu_triangles.clone()); // clone really neccasary?
-        self.ssbo_spheres.initalize(self.cpu_spheres.clone());
+    pub fn set_use_bvh(&mut self, use_bvh : bool){
+        self.use_bvh = use_bvh;
     }
-    */
+
+    pub fn get_use_bvh(&self) -> bool {
+        self.use_bvh
+    }
+
+    pub fn set_use_shadows(&mut self, use_shadows : bool){
+        self.use_shadows = use_shadows;
+    }
+
+    pub fn get_use_shadows(&self) -> bool {
+        self.use_shadows
+    }
+
+    pub fn set_shadow_softness(&mut self, shadow_softness : f32){
+        self.shadow_softness = shadow_softness;
+    }
+
+    pub fn get_shadow_softness(&self) -> f32 {
+        self.shadow_softness
+    }
+
+    pub fn set_ambient_occlusion(&mut self, ambient_occlusion : AmbientOcclusion){
+        self.ambient_occlusion = ambient_occlusion;
+    }
+
+    pub fn get_ambient_occlusion(&self) -> AmbientOcclusion {
+        self.ambient_occlusion
+    }
+
+    pub fn set_max_bounces(&mut self, max_bounces : u32){
+        self.max_bounces = max_bounces;
+    }
+
+    pub fn get_max_bounces(&self) -> u32 {
+        self.max_bounces
+    }
+}
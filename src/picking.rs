@@ -0,0 +1,28 @@
+use crate::camera::Camera;
+use crate::cpu_renderer::{get_ray, march_ray, MarchResult, MAX_DEPTH};
+use crate::object_handler::{ObjectHandeler, ObjectId};
+use crate::sdf::{closest_object, on_ground};
+
+// converts a position on the screen, in pixels from the top left corner, to uv coordinates like the ones in the shader
+pub fn screen_to_uv(pos : [f32; 2], screen_size : [f32; 2]) -> [f32; 2] {
+    [
+        2.0 * pos[0] / screen_size[0] - 1.0,
+        1.0 - 2.0 * pos[1] / screen_size[1]
+    ]
+}
+
+// the inverse of screen_to_uv
+pub fn uv_to_screen(uv : [f32; 2], screen_size : [f32; 2]) -> [f32; 2] {
+    [
+        (uv[0] + 1.0) / 2.0 * screen_size[0],
+        (1.0 - uv[1]) / 2.0 * screen_size[1]
+    ]
+}
+
+// casts a ray through uv in the same way as the shader and returns the object it hits, if any. The ground can not be picked
+pub fn pick(object_handeler : &ObjectHandeler, camera : &Camera, uv : [f32; 2]) -> Option<ObjectId> {
+    match march_ray(object_handeler, get_ray(camera, uv), MAX_DEPTH, false) {
+        MarchResult::Surface { pos, .. } if !on_ground(object_handeler, pos) => closest_object(object_handeler, pos),
+        _ => None
+    }
+}
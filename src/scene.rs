@@ -0,0 +1,617 @@
+use std::{fmt, fs, path::Path};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{Camera, CameraBookmark};
+use crate::light::Light;
+use crate::material::{default_library, duplicate_material_name, LibraryMaterial, Material};
+use crate::object_handler::{ObjectHandeler, ObjectId, ObjectKind};
+use crate::texture::{Pattern, Texture, TextureError, TextureImage, MAX_TEXTURE_IMAGES};
+use crate::sky::{Sky, SkyImages};
+use crate::ground::Ground;
+use crate::shapes::{Capsule, Combine, Cone, Cube, Cylinder, MengerSponge, Mesh, Operation, Sphere, Torus, Triangle, DEFAULT_BLEND_RADIUS};
+
+// bump this whenever the layout of SceneDocument changes, and handle the old versions in SceneDocument::from_json
+// 1 : first version
+// 2 : camera bookmarks
+// 3 : meshes
+// 4 : tori, capsules, cylinders and cones
+// 5 : rotation and scale of objects, the scale of meshes moved into their transform
+// 6 : an operation per object instead of the render mode, and the order of the objects
+// 7 : a list of lights instead of one light position
+// 8 : a material per object
+// 9 : a library of materials that the objects refer to by index
+// 10 : textures of the objects and the paths of the images they use
+// 11 : the sky and the paths of its images
+// 12 : the ground plane, before it the floor could not be changed
+// 13 : materials are referred to by name instead of by index
+pub const SCENE_FORMAT_VERSION : u32 = 13;
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    UnsupportedVersion(u32),
+    Texture { path : String, err : TextureError },
+    Sky { path : String, err : TextureError },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "could not access scene file: {}", err),
+            SceneError::Parse(err) => write!(f, "invalid scene file: {}", err),
+            SceneError::UnsupportedVersion(version) => write!(f, "scene file version {} is not supported (newest supported version is {})", version, SCENE_FORMAT_VERSION),
+            SceneError::Texture { path, err } => write!(f, "texture {}: {}", path, err),
+            SceneError::Sky { path, err } => write!(f, "sky {}: {}", path, err),
+        }
+    }
+}
+
+impl From<std::io::Error> for SceneError {
+    fn from(err : std::io::Error) -> Self {
+        SceneError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(err : serde_json::Error) -> Self {
+        SceneError::Parse(err)
+    }
+}
+
+// everything needed to recreate a scene, this is what gets written to a scene file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneDocument {
+    pub version : u32,
+    pub camera : Camera,
+    pub lights : Vec<Light>,
+    pub spheres : Vec<Sphere>,
+    pub cubes : Vec<Cube>,
+    pub triangles : Vec<Triangle>,
+    pub menger_sponges : Vec<MengerSponge>,
+    #[serde(default)]
+    pub camera_bookmarks : Vec<CameraBookmark>,
+    #[serde(default)]
+    pub meshes : Vec<Mesh>,
+    // the triangles of the meshes, first_triangle in a mesh is an index in this list
+    #[serde(default)]
+    pub mesh_triangles : Vec<Triangle>,
+    #[serde(default)]
+    pub tori : Vec<Torus>,
+    #[serde(default)]
+    pub capsules : Vec<Capsule>,
+    #[serde(default)]
+    pub cylinders : Vec<Cylinder>,
+    #[serde(default)]
+    pub cones : Vec<Cone>,
+    // the list order of the objects, older scenes have them in the order of their kinds
+    #[serde(default)]
+    pub order : Vec<ObjectId>,
+    #[serde(default = "default_library")]
+    pub materials : Vec<LibraryMaterial>,
+    // only the paths are saved, the images are read again by load
+    #[serde(default)]
+    pub textures : Vec<TextureImage>,
+    #[serde(default)]
+    pub sky : Sky,
+    // like the textures only the paths are saved
+    #[serde(default)]
+    pub sky_images : SkyImages,
+    // older scenes get the default ground, which looks like the floor they had
+    #[serde(default)]
+    pub ground : Ground,
+}
+
+impl SceneDocument {
+
+    pub fn from_scene(object_handeler : &ObjectHandeler, camera : &Camera) -> SceneDocument {
+        // only the triangles of meshes that are still in the scene are saved, removed meshes keep theirs for undo
+        let mut meshes = object_handeler.get_meshes().clone();
+        let mut mesh_triangles = Vec::new();
+        for mesh in meshes.iter_mut() {
+            let first_triangle = mesh_triangles.len();
+            mesh_triangles.extend_from_slice(&object_handeler.get_mesh_triangles()[mesh.triangles()]);
+            mesh.first_triangle = first_triangle;
+        }
+
+        SceneDocument {
+            version : SCENE_FORMAT_VERSION,
+            camera : *camera,
+            lights : object_handeler.get_lights().clone(),
+            spheres : object_handeler.get_spheres().clone(),
+            cubes : object_handeler.get_cubes().clone(),
+            triangles : object_handeler.get_triangles().clone(),
+            menger_sponges : object_handeler.get_menger_sponges().clone(),
+            camera_bookmarks : object_handeler.get_camera_bookmarks().clone(),
+            meshes,
+            mesh_triangles,
+            tori : object_handeler.get_tori().clone(),
+            capsules : object_handeler.get_capsules().clone(),
+            cylinders : object_handeler.get_cylinders().clone(),
+            cones : object_handeler.get_cones().clone(),
+            order : object_handeler.get_order().clone(),
+            materials : object_handeler.get_materials().clone(),
+            textures : object_handeler.get_textures().clone(),
+            sky : object_handeler.get_sky(),
+            sky_images : object_handeler.get_sky_images().clone(),
+            ground : object_handeler.get_ground(),
+        }
+    }
+
+    // replaces the current scene with the content of the document
+    pub fn apply(self, object_handeler : &mut ObjectHandeler, camera : &mut Camera) {
+        object_handeler.clear();
+        object_handeler.set_materials(self.materials);
+        object_handeler.set_textures(self.textures);
+        object_handeler.add_spheres_from(self.spheres);
+        object_handeler.add_cubes_from(self.cubes);
+        object_handeler.add_triangles_from(self.triangles);
+        object_handeler.add_menger_sponges_from(self.menger_sponges);
+        object_handeler.add_mesh_triangles(self.mesh_triangles);
+        object_handeler.add_meshes_from(self.meshes);
+        object_handeler.add_tori_from(self.tori);
+        object_handeler.add_capsules_from(self.capsules);
+        object_handeler.add_cylinders_from(self.cylinders);
+        object_handeler.add_cones_from(self.cones);
+        // the objects are added in the order of their kinds
+        if !self.order.is_empty() {
+            object_handeler.set_order(self.order);
+        }
+        object_handeler.set_lights(self.lights);
+        object_handeler.set_sky_images(self.sky_images);
+        object_handeler.set_sky(self.sky);
+        object_handeler.set_ground(self.ground);
+        *object_handeler.get_camera_bookmarks_reference() = self.camera_bookmarks;
+
+        *camera = self.camera;
+    }
+
+    pub fn to_json(&self) -> Result<String, SceneError> {
+        if let Some(name) = duplicate_material_name(&self.materials) {
+            return Err(SceneError::Parse(serde::de::Error::custom(format!("more than one material is named {}", name))));
+        }
+
+        let mut value = serde_json::to_value(self)?;
+        map_materials(&mut value, |material| {
+            let name = material.as_u64().and_then(|index| self.materials.get(index as usize)).map(|material| material.name.clone());
+            name.map(serde_json::Value::String).ok_or_else(|| serde::de::Error::custom("an object refers to a material that is not in materials"))
+        })?;
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
+    pub fn from_json(json : &str) -> Result<SceneDocument, SceneError> {
+        // read the version first so that older files can be migrated before they are parsed
+        let mut value : serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+        if version == 0 || version > SCENE_FORMAT_VERSION {
+            return Err(SceneError::UnsupportedVersion(version));
+        }
+
+        if version < 5 {
+            migrate_mesh_scale(&mut value);
+        }
+        if version < 6 {
+            migrate_render_mode(&mut value)?;
+        }
+        if version < 7 {
+            migrate_light_pos(&mut value)?;
+        }
+        if version < 9 {
+            migrate_object_materials(&mut value)?;
+        }
+        if version >= 13 {
+            material_names_to_indices(&mut value)?;
+        }
+
+        let document : SceneDocument = serde_json::from_value(value)?;
+
+        // a mesh outside of the mesh triangles would crash the renderer later on
+        if document.meshes.iter().any(|mesh| mesh.triangles().end > document.mesh_triangles.len()) {
+            return Err(SceneError::Parse(serde::de::Error::custom("a mesh refers to triangles that are not in mesh_triangles")));
+        }
+        if !document.order.is_empty() {
+            let mut order = document.order.clone();
+            order.sort();
+            if !order.into_iter().eq(document.kind_order()) {
+                return Err(SceneError::Parse(serde::de::Error::custom("order does not have every object exactly once")));
+            }
+        }
+        if let Some(name) = duplicate_material_name(&document.materials) {
+            return Err(SceneError::Parse(serde::de::Error::custom(format!("more than one material is named {}", name))));
+        }
+        if document.object_materials().any(|material| material >= document.materials.len()) {
+            return Err(SceneError::Parse(serde::de::Error::custom("an object refers to a material that is not in materials")));
+        }
+        if document.textures.len() > MAX_TEXTURE_IMAGES {
+            return Err(SceneError::Parse(serde::de::Error::custom(TextureError::TooManyImages)));
+        }
+        if document.object_textures().any(|texture| texture.pattern == Pattern::Image && texture.image >= document.textures.len()) {
+            return Err(SceneError::Parse(serde::de::Error::custom("an object refers to an image that is not in textures")));
+        }
+        if document.ground.material >= document.materials.len() {
+            return Err(SceneError::Parse(serde::de::Error::custom("the ground refers to a material that is not in materials")));
+        }
+        if document.ground.pattern == Pattern::Image && document.ground.image >= document.textures.len() {
+            return Err(SceneError::Parse(serde::de::Error::custom("the ground refers to an image that is not in textures")));
+        }
+        if !document.sky_images.has(document.sky.kind) {
+            return Err(SceneError::Parse(serde::de::Error::custom("the sky shows an image that is not in sky_images")));
+        }
+
+        Ok(document)
+    }
+
+    // every object in the order of their kinds, the order of scenes without an order
+    fn kind_order(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        ObjectKind::ALL.into_iter().flat_map(move |kind| {
+            let num_of = match kind {
+                ObjectKind::Sphere => self.spheres.len(),
+                ObjectKind::Cube => self.cubes.len(),
+                ObjectKind::Triangle => self.triangles.len(),
+                ObjectKind::MengerSponge => self.menger_sponges.len(),
+                ObjectKind::Mesh => self.meshes.len(),
+                ObjectKind::Torus => self.tori.len(),
+                ObjectKind::Capsule => self.capsules.len(),
+                ObjectKind::Cylinder => self.cylinders.len(),
+                ObjectKind::Cone => self.cones.len(),
+            };
+            (0..num_of).map(move |index| ObjectId::new(kind, index))
+        })
+    }
+
+    // the material indices of every object
+    fn object_materials(&self) -> impl Iterator<Item = usize> + '_ {
+        self.spheres.iter().map(|sphere| sphere.material)
+            .chain(self.cubes.iter().map(|cube| cube.material))
+            .chain(self.triangles.iter().map(|triangle| triangle.material))
+            .chain(self.menger_sponges.iter().map(|menger_sponge| menger_sponge.material))
+            .chain(self.meshes.iter().map(|mesh| mesh.material))
+            .chain(self.tori.iter().map(|torus| torus.material))
+            .chain(self.capsules.iter().map(|capsule| capsule.material))
+            .chain(self.cylinders.iter().map(|cylinder| cylinder.material))
+            .chain(self.cones.iter().map(|cone| cone.material))
+    }
+
+    fn object_textures(&self) -> impl Iterator<Item = Texture> + '_ {
+        self.spheres.iter().map(|sphere| sphere.texture)
+            .chain(self.cubes.iter().map(|cube| cube.texture))
+            .chain(self.triangles.iter().map(|triangle| triangle.texture))
+            .chain(self.menger_sponges.iter().map(|menger_sponge| menger_sponge.texture))
+            .chain(self.meshes.iter().map(|mesh| mesh.texture))
+            .chain(self.tori.iter().map(|torus| torus.texture))
+            .chain(self.capsules.iter().map(|capsule| capsule.texture))
+            .chain(self.cylinders.iter().map(|cylinder| cylinder.texture))
+            .chain(self.cones.iter().map(|cone| cone.texture))
+    }
+
+    pub fn save(&self, path : impl AsRef<Path>) -> Result<(), SceneError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn load(path : impl AsRef<Path>) -> Result<SceneDocument, SceneError> {
+        let json = fs::read_to_string(path)?;
+        let mut document = SceneDocument::from_json(&json)?;
+
+        // the file only has the paths of the images
+        for texture in document.textures.iter_mut() {
+            *texture = TextureImage::load(&texture.path).map_err(|err| SceneError::Texture { path : texture.path.clone(), err })?;
+        }
+        document.sky_images = document.sky_images.reload().map_err(|(path, err)| SceneError::Sky { path, err })?;
+        Ok(document)
+    }
+}
+
+// before version 5 meshes had a single scale instead of a transform
+fn migrate_mesh_scale(value : &mut serde_json::Value) {
+    let Some(meshes) = value.get_mut("meshes").and_then(|meshes| meshes.as_array_mut()) else {
+        return;
+    };
+
+    for mesh in meshes.iter_mut().filter_map(|mesh| mesh.as_object_mut()) {
+        let scale = mesh.remove("scale").and_then(|scale| scale.as_f64()).unwrap_or(1.0);
+        mesh.insert(String::from("transform"), serde_json::json!({ "rotation" : [1.0, 0.0, 0.0, 0.0], "scale" : [scale, scale, scale] }));
+    }
+}
+
+// before version 6 one render mode was used for the whole scene. the blending modes (2 and 3) become smooth unions,
+// the rest plain unions. mode 2 did not show the menger sponges and mode 1 was a test of boolean operations, neither is kept
+fn migrate_render_mode(value : &mut serde_json::Value) -> Result<(), SceneError> {
+    let render_mode = value.as_object_mut().and_then(|document| document.remove("render_mode")).and_then(|mode| mode.as_u64()).unwrap_or(0);
+    if render_mode < 2 {
+        return Ok(());
+    }
+
+    let combine = serde_json::to_value(Combine::new(Operation::SmoothUnion, DEFAULT_BLEND_RADIUS))?;
+    for list in ["spheres", "cubes", "triangles", "menger_sponges", "meshes", "tori", "capsules", "cylinders", "cones"] {
+        let Some(objects) = value.get_mut(list).and_then(|objects| objects.as_array_mut()) else {
+            continue;
+        };
+        for object in objects.iter_mut().filter_map(|object| object.as_object_mut()) {
+            object.insert(String::from("combine"), combine.clone());
+        }
+    }
+    Ok(())
+}
+
+// before version 7 there was one light position, it becomes a point light that looks the same
+fn migrate_light_pos(value : &mut serde_json::Value) -> Result<(), SceneError> {
+    let Some(document) = value.as_object_mut() else {
+        return Ok(());
+    };
+    let Some(light_pos) = document.remove("light_pos") else {
+        return Ok(());
+    };
+
+    let pos : [f32; 3] = serde_json::from_value(light_pos)?;
+    document.insert(String::from("lights"), serde_json::to_value(vec![Light::point(pos)])?);
+    Ok(())
+}
+
+// in version 8 every object had its own material, the different ones become materials in the library
+fn migrate_object_materials(value : &mut serde_json::Value) -> Result<(), SceneError> {
+    let mut library = default_library();
+
+    for list in ["spheres", "cubes", "triangles", "menger_sponges", "meshes", "tori", "capsules", "cylinders", "cones", "mesh_triangles"] {
+        let Some(objects) = value.get_mut(list).and_then(|objects| objects.as_array_mut()) else {
+            continue;
+        };
+        for object in objects.iter_mut().filter_map(|object| object.as_object_mut()) {
+            let Some(material) = object.remove("material") else {
+                continue;
+            };
+            // the triangles of a mesh use the material of the mesh
+            if list == "mesh_triangles" {
+                continue;
+            }
+
+            let material : Material = serde_json::from_value(material)?;
+            let index = match library.iter().position(|library_material| library_material.material == material) {
+                Some(index) => index,
+                None => {
+                    library.push(LibraryMaterial::new(format!("Material {}", library.len()), material));
+                    library.len() - 1
+                },
+            };
+            object.insert(String::from("material"), serde_json::to_value(index)?);
+        }
+    }
+
+    if let Some(document) = value.as_object_mut() {
+        document.insert(String::from("materials"), serde_json::to_value(library)?);
+    }
+    Ok(())
+}
+
+// replaces the material of every object and of the ground with what `f` returns for it
+fn map_materials(value : &mut serde_json::Value, mut f : impl FnMut(&serde_json::Value) -> Result<serde_json::Value, serde_json::Error>) -> Result<(), SceneError> {
+    for list in ["spheres", "cubes", "triangles", "menger_sponges", "meshes", "tori", "capsules", "cylinders", "cones", "mesh_triangles"] {
+        let Some(objects) = value.get_mut(list).and_then(|objects| objects.as_array_mut()) else {
+            continue;
+        };
+        for object in objects.iter_mut() {
+            if let Some(material) = object.get_mut("material") {
+                *material = f(material)?;
+            }
+        }
+    }
+
+    if let Some(material) = value.get_mut("ground").and_then(|ground| ground.get_mut("material")) {
+        *material = f(material)?;
+    }
+    Ok(())
+}
+
+// from version 13 the file has the names of the materials, SceneDocument has their indices like the objects in the scene
+fn material_names_to_indices(value : &mut serde_json::Value) -> Result<(), SceneError> {
+    let materials : Vec<LibraryMaterial> = match value.get("materials") {
+        Some(materials) => serde_json::from_value(materials.clone())?,
+        None => default_library(),
+    };
+    // a name that more than one material has could refer to either of them
+    if let Some(name) = duplicate_material_name(&materials) {
+        return Err(SceneError::Parse(serde::de::Error::custom(format!("more than one material is named {}", name))));
+    }
+
+    map_materials(value, |material| {
+        let name = material.as_str().ok_or_else(|| serde::de::Error::custom("materials are referred to by their name"))?;
+        let index = materials.iter().position(|material| material.name == name)
+            .ok_or_else(|| serde::de::Error::custom(format!("there is no material named {}", name)))?;
+        Ok(serde_json::Value::from(index))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    use crate::light::LightKind;
+    use crate::object_handler::SceneObject;
+    use crate::shapes::Transform;
+    use crate::sky::SkyKind;
+
+    // a scene file with the lists that every version has, extra adds to them or replaces them
+    fn scene_json(version : u32, extra : serde_json::Value) -> String {
+        let mut scene = json!({
+            "version" : version,
+            "camera" : Camera::new(),
+            "spheres" : [],
+            "cubes" : [],
+            "triangles" : [],
+            "menger_sponges" : [],
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            scene[key] = value.clone();
+        }
+        scene.to_string()
+    }
+
+    fn sphere_json() -> serde_json::Value {
+        json!({ "pos" : [0.0, 1.0, 0.0], "color" : [1.0, 0.0, 0.0], "radius" : 1.0 })
+    }
+
+    // message is a part of the error, so that the test fails if the file is rejected for another reason
+    fn assert_parse_error(json : &str, message : &str) {
+        match SceneDocument::from_json(json) {
+            Err(SceneError::Parse(err)) => assert!(err.to_string().contains(message), "expected \"{}\", got \"{}\"", message, err),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn mesh_scale_becomes_transform() {
+        let json = scene_json(4, json!({
+            "light_pos" : [0.0, 5.0, 0.0],
+            "meshes" : [{ "pos" : [0.0, 0.0, 0.0], "color" : [1.0, 1.0, 1.0], "first_triangle" : 0, "num_triangles" : 1, "scale" : 2.0 }],
+            "mesh_triangles" : [{ "v1" : [0.0, 0.0, 0.0], "v2" : [1.0, 0.0, 0.0], "v3" : [0.0, 1.0, 0.0], "color" : [1.0, 1.0, 1.0] }],
+        }));
+
+        let document = SceneDocument::from_json(&json).unwrap();
+        assert_eq!(document.meshes[0].transform, Transform { rotation : [1.0, 0.0, 0.0, 0.0], scale : [2.0; 3] });
+    }
+
+    #[test]
+    fn render_mode_becomes_operation() {
+        let expected = [(0, Operation::Union), (1, Operation::Union), (2, Operation::SmoothUnion), (3, Operation::SmoothUnion)];
+
+        for (render_mode, operation) in expected {
+            let json = scene_json(5, json!({ "light_pos" : [0.0, 5.0, 0.0], "render_mode" : render_mode, "spheres" : [sphere_json()] }));
+
+            let document = SceneDocument::from_json(&json).unwrap();
+            assert_eq!(document.spheres[0].combine, Combine::new(operation, DEFAULT_BLEND_RADIUS), "render mode {}", render_mode);
+        }
+    }
+
+    #[test]
+    fn light_pos_becomes_point_light() {
+        let json = scene_json(6, json!({ "light_pos" : [1.0, 2.0, 3.0] }));
+
+        let document = SceneDocument::from_json(&json).unwrap();
+        assert_eq!(document.lights, vec![Light::point([1.0, 2.0, 3.0])]);
+        assert_eq!(document.lights[0].kind, LightKind::Point);
+    }
+
+    #[test]
+    fn object_materials_become_library() {
+        let shiny = Material { metallic : 1.0, ..Material::DEFAULT };
+        let mut spheres = Vec::new();
+        for material in [Material::DEFAULT, shiny, shiny] {
+            let mut sphere = sphere_json();
+            sphere["material"] = json!(material);
+            spheres.push(sphere);
+        }
+        let triangle = json!({ "v1" : [0.0, 0.0, 0.0], "v2" : [1.0, 0.0, 0.0], "v3" : [0.0, 1.0, 0.0], "color" : [1.0, 1.0, 1.0], "material" : shiny });
+        let json = scene_json(8, json!({ "lights" : [], "spheres" : spheres, "triangles" : [triangle] }));
+
+        let document = SceneDocument::from_json(&json).unwrap();
+        assert_eq!(document.materials.len(), 2);
+        assert_eq!(document.materials[0], default_library()[0]);
+        assert_eq!(document.materials[1].material, shiny);
+        assert_eq!(document.spheres.iter().map(|sphere| sphere.material).collect::<Vec<usize>>(), vec![0, 1, 1]);
+        assert_eq!(document.triangles[0].material, 1);
+    }
+
+    #[test]
+    fn unsupported_versions() {
+        for version in [0, SCENE_FORMAT_VERSION + 1] {
+            let result = SceneDocument::from_json(&scene_json(version, json!({ "lights" : [] })));
+            assert!(matches!(result, Err(SceneError::UnsupportedVersion(v)) if v == version), "version {}: {:?}", version, result);
+        }
+        assert!(matches!(SceneDocument::from_json("{}"), Err(SceneError::UnsupportedVersion(0))));
+        assert!(matches!(SceneDocument::from_json("not json"), Err(SceneError::Parse(_))));
+    }
+
+    #[test]
+    fn invalid_references() {
+        let current = |extra : serde_json::Value| {
+            let mut extra = extra;
+            extra["lights"] = json!([]);
+            scene_json(SCENE_FORMAT_VERSION, extra)
+        };
+        let with_material = |mut object : serde_json::Value, material : serde_json::Value| {
+            object["material"] = material;
+            object
+        };
+        let mut sphere_with_image = sphere_json();
+        sphere_with_image["texture"] = json!(Texture { pattern : Pattern::Image, ..Texture::NONE });
+
+        assert!(SceneDocument::from_json(&current(json!({ "spheres" : [sphere_json()] }))).is_ok());
+
+        assert_parse_error(&current(json!({
+            "meshes" : [{ "pos" : [0.0, 0.0, 0.0], "color" : [1.0, 1.0, 1.0], "first_triangle" : 0, "num_triangles" : 1 }],
+        })), "a mesh refers to triangles");
+        assert_parse_error(&current(json!({
+            "spheres" : [sphere_json(), sphere_json()],
+            "order" : [ObjectId::new(ObjectKind::Sphere, 0), ObjectId::new(ObjectKind::Sphere, 0)],
+        })), "order does not have every object");
+        assert_parse_error(&current(json!({ "spheres" : [with_material(sphere_json(), json!("Missing"))] })), "there is no material named Missing");
+        assert_parse_error(&current(json!({ "spheres" : [with_material(sphere_json(), json!(0))] })), "materials are referred to by their name");
+        assert_parse_error(&current(json!({ "ground" : with_material(json!(Ground::DEFAULT), json!("Missing")) })), "there is no material named Missing");
+        assert_parse_error(&current(json!({ "materials" : [default_library()[0], default_library()[0]] })), "more than one material is named Default");
+        assert_parse_error(&current(json!({ "spheres" : [sphere_with_image] })), "an object refers to an image");
+
+        // before version 13 the materials were referred to by index
+        let indexed = |extra : serde_json::Value| {
+            let mut extra = extra;
+            extra["lights"] = json!([]);
+            scene_json(12, extra)
+        };
+        assert_eq!(SceneDocument::from_json(&indexed(json!({ "spheres" : [with_material(sphere_json(), json!(0))] }))).unwrap().spheres[0].material, 0);
+        assert_parse_error(&indexed(json!({ "spheres" : [with_material(sphere_json(), json!(1))] })), "an object refers to a material");
+        assert_parse_error(&indexed(json!({ "ground" : Ground { material : 1, ..Ground::DEFAULT } })), "the ground refers to a material");
+        assert_parse_error(&current(json!({ "ground" : with_material(json!(Ground { pattern : Pattern::Image, ..Ground::DEFAULT }), json!("Default")) })), "the ground refers to an image");
+        assert_parse_error(&current(json!({ "sky" : Sky { kind : SkyKind::Cubemap, ..Sky::DEFAULT } })), "the sky shows an image");
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut materials = default_library();
+        materials.push(LibraryMaterial::new("Mirror", Material { reflectivity : 1.0, ..Material::DEFAULT }));
+        object_handeler.set_materials(materials);
+        object_handeler.set_lights(vec![Light::point([1.0, 2.0, 3.0])]);
+
+        let mut cube = Cube::new([1.0, 0.5, 2.0], [0.5; 3], [0.2, 0.3, 1.0]);
+        cube.combine = Combine::new(Operation::SmoothSubtraction, 0.3);
+        cube.material = 1;
+        object_handeler.add_object(SceneObject::Sphere(Sphere::new([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], 1.0)));
+        let cube_id = object_handeler.add_object(SceneObject::Cube(cube));
+        // the cube is first in the list, which is not the order of the kinds
+        object_handeler.move_object(cube_id, 0);
+
+        let mut camera = Camera::new();
+        camera.pos = [0.0, 2.0, -4.0];
+
+        let path = std::env::temp_dir().join(format!("ray-marching-scene-test-{}.json", std::process::id()));
+        SceneDocument::from_scene(&object_handeler, &camera).save(&path).unwrap();
+        let document = SceneDocument::load(&path);
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(json.contains("\"material\": \"Mirror\""), "{}", json);
+
+        let mut loaded = ObjectHandeler::new();
+        let mut loaded_camera = Camera::new();
+        document.unwrap().apply(&mut loaded, &mut loaded_camera);
+
+        assert_eq!(loaded_camera, camera);
+        assert_eq!(loaded.get_spheres(), object_handeler.get_spheres());
+        assert_eq!(loaded.get_cubes(), object_handeler.get_cubes());
+        assert_eq!(loaded.get_order(), object_handeler.get_order());
+        assert_eq!(loaded.get_lights(), object_handeler.get_lights());
+        assert_eq!(loaded.get_materials(), object_handeler.get_materials());
+        assert_eq!(loaded.get_ground(), object_handeler.get_ground());
+        assert_eq!(loaded.get_sky(), object_handeler.get_sky());
+    }
+
+    #[test]
+    fn duplicate_material_names_are_not_saved() {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut materials = default_library();
+        materials.push(LibraryMaterial::new("Default", Material { metallic : 1.0, ..Material::DEFAULT }));
+        object_handeler.set_materials(materials);
+
+        let result = SceneDocument::from_scene(&object_handeler, &Camera::new()).to_json();
+        assert!(matches!(&result, Err(SceneError::Parse(err)) if err.to_string().contains("more than one material is named Default")), "{:?}", result);
+    }
+}
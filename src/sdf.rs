@@ -0,0 +1,511 @@
+// CPU version of the distance functions in shaders/sdf.glsl
+// every function here should give the same result as its glsl counterpart, so keep them in sync when the shader changes
+
+use crate::bvh::{Aabb, Bvh, BvhContent};
+use crate::material::Material;
+use crate::object_handler::{ObjectHandeler, ObjectId, ObjectKind};
+use crate::shapes::{Capsule, Combine, Cone, Cube, Cylinder, Mesh, Operation, Sphere, Torus, Transform, Triangle};
+use crate::texture::apply_texture;
+use crate::vec_util::{cross, dot, length, normalize, vec_add, vec_scale};
+
+// the same as MIN_BLEND_RADIUS in the shader, blend divides by the radius
+const MIN_BLEND_RADIUS : f32 = 0.0001;
+
+// the same as MESH_BOUNDS_MARGIN in the shader. further than this from the box around a mesh the distance to the box is used,
+// closer the triangles are needed for the normals, the shadows and the smooth operations
+const MESH_BOUNDS_MARGIN : f32 = 1.0;
+
+// the same as the glsl functions with the same name, all work per component
+fn vec_abs(v : [f32; 3]) -> [f32; 3] {
+    [v[0].abs(), v[1].abs(), v[2].abs()]
+}
+
+fn vec_max(v : [f32; 3], k : f32) -> [f32; 3] {
+    [v[0].max(k), v[1].max(k), v[2].max(k)]
+}
+
+// glsl mod, which unlike % always has the sign of y
+fn glsl_mod(x : f32, y : f32) -> f32 {
+    x - y * f32::floor(x / y)
+}
+
+fn vec_mod(v : [f32; 3], k : f32) -> [f32; 3] {
+    [glsl_mod(v[0], k), glsl_mod(v[1], k), glsl_mod(v[2], k)]
+}
+
+fn dot2(v : [f32; 3]) -> f32 {
+    dot(v, v)
+}
+
+// the 2d vectors in the shader, used for the shapes around the y axis
+fn length2d(v : [f32; 2]) -> f32 {
+    f32::sqrt(v[0] * v[0] + v[1] * v[1])
+}
+
+fn dot2d(a : [f32; 2], b : [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn sign(x : f32) -> f32 {
+    if x > 0.0 { 1.0 } else if x < 0.0 { -1.0 } else { 0.0 }
+}
+
+// "blendFactor" in the shader, how much of a is in the smooth minimum of a and b
+fn blend_factor(a : f32, b : f32, k : f32) -> f32 {
+    (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0)
+}
+
+// smooth minimum that also blends the colors, returns (color, distance)
+pub fn blend(a : f32, b : f32, col_a : [f32; 3], col_b : [f32; 3], k : f32) -> ([f32; 3], f32) {
+    let h = blend_factor(a, b, k);
+    let blend_dst = (a - b) * h + b - k * h * (1.0 - h);
+    let blend_col = vec_add(vec_scale(vec_add(col_a, col_b, -1.0), h), col_b, 1.0);
+    (blend_col, blend_dst)
+}
+
+pub fn sphere_dist(sphere : &Sphere, pos : [f32; 3]) -> f32 {
+    length(vec_add(sphere.pos, pos, -1.0)) - sphere.radius
+}
+
+// the normal is calculated from the vertices, in the same way as newTriangle in the shader
+// it is uploaded with the triangle, and is zero for triangles without an area so that the shader does not get nan
+pub fn triangle_normal(triangle : &Triangle) -> [f32; 3] {
+    let ba = vec_add(triangle.v2, triangle.v1, -1.0);
+    let ac = vec_add(triangle.v1, triangle.v3, -1.0);
+    let normal = cross(ba, ac);
+
+    if length(normal) == 0.0 {
+        return [0.0; 3];
+    }
+    normalize(normal)
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+pub fn triangle_dist(triangle : &Triangle, pos : [f32; 3]) -> f32 {
+    let a = triangle.v1;
+    let b = triangle.v2;
+    let c = triangle.v3;
+
+    let ba = vec_add(b, a, -1.0); let pa = vec_add(pos, a, -1.0);
+    let cb = vec_add(c, b, -1.0); let pb = vec_add(pos, b, -1.0);
+    let ac = vec_add(a, c, -1.0); let pc = vec_add(pos, c, -1.0);
+    let nor = triangle_normal(triangle);
+
+    let outside = sign(dot(cross(ba, nor), pa))
+        + sign(dot(cross(cb, nor), pb))
+        + sign(dot(cross(ac, nor), pc)) < 2.0;
+
+    let dst2 = if outside {
+        let edge = |e : [f32; 3], p : [f32; 3]| dot2(vec_add(vec_scale(e, (dot(e, p) / dot2(e)).clamp(0.0, 1.0)), p, -1.0));
+        f32::min(f32::min(edge(ba, pa), edge(cb, pb)), edge(ac, pc))
+    } else {
+        dot(nor, pa) * dot(nor, pa) / dot2(nor)
+    };
+
+    f32::sqrt(dst2)
+}
+
+// "meshDist" in the shader, the closest triangle of the mesh. triangles are the mesh triangles of the object handeler
+pub fn mesh_dist(mesh : &Mesh, triangles : &[Triangle], pos : [f32; 3]) -> f32 {
+    let local_pos = vec_add(pos, mesh.pos, -1.0);
+
+    // the box is around the triangles, so the distance to it is never more than the distance to the mesh
+    let bounds_dist = Aabb::new(mesh.bounds_min, mesh.bounds_max).dist(local_pos);
+    if bounds_dist > MESH_BOUNDS_MARGIN {
+        return bounds_dist;
+    }
+
+    triangles[mesh.triangles()].iter().fold(10000000.0, |dst, triangle| f32::min(dst, triangle_dist(triangle, local_pos)))
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+pub fn cube_dist(cube : &Cube, pos : [f32; 3]) -> f32 {
+    let p = vec_add(pos, cube.pos, -1.0);
+    let q = vec_add(vec_abs(p), cube.dim, -1.0);
+    length(vec_max(q, 0.0)) + f32::min(f32::max(q[0], f32::max(q[1], q[2])), 0.0)
+}
+
+// the torus, capsule, cylinder and cone are all around the y axis
+// from https://iquilezles.org/articles/distfunctions/
+pub fn torus_dist(torus : &Torus, pos : [f32; 3]) -> f32 {
+    let p = vec_add(pos, torus.pos, -1.0);
+    let q = [length2d([p[0], p[2]]) - torus.radius, p[1]];
+    length2d(q) - torus.thickness
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+pub fn capsule_dist(capsule : &Capsule, pos : [f32; 3]) -> f32 {
+    let mut p = vec_add(pos, capsule.pos, -1.0);
+    p[1] -= p[1].clamp(-capsule.half_height, capsule.half_height);
+    length(p) - capsule.radius
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+pub fn cylinder_dist(cylinder : &Cylinder, pos : [f32; 3]) -> f32 {
+    let p = vec_add(pos, cylinder.pos, -1.0);
+    let d = [length2d([p[0], p[2]]) - cylinder.radius, p[1].abs() - cylinder.half_height];
+    f32::min(f32::max(d[0], d[1]), 0.0) + length2d([d[0].max(0.0), d[1].max(0.0)])
+}
+
+// from https://iquilezles.org/articles/distfunctions/
+pub fn cone_dist(cone : &Cone, pos : [f32; 3]) -> f32 {
+    let p = vec_add(pos, cone.pos, -1.0);
+    let h = cone.half_height;
+    let r1 = cone.bottom_radius;
+    let r2 = cone.top_radius;
+
+    let q = [length2d([p[0], p[2]]), p[1]];
+    let k1 = [r2, h];
+    let k2 = [r2 - r1, 2.0 * h];
+    let ca = [q[0] - f32::min(q[0], if q[1] < 0.0 { r1 } else { r2 }), q[1].abs() - h];
+    let t = (dot2d([k1[0] - q[0], k1[1] - q[1]], k2) / dot2d(k2, k2)).clamp(0.0, 1.0);
+    let cb = [q[0] - k1[0] + k2[0] * t, q[1] - k1[1] + k2[1] * t];
+    let s = if cb[0] < 0.0 && ca[1] < 0.0 { -1.0 } else { 1.0 };
+    s * f32::sqrt(f32::min(dot2d(ca, ca), dot2d(cb, cb)))
+}
+
+// "cross" in the shader, three infinitely long boxes through offset
+pub fn cross_dist(pos : [f32; 3], side_length : f32, offset : [f32; 3]) -> f32 {
+    let inf = 100000.0;
+    let dist_a = cube_dist(&Cube::new(offset, [inf, side_length, side_length], [1.0; 3]), pos);
+    let dist_b = cube_dist(&Cube::new(offset, [side_length, inf, side_length], [1.0; 3]), pos);
+    let dist_c = cube_dist(&Cube::new(offset, [side_length, side_length, inf], [1.0; 3]), pos);
+
+    f32::min(dist_a, f32::min(dist_b, dist_c))
+}
+
+//https://iquilezles.org/articles/menger/
+pub fn menger_sponge_dist(ray_pos : [f32; 3], num_iterations : i32) -> f32 {
+    let cube_width = 2.0;
+    let one_third = 1.0 / 3.0;
+    let sponge_cube = cube_dist(&Cube::new([0.0; 3], [1.0; 3], [1.0; 3]), ray_pos);
+    let mut menger_sponge_dist = sponge_cube;
+
+    let mut scale = 1.0;
+    for _ in 0..num_iterations {
+        let boxed_width = cube_width / scale;
+
+        let translation = -boxed_width / 2.0;
+        let ray = vec_add(ray_pos, [translation; 3], -1.0);
+        let mut repeated_pos = vec_mod(ray, boxed_width);
+        repeated_pos = vec_add(repeated_pos, [translation; 3], 1.0);
+
+        repeated_pos = vec_scale(repeated_pos, scale);
+
+        let mut crosses_dist = cross_dist(vec_scale(repeated_pos, 1.0 / one_third), 1.0, [0.0; 3]) * one_third;
+
+        crosses_dist /= scale;
+
+        menger_sponge_dist = f32::max(menger_sponge_dist, -crosses_dist);
+
+        scale *= 3.0;
+    }
+    menger_sponge_dist
+}
+
+// what minDist in the shader keeps track of while it goes through the objects
+struct DistState {
+    dst : f32,
+    clr : [f32; 3],
+    mat : Material,
+}
+
+// distance from pos to the scene and the color of the closest surface, without the textures
+pub fn min_dist(object_handeler : &ObjectHandeler, pos : [f32; 3]) -> (f32, [f32; 3]) {
+    let mut state = scene_dist(object_handeler, pos, None);
+    add_ground(object_handeler, pos, None, &mut state);
+    (state.dst, state.clr)
+}
+
+// "surfaceAt" in the shader, the material and the color of the surface at pos. the color is textured when the normal is given
+pub fn surface_at(object_handeler : &ObjectHandeler, pos : [f32; 3], norm : Option<[f32; 3]>) -> (Material, [f32; 3]) {
+    let mut state = scene_dist(object_handeler, pos, norm);
+    add_ground(object_handeler, pos, norm, &mut state);
+    (state.mat, state.clr)
+}
+
+// "addGround" in the shader, a union with everything before it like an object after the last one
+fn add_ground(object_handeler : &ObjectHandeler, pos : [f32; 3], norm : Option<[f32; 3]>, state : &mut DistState) {
+    let ground = object_handeler.get_ground();
+    if !ground.enabled {
+        return;
+    }
+
+    // the pattern is only needed where a ray hits, like the textures
+    let color = if norm.is_some() { ground.color_at(object_handeler.get_textures(), pos) } else { ground.color };
+    let material = object_handeler.get_materials()[ground.material].material;
+    combine(Combine::UNION, ground.dist(pos), color, material, state);
+}
+
+// "onGround" in the shader, whether the surface at pos is the ground and not an object
+pub fn on_ground(object_handeler : &ObjectHandeler, pos : [f32; 3]) -> bool {
+    let ground = object_handeler.get_ground();
+    ground.enabled && ground.dist(pos) < scene_dist(object_handeler, pos, None).dst
+}
+
+// "sceneDist" in the shader, norm is the normal of the surface for the textures
+fn scene_dist(object_handeler : &ObjectHandeler, pos : [f32; 3], norm : Option<[f32; 3]>) -> DistState {
+    let mut state = DistState { dst : 10000000.0, clr : [0.0; 3], mat : Material::ZERO };
+
+    // the objects that are not in the bvh are applied in list order afterwards
+    let ordered_start = match object_handeler.get_bvh() {
+        Some(bvh) if object_handeler.get_use_bvh() => {
+            bvh_min_dist(object_handeler, bvh, pos, norm, &mut state);
+            bvh.get_ordered_start()
+        },
+        _ => 0,
+    };
+
+    for id in object_handeler.object_ids().skip(ordered_start) {
+        add_object(object_handeler, id, pos, norm, &mut state);
+    }
+
+    state
+}
+
+// "addObject" in the shader
+fn add_object(object_handeler : &ObjectHandeler, id : ObjectId, pos : [f32; 3], norm : Option<[f32; 3]>, state : &mut DistState) {
+    let (new_dst, mut color) = object_dist_color(object_handeler, id, pos);
+    let object = object_handeler.get_object(id);
+    if let Some(norm) = norm {
+        let (center, transform) = object.texture_space();
+        color = apply_texture(color, &object.texture(), object_handeler.get_textures(), center, &transform, pos, norm);
+    }
+    let material = object_handeler.get_materials()[object.material()].material;
+    combine(object.combine(), new_dst, color, material, state);
+}
+
+// "combine" in the shader, applies the operation of an object to everything before it, the material follows the color
+fn combine(combine : Combine, new_dst : f32, color : [f32; 3], material : Material, state : &mut DistState) {
+    let k = f32::max(combine.blend_radius, MIN_BLEND_RADIUS);
+
+    match combine.operation {
+        Operation::Union => if new_dst < state.dst {
+            state.dst = new_dst;
+            state.clr = color;
+            state.mat = material;
+        },
+        // the cut surface gets the color of the object that cuts
+        Operation::Subtraction => if -new_dst > state.dst {
+            state.dst = -new_dst;
+            state.clr = color;
+            state.mat = material;
+        },
+        Operation::Intersection => if new_dst > state.dst {
+            state.dst = new_dst;
+            state.clr = color;
+            state.mat = material;
+        },
+        // the smooth maximums are the smooth minimum of the negated distances
+        Operation::SmoothUnion => {
+            state.mat = material.mix(&state.mat, blend_factor(state.dst, new_dst, k));
+            (state.clr, state.dst) = blend(state.dst, new_dst, state.clr, color, k);
+        },
+        Operation::SmoothSubtraction => {
+            state.mat = material.mix(&state.mat, blend_factor(-state.dst, new_dst, k));
+            let (clr, dst) = blend(-state.dst, new_dst, state.clr, color, k);
+            state.dst = -dst;
+            state.clr = clr;
+        },
+        Operation::SmoothIntersection => {
+            state.mat = material.mix(&state.mat, blend_factor(-state.dst, -new_dst, k));
+            let (clr, dst) = blend(-state.dst, -new_dst, state.clr, color, k);
+            state.dst = -dst;
+            state.clr = clr;
+        },
+    }
+}
+
+// "bvhMinDist" in the shader, goes through the tree with the closest child first and skips nodes that are further away than the current distance.
+// the boxes of smooth unions are grown by their blend radius, so a skipped object can not change the distance or the color
+fn bvh_min_dist(object_handeler : &ObjectHandeler, bvh : &Bvh, pos : [f32; 3], norm : Option<[f32; 3]>, state : &mut DistState) {
+    let nodes = bvh.get_nodes();
+    if nodes.is_empty() {
+        return;
+    }
+
+    let mut stack = vec![0];
+    while let Some(index) = stack.pop() {
+        let node = &nodes[index];
+        if node.bounds.dist(pos) >= state.dst {
+            continue;
+        }
+
+        match node.content {
+            BvhContent::Object(id) => add_object(object_handeler, id, pos, norm, state),
+            BvhContent::Children(left, right) => {
+                // the child that is visited first is pushed last
+                if nodes[left].bounds.dist(pos) < nodes[right].bounds.dist(pos) {
+                    stack.push(right);
+                    stack.push(left);
+                } else {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            },
+        }
+    }
+}
+
+// "untransform" and "distScale" in the shader, dist is the distance function of the object without its transform
+fn transformed_dist(transform : &Transform, center : [f32; 3], pos : [f32; 3], dist : impl Fn([f32; 3]) -> f32) -> f32 {
+    dist(transform.unapply(center, pos)) * transform.dist_scale()
+}
+
+// "objectDistColor" in the shader, the distance to a single object and its color
+fn object_dist_color(object_handeler : &ObjectHandeler, id : ObjectId, pos : [f32; 3]) -> (f32, [f32; 3]) {
+    match id.kind {
+        ObjectKind::Sphere => {
+            let sphere = &object_handeler.get_spheres()[id.index];
+            (transformed_dist(&sphere.transform, sphere.pos, pos, |p| sphere_dist(sphere, p)), sphere.color)
+        },
+        ObjectKind::Cube => {
+            let cube = &object_handeler.get_cubes()[id.index];
+            (transformed_dist(&cube.transform, cube.pos, pos, |p| cube_dist(cube, p)), cube.color)
+        },
+        ObjectKind::Triangle => {
+            let triangle = &object_handeler.get_triangles()[id.index];
+            (triangle_dist(triangle, pos), triangle.color)
+        },
+        ObjectKind::MengerSponge => {
+            let menger_sponge = &object_handeler.get_menger_sponges()[id.index];
+            let dst = transformed_dist(&menger_sponge.transform, menger_sponge.pos, pos, |p| menger_sponge_dist(vec_add(p, menger_sponge.pos, -1.0), menger_sponge.iterations as i32));
+            (dst, menger_sponge.color)
+        },
+        ObjectKind::Mesh => {
+            let mesh = &object_handeler.get_meshes()[id.index];
+            (transformed_dist(&mesh.transform, mesh.pos, pos, |p| mesh_dist(mesh, object_handeler.get_mesh_triangles(), p)), mesh.color)
+        },
+        ObjectKind::Torus => {
+            let torus = &object_handeler.get_tori()[id.index];
+            (transformed_dist(&torus.transform, torus.pos, pos, |p| torus_dist(torus, p)), torus.color)
+        },
+        ObjectKind::Capsule => {
+            let capsule = &object_handeler.get_capsules()[id.index];
+            (transformed_dist(&capsule.transform, capsule.pos, pos, |p| capsule_dist(capsule, p)), capsule.color)
+        },
+        ObjectKind::Cylinder => {
+            let cylinder = &object_handeler.get_cylinders()[id.index];
+            (transformed_dist(&cylinder.transform, cylinder.pos, pos, |p| cylinder_dist(cylinder, p)), cylinder.color)
+        },
+        ObjectKind::Cone => {
+            let cone = &object_handeler.get_cones()[id.index];
+            (transformed_dist(&cone.transform, cone.pos, pos, |p| cone_dist(cone, p)), cone.color)
+        },
+    }
+}
+
+// distance from pos to a single object, ignoring the rest of the scene
+pub fn object_dist(object_handeler : &ObjectHandeler, id : ObjectId, pos : [f32; 3]) -> f32 {
+    object_dist_color(object_handeler, id, pos).0
+}
+
+// the object whose surface is closest to pos, used to find out what a ray hit
+pub fn closest_object(object_handeler : &ObjectHandeler, pos : [f32; 3]) -> Option<ObjectId> {
+    object_handeler.object_ids()
+        .map(|id| (id, object_dist(object_handeler, id, pos).abs()))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+// surface normal from the gradient of the distance field, dst is the distance at pos
+pub fn approx_norm(object_handeler : &ObjectHandeler, pos : [f32; 3], dst : f32) -> [f32; 3] {
+    let dx = dst - min_dist(object_handeler, vec_add(pos, [0.0001, 0.0, 0.0], 1.0)).0;
+    let dy = dst - min_dist(object_handeler, vec_add(pos, [0.0, 0.0001, 0.0], 1.0)).0;
+    let dz = dst - min_dist(object_handeler, vec_add(pos, [0.0, 0.0, 0.0001], 1.0)).0;
+
+    normalize([dx, dy, dz])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_handler::SceneObject;
+
+    fn assert_close(actual : f32, expected : f32) {
+        assert!((actual - expected).abs() < 1e-5, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn sphere_distances() {
+        let sphere = Sphere::new([1.0, 2.0, 3.0], [1.0; 3], 1.0);
+
+        assert_close(sphere_dist(&sphere, [1.0, 2.0, 6.0]), 2.0);
+        assert_close(sphere_dist(&sphere, [1.0, 3.0, 3.0]), 0.0);
+        assert_close(sphere_dist(&sphere, [1.0, 2.0, 3.0]), -1.0);
+    }
+
+    #[test]
+    fn cube_distances() {
+        // dim is half of the size
+        let cube = Cube::new([0.0; 3], [1.0, 2.0, 3.0], [1.0; 3]);
+
+        assert_close(cube_dist(&cube, [3.0, 0.0, 0.0]), 2.0);
+        assert_close(cube_dist(&cube, [2.0, 3.0, 0.0]), f32::sqrt(2.0));
+        assert_close(cube_dist(&cube, [0.0, 0.0, 0.0]), -1.0);
+        assert_close(cube_dist(&cube, [0.0, 0.0, 2.5]), -0.5);
+    }
+
+    #[test]
+    fn triangle_distances() {
+        let triangle = Triangle::new([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0; 3]);
+
+        // above the face, to an edge and to a corner
+        assert_close(triangle_dist(&triangle, [0.25, 0.25, 2.0]), 2.0);
+        assert_close(triangle_dist(&triangle, [0.5, -1.0, 0.0]), 1.0);
+        assert_close(triangle_dist(&triangle, [2.0, 0.0, 0.0]), 1.0);
+        assert_close(triangle_dist(&triangle, [-1.0, -1.0, 0.0]), f32::sqrt(2.0));
+    }
+
+    #[test]
+    fn smooth_minimum() {
+        // further apart than the blend radius it is the minimum
+        let (color, dst) = blend(1.0, 3.0, [1.0, 0.0, 0.0], [0.0, 0.0, 1.0], 1.0);
+        assert_close(dst, 1.0);
+        assert_eq!(color, [1.0, 0.0, 0.0]);
+
+        // at the same distance it is a quarter of the radius closer, with half of each color
+        let (color, dst) = blend(1.0, 1.0, [1.0, 0.0, 0.0], [0.0, 0.0, 1.0], 1.0);
+        assert_close(dst, 0.75);
+        assert_eq!(color, [0.5, 0.0, 0.5]);
+    }
+
+    // a unit sphere at the origin and a unit sphere 1.5 further along x that is combined with it
+    fn two_spheres(operation : Operation) -> ObjectHandeler {
+        let mut object_handeler = ObjectHandeler::new();
+        let mut ground = object_handeler.get_ground();
+        ground.enabled = false;
+        object_handeler.set_ground(ground);
+
+        let mut second = Sphere::new([1.5, 0.0, 0.0], [0.0, 0.0, 1.0], 1.0);
+        second.combine = Combine::new(operation, 0.5);
+        object_handeler.add_object(SceneObject::Sphere(Sphere::new([0.0; 3], [1.0, 0.0, 0.0], 1.0)));
+        object_handeler.add_object(SceneObject::Sphere(second));
+        object_handeler
+    }
+
+    #[test]
+    fn min_dist_of_every_operation() {
+        // (operation, distance outside both spheres, distance inside both spheres)
+        let expected = [
+            (Operation::Union, 2.0, -0.25),
+            (Operation::Subtraction, 2.0, 0.25),
+            (Operation::Intersection, 3.5, -0.25),
+            (Operation::SmoothUnion, 2.0, -0.375),
+            (Operation::SmoothSubtraction, 2.0, 0.25),
+            (Operation::SmoothIntersection, 3.5, -0.125),
+        ];
+
+        for (operation, outside, inside) in expected {
+            let mut object_handeler = two_spheres(operation);
+            assert_close(min_dist(&object_handeler, [-3.0, 0.0, 0.0]).0, outside);
+            assert_close(min_dist(&object_handeler, [0.75, 0.0, 0.0]).0, inside);
+
+            // outside of the objects the bvh gives the same distance as going through the objects one at a time,
+            // inside it skips the rest of the tree once the distance is negative
+            object_handeler.update_bvh();
+            assert!(object_handeler.get_bvh().is_some());
+            assert_close(min_dist(&object_handeler, [-3.0, 0.0, 0.0]).0, outside);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// the shader sources, embedded in the binary so that it runs from any directory
+// a directory with the shaders can be given instead, then they are read from there and reloaded when they are saved
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::shader_handler::ShaderError;
+
+const VERTEX : &str = "vertex.glsl";
+const FRAGMENT : &str = "fragment.glsl";
+
+// every file in shaders/, the names are the ones used by #include
+const EMBEDDED : [(&str, &str); 7] = [
+    ("vertex.glsl", include_str!("../shaders/vertex.glsl")),
+    ("fragment.glsl", include_str!("../shaders/fragment.glsl")),
+    ("sdf.glsl", include_str!("../shaders/sdf.glsl")),
+    ("lighting.glsl", include_str!("../shaders/lighting.glsl")),
+    ("texture.glsl", include_str!("../shaders/texture.glsl")),
+    ("sky.glsl", include_str!("../shaders/sky.glsl")),
+    ("ground.glsl", include_str!("../shaders/ground.glsl")),
+];
+
+// the shaders/ directory of the repository in debug builds, so that `cargo run` reloads the shaders when they are edited
+pub fn development_dir() -> Option<PathBuf> {
+    if cfg!(debug_assertions) {
+        let dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders"));
+        dir.is_dir().then_some(dir)
+    } else {
+        None
+    }
+}
+
+pub struct ShaderFiles {
+    // None uses the embedded sources
+    dir : Option<PathBuf>,
+    // the files that were read by the last load, and when they were changed then
+    modified : Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ShaderFiles {
+    pub fn new(dir : Option<PathBuf>) -> ShaderFiles {
+        ShaderFiles { dir, modified : Vec::new() }
+    }
+
+    // whether a file that was read by the last load has changed since, the embedded sources never change
+    pub fn changed(&self) -> bool {
+        self.modified.iter().any(|(path, modified)| modified_time(path) != *modified)
+    }
+
+    // the vertex and fragment source with the includes replaced by the included files
+    pub fn load(&mut self) -> Result<(String, String), ShaderError> {
+        self.modified.clear();
+
+        let mut read = |name : &str| self.read(name);
+        let vertex_source = preprocess(VERTEX, &mut read, &mut Vec::new(), &mut Vec::new())?;
+        let fragment_source = preprocess(FRAGMENT, &mut read, &mut Vec::new(), &mut Vec::new())?;
+        Ok((vertex_source, fragment_source))
+    }
+
+    fn read(&mut self, name : &str) -> Result<String, ShaderError> {
+        match &self.dir {
+            Some(dir) => {
+                let path = dir.join(name);
+                // the time is taken before reading, so a change while reading is found by the next check
+                self.modified.push((path.clone(), modified_time(&path)));
+                fs::read_to_string(&path).map_err(|err| ShaderError::new(format!("Could not read {}: {}", path.display(), err)))
+            },
+            None => EMBEDDED.iter()
+                .find(|(embedded_name, _)| *embedded_name == name)
+                .map(|(_, source)| source.to_string())
+                .ok_or_else(|| ShaderError::new(format!("There is no shader named {}", name))),
+        }
+    }
+}
+
+// replaces the #include "file" lines with the file, every file is only included once so libraries can include what they need.
+// `included` has every file that was included so far and `including` the files whose includes are being replaced,
+// a file that includes one of those would include itself
+fn preprocess(name : &str, read : &mut impl FnMut(&str) -> Result<String, ShaderError>, included : &mut Vec<String>, including : &mut Vec<String>) -> Result<String, ShaderError> {
+    included.push(name.to_string());
+    including.push(name.to_string());
+    let source = read(name)?;
+
+    let mut result = String::with_capacity(source.len());
+    for (i, line) in source.lines().enumerate() {
+        let Some(rest) = line.trim_start().strip_prefix("#include") else {
+            result.push_str(line);
+            result.push('\n');
+            continue;
+        };
+
+        let file = rest.trim().strip_prefix('"').and_then(|rest| rest.strip_suffix('"'))
+            .ok_or_else(|| ShaderError::new(format!("{} line {}: expected #include \"file.glsl\"", name, i + 1)))?;
+
+        if including.iter().any(|including| including == file) {
+            return Err(ShaderError::new(format!("{} line {}: including {} here would include it in itself", name, i + 1, file)));
+        }
+
+        if !included.iter().any(|included| included == file) {
+            let file_source = preprocess(file, read, included, including)?;
+            result.push_str(&file_source);
+        }
+    }
+
+    including.pop();
+    Ok(result)
+}
+
+fn modified_time(path : &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preprocess_files(name : &str, files : &[(&str, &str)]) -> Result<String, ShaderError> {
+        let mut read = |name : &str| files.iter()
+            .find(|(file, _)| *file == name)
+            .map(|(_, source)| source.to_string())
+            .ok_or_else(|| ShaderError::new(format!("There is no shader named {}", name)));
+        preprocess(name, &mut read, &mut Vec::new(), &mut Vec::new())
+    }
+
+    fn assert_error(result : Result<String, ShaderError>, message : &str) {
+        match result {
+            Ok(source) => panic!("expected \"{}\", got the source {}", message, source),
+            Err(err) => assert_eq!(err.message, message),
+        }
+    }
+
+    #[test]
+    fn files_are_included_once() {
+        let files = [
+            ("main.glsl", "#include \"a.glsl\"\n  #include \"b.glsl\"\nmain\n"),
+            ("a.glsl", "#include \"common.glsl\"\na\n"),
+            ("b.glsl", "#include \"common.glsl\"\n#include \"a.glsl\"\nb\n"),
+            ("common.glsl", "common\n"),
+        ];
+        assert_eq!(preprocess_files("main.glsl", &files).unwrap(), "common\na\nb\nmain\n");
+    }
+
+    #[test]
+    fn malformed_includes() {
+        for line in ["#include a.glsl", "#include \"a.glsl", "#include <a.glsl>", "#include"] {
+            let files = [("main.glsl", format!("first\n{}\n", line)), ("a.glsl", String::new())];
+            let files = files.iter().map(|(name, source)| (*name, source.as_str())).collect::<Vec<_>>();
+            assert_error(preprocess_files("main.glsl", &files), "main.glsl line 2: expected #include \"file.glsl\"");
+        }
+    }
+
+    #[test]
+    fn unknown_files() {
+        let files = [("main.glsl", "#include \"missing.glsl\"\n")];
+        assert_error(preprocess_files("main.glsl", &files), "There is no shader named missing.glsl");
+    }
+
+    #[test]
+    fn including_an_ancestor() {
+        let files = [
+            ("main.glsl", "#include \"a.glsl\"\n"),
+            ("a.glsl", "#include \"b.glsl\"\n"),
+            ("b.glsl", "b\n#include \"main.glsl\"\n"),
+        ];
+        assert_error(preprocess_files("main.glsl", &files), "b.glsl line 2: including main.glsl here would include it in itself");
+
+        let files = [("main.glsl", "#include \"main.glsl\"\n")];
+        assert_error(preprocess_files("main.glsl", &files), "main.glsl line 1: including main.glsl here would include it in itself");
+    }
+
+    #[test]
+    fn embedded_shaders_preprocess() {
+        let mut files = ShaderFiles::new(None);
+        let (vertex_source, fragment_source) = files.load().unwrap();
+        assert!(!vertex_source.contains("#include"));
+        assert!(!fragment_source.contains("#include"));
+    }
+}
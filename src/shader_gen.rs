@@ -0,0 +1,131 @@
+// glsl generated from the scene, spliced into fragment.glsl in place of the generic functions
+// the generated sceneDist has every object unrolled with its type, index and operation as constants, so it is only correct for objects in that order
+
+use std::fmt;
+
+use crate::object_handler::ObjectHandeler;
+use crate::texture::Pattern;
+
+// scenes with more objects use the generic sceneDist, where the bvh skips the objects that are far away
+pub const MAX_GENERATED_OBJECTS : usize = 32;
+
+#[derive(Debug)]
+pub enum SpliceError {
+    NoMarkers { function : &'static str },
+}
+
+impl fmt::Display for SpliceError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpliceError::NoMarkers { function } => write!(f, "the fragment shader has no // {} begin and // {} end lines", function, function),
+        }
+    }
+}
+
+// replaces the code between the `// <function> begin` and `// <function> end` lines of the source
+pub fn splice(source : &str, function : &'static str, code : &str) -> Result<String, SpliceError> {
+    let begin_marker = format!("// {} begin", function);
+    let end_marker = format!("// {} end", function);
+
+    let begin = source.find(&begin_marker).ok_or(SpliceError::NoMarkers { function })? + begin_marker.len();
+    let end = source[begin..].find(&end_marker).ok_or(SpliceError::NoMarkers { function })? + begin;
+
+    Ok(format!("{}\n{}{}", &source[..begin], code, &source[end..]))
+}
+
+// a sceneDist with the objects of the scene in list order, None when the scene has too many objects.
+// the values of the objects are read from the buffers like in the generic sceneDist, so the code only changes
+// when objects are added, removed or moved in the list, or when their operation or whether they have a texture changes
+pub fn scene_dist(object_handeler : &ObjectHandeler) -> Option<String> {
+    if object_handeler.object_ids().count() > MAX_GENERATED_OBJECTS {
+        return None;
+    }
+
+    let mut code = String::from("vec4 sceneDist(vec3 pos, vec3 norm, out Material material) {\n    float dst = 10000000.0;\n    vec3 clr = vec3(0.0);\n    material = NO_MATERIAL;\n");
+
+    for (i, id) in object_handeler.object_ids().enumerate() {
+        let object = object_handeler.get_object(id);
+        let (kind, index) = (id.kind as i32, id.index);
+        code.push_str(&format!("    vec4 d{} = objectDistColor({}, {}, pos);\n", i, kind, index));
+
+        // like in addObject the texture is only read where a ray hits
+        let color = if object.texture().pattern == Pattern::None {
+            format!("d{}.yzw", i)
+        } else {
+            format!("norm != vec3(0.0) ? applyTexture(d{}.yzw, getObjectTexture({}, {}), pos, norm) : d{}.yzw", i, kind, index, i)
+        };
+        code.push_str(&format!("    combine(vec2({}, getCombine({}, {}).y), d{}.x, {}, getMaterial({}, {}), dst, clr, material);\n",
+            float(object.combine().operation as i32 as f32), kind, index, i, color, kind, index));
+    }
+
+    code.push_str("    return vec4(dst, clr);\n}\n");
+    Some(code)
+}
+
+// debug formatting always has a decimal point or an exponent, so the numbers are floats in glsl
+pub fn float(x : f32) -> String {
+    format!("{:?}", x)
+}
+
+pub fn vec3(v : [f32; 3]) -> String {
+    format!("vec3({}, {}, {})", float(v[0]), float(v[1]), float(v[2]))
+}
+
+pub fn vec4(v : [f32; 4]) -> String {
+    format!("vec4({}, {}, {}, {})", float(v[0]), float(v[1]), float(v[2]), float(v[3]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_handler::{ObjectId, SceneObject};
+    use crate::shapes::{Combine, Operation, Sphere};
+
+    fn spheres(count : usize) -> (ObjectHandeler, Vec<ObjectId>) {
+        let mut object_handeler = ObjectHandeler::new();
+        let ids = (0..count).map(|i| object_handeler.add_object(SceneObject::Sphere(Sphere::new([i as f32, 0.0, 0.0], [1.0; 3], 0.5)))).collect();
+        (object_handeler, ids)
+    }
+
+    #[test]
+    fn splice_replaces_between_the_markers() {
+        let source = "before\n// sceneDist begin\nold\n// sceneDist end\nafter\n";
+        let spliced = splice(source, "sceneDist", "new\n").unwrap();
+        assert_eq!(spliced, "before\n// sceneDist begin\nnew\n// sceneDist end\nafter\n");
+    }
+
+    #[test]
+    fn splice_without_markers() {
+        let result = splice("// sceneDist begin\nold\n", "sceneDist", "new\n");
+        assert!(matches!(result, Err(SpliceError::NoMarkers { function : "sceneDist" })));
+        let result = splice("old\n// sceneDist end\n", "sceneDist", "new\n");
+        assert!(matches!(result, Err(SpliceError::NoMarkers { function : "sceneDist" })));
+    }
+
+    #[test]
+    fn too_many_objects_are_not_generated() {
+        assert!(scene_dist(&spheres(MAX_GENERATED_OBJECTS).0).is_some());
+        assert!(scene_dist(&spheres(MAX_GENERATED_OBJECTS + 1).0).is_none());
+    }
+
+    #[test]
+    fn code_only_changes_with_the_list_of_objects() {
+        let (mut object_handeler, ids) = spheres(2);
+        let code = scene_dist(&object_handeler).unwrap();
+
+        // moving an object or changing its values is read from the buffers
+        object_handeler.set_object_pos(ids[0], [5.0, 1.0, 2.0]);
+        let mut sphere = Sphere::new([0.0; 3], [0.0, 1.0, 0.0], 2.0);
+        sphere.combine = Combine::new(Operation::Union, 0.8);
+        object_handeler.set_object(ids[1], SceneObject::Sphere(sphere));
+        assert_eq!(scene_dist(&object_handeler).unwrap(), code);
+
+        sphere.combine = Combine::new(Operation::Subtraction, 0.8);
+        object_handeler.set_object(ids[1], SceneObject::Sphere(sphere));
+        let subtracted = scene_dist(&object_handeler).unwrap();
+        assert_ne!(subtracted, code);
+
+        object_handeler.move_object(ids[1], 0);
+        assert_ne!(scene_dist(&object_handeler).unwrap(), subtracted);
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use glium::backend::Facade;
+use glium::program::ShaderType;
+use glium::{Program, ProgramCreationError};
+
+use crate::object_handler::ObjectHandeler;
+use crate::shader_gen;
+use crate::shader_files::ShaderFiles;
+
+// how long the generated code has to stay the same before it is compiled, so that adding or reordering several objects does not compile a shader for each step
+const SPECIALIZE_DELAY : Duration = Duration::from_millis(500);
+
+// how often the shader files are checked for changes when nothing else redraws the window
+pub const POLL_INTERVAL : Duration = Duration::from_millis(250);
+
+// drawn when the shaders have never compiled, so that the window still opens and shows the error
+const FALLBACK_VERTEX : &str = "#version 140\nin vec2 position;\nvoid main() { gl_Position = vec4(position, 0.0, 1.0); }\n";
+const FALLBACK_FRAGMENT : &str = "#version 140\nout vec4 f_color;\nvoid main() { f_color = vec4(0.6196, 0.6118, 0.6549, 1.0); }\n";
+
+// a shader that could not be read or compiled
+#[derive(Clone, Debug)]
+pub struct ShaderError {
+    pub message : String,
+    // the compiler log, empty if the shader was not compiled
+    pub log : String,
+    // the source that the line numbers in the log refer to, with the includes replaced by the files
+    pub source : String,
+    // the shader generated for the scene did not compile, the generic one is used instead of the last one that compiled
+    pub generated : bool,
+}
+
+impl ShaderError {
+    pub fn new(message : String) -> ShaderError {
+        ShaderError { message, log : String::new(), source : String::new(), generated : false }
+    }
+
+    fn from_program(err : ProgramCreationError, vertex_source : &str, fragment_source : &str) -> ShaderError {
+        match &err {
+            ProgramCreationError::CompilationError(log, shader_type) => {
+                let (message, source) = match shader_type {
+                    ShaderType::Vertex => ("Could not compile the vertex shader", vertex_source),
+                    _ => ("Could not compile the fragment shader", fragment_source),
+                };
+                ShaderError { message : message.to_string(), log : log.clone(), source : source.to_string(), generated : false }
+            },
+            ProgramCreationError::LinkingError(log) => ShaderError { message : String::from("Could not link the shaders"), log : log.clone(), source : String::new(), generated : false },
+            _ => ShaderError::new(err.to_string()),
+        }
+    }
+
+    // the line of the source that a line of the log is about, the drivers write them as 0:12(5) (mesa), 0(12) (nvidia) or ERROR: 0:12: (amd and intel)
+    pub fn source_line(&self, log_line : &str) -> Option<(usize, &str)> {
+        let log_line = log_line.trim_start_matches("ERROR: ").trim_start_matches("WARNING: ");
+        let rest = log_line.strip_prefix("0:").or_else(|| log_line.strip_prefix("0("))?;
+        let digits : String = rest.chars().take_while(char::is_ascii_digit).collect();
+
+        let number : usize = digits.parse().ok()?;
+        let line = self.source.lines().nth(number.checked_sub(1)?)?;
+        Some((number, line))
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.log.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.message, self.log)
+        }
+    }
+}
+
+// a program generated for one exact scene, see shader_gen
+struct Specialized {
+    scene_dist : String,
+    // None if it did not compile, then the generic program is used for this scene
+    program : Option<Program>,
+}
+
+// keeps the generic program, which works for every scene, and a program generated for the current list of objects.
+// While the list changes the generic program is drawn with, moving objects or changing their values keeps the generated one. The shader files are read again when they change,
+// and if they do not compile the last program that did is kept
+pub struct ShaderHandler {
+    files : ShaderFiles,
+    // the sources of the generic program
+    vertex_source : String,
+    fragment_source : String,
+    // the compiled node graph, None uses the objectsDist in the fragment shader
+    graph_min_dist : Option<String>,
+    generic : Program,
+    specialized : Option<Specialized>,
+    // the specialized program was made from the current scene
+    up_to_date : bool,
+    // the generated sceneDist of the scene, and since when the scene has looked like this
+    pending : Option<(String, Instant)>,
+    error : Option<ShaderError>,
+    // why the last shader generated for the scene did not compile
+    generated_error : Option<ShaderError>,
+}
+
+impl ShaderHandler {
+    // uses the shaders in dir if it is given, otherwise the ones embedded in the binary
+    pub fn new<F : ?Sized + Facade>(facade : &F, dir : Option<PathBuf>) -> ShaderHandler {
+        let fallback = Program::from_source(facade, FALLBACK_VERTEX, FALLBACK_FRAGMENT, None).expect("The fallback shader should always compile");
+
+        let mut shader_handler = ShaderHandler {
+            files : ShaderFiles::new(dir),
+            vertex_source : String::new(),
+            fragment_source : String::new(),
+            graph_min_dist : None,
+            generic : fallback,
+            specialized : None,
+            up_to_date : false,
+            pending : None,
+            error : None,
+            generated_error : None,
+        };
+        shader_handler.reload(facade);
+        shader_handler
+    }
+
+    pub fn get_program(&self) -> &Program {
+        match &self.specialized {
+            Some(Specialized { program : Some(program), .. }) if self.up_to_date => program,
+            _ => &self.generic,
+        }
+    }
+
+    // why the shader files could not be used, the last program that compiled is drawn with until they are fixed.
+    // Otherwise why the shader generated for the scene could not be used, the generic program is drawn with then
+    pub fn get_error(&self) -> Option<&ShaderError> {
+        self.error.as_ref().or(self.generated_error.as_ref())
+    }
+
+    // whether drawing would change the program, because a shader file has changed or a shader can be generated for the scene
+    pub fn needs_redraw(&self) -> bool {
+        self.files.changed() || self.pending.as_ref().is_some_and(|(_, since)| since.elapsed() >= SPECIALIZE_DELAY)
+    }
+
+    // reads and compiles the shader files if they have changed, returns true if they were read
+    pub fn reload_if_changed<F : ?Sized + Facade>(&mut self, facade : &F) -> bool {
+        if !self.files.changed() {
+            return false;
+        }
+        self.reload(facade);
+        true
+    }
+
+    fn reload<F : ?Sized + Facade>(&mut self, facade : &F) {
+        let result = self.files.load().and_then(|(vertex_source, fragment_source)| {
+            let program = Self::build(facade, &vertex_source, &fragment_source, self.graph_min_dist.as_deref(), None)?;
+            Ok((vertex_source, fragment_source, program))
+        });
+
+        match result {
+            Ok((vertex_source, fragment_source, program)) => {
+                self.vertex_source = vertex_source;
+                self.fragment_source = fragment_source;
+                self.generic = program;
+                self.error = None;
+
+                // the specialized program has the old shader
+                self.specialized = None;
+                self.generated_error = None;
+                self.up_to_date = false;
+            },
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    // builds the shaders with the node graph as objectsDist, the old ones are kept if it does not compile
+    pub fn set_graph_min_dist<F : ?Sized + Facade>(&mut self, facade : &F, min_dist : String) -> Result<(), String> {
+        let graph_min_dist = Some(min_dist);
+        self.generic = Self::build(facade, &self.vertex_source, &self.fragment_source, graph_min_dist.as_deref(), None).map_err(|err| err.to_string())?;
+        self.graph_min_dist = graph_min_dist;
+
+        // the specialized program has the old graph
+        self.specialized = None;
+        self.generated_error = None;
+        self.up_to_date = false;
+        Ok(())
+    }
+
+    // generates a program for the list of objects once it has stopped changing, called every frame. Returns true if a program was compiled
+    pub fn update<F : ?Sized + Facade>(&mut self, facade : &F, object_handeler : &ObjectHandeler) -> bool {
+        let Some(scene_dist) = shader_gen::scene_dist(object_handeler) else {
+            self.up_to_date = false;
+            self.pending = None;
+            return false;
+        };
+
+        self.up_to_date = self.specialized.as_ref().is_some_and(|specialized| specialized.scene_dist == scene_dist);
+        if self.up_to_date {
+            self.pending = None;
+            return false;
+        }
+
+        match &self.pending {
+            Some((pending, since)) if *pending == scene_dist => {
+                if since.elapsed() < SPECIALIZE_DELAY {
+                    return false;
+                }
+
+                // the generated code should always compile, if it does not the generic program still draws the scene correctly.
+                // glium can only compile on this thread and has no parallel shader compile, so the window stalls for as long as the driver takes,
+                // which is why this only happens once the list of objects has stopped changing
+                let program = match Self::build(facade, &self.vertex_source, &self.fragment_source, self.graph_min_dist.as_deref(), Some(&scene_dist)) {
+                    Ok(program) => {
+                        self.generated_error = None;
+                        Some(program)
+                    },
+                    Err(err) => {
+                        let message = format!("{}, it was generated for the scene", err.message);
+                        self.generated_error = Some(ShaderError { message, generated : true, ..err });
+                        None
+                    }
+                };
+
+                self.specialized = Some(Specialized { scene_dist, program });
+                self.up_to_date = true;
+                self.pending = None;
+                true
+            },
+            _ => {
+                self.pending = Some((scene_dist, Instant::now()));
+                false
+            }
+        }
+    }
+
+    fn build<F : ?Sized + Facade>(facade : &F, vertex_source : &str, fragment_source : &str, min_dist : Option<&str>, scene_dist : Option<&str>) -> Result<Program, ShaderError> {
+        let mut fragment_source = fragment_source.to_string();
+        if let Some(min_dist) = min_dist {
+            fragment_source = shader_gen::splice(&fragment_source, "objectsDist", min_dist).map_err(|err| ShaderError::new(err.to_string()))?;
+        }
+        if let Some(scene_dist) = scene_dist {
+            fragment_source = shader_gen::splice(&fragment_source, "sceneDist", scene_dist).map_err(|err| ShaderError::new(err.to_string()))?;
+        }
+
+        Program::from_source(facade, vertex_source, &fragment_source, None).map_err(|err| ShaderError::from_program(err, vertex_source, &fragment_source))
+    }
+}
@@ -1,54 +1,318 @@
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+use crate::texture::Texture;
+use crate::vec_util::{rotate_pos, vec_add};
+
+// rotation and scale of an object around its pos, the object is scaled first and then rotated.
+// Triangles do not have one, they are placed by their vertices
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    // quaternion, (w, x, y, z) like in vec_util
+    pub rotation : [f32;4],
+    pub scale : [f32;3]
+}
+
+impl Transform {
+    pub const IDENTITY : Transform = Transform { rotation : [1.0, 0.0, 0.0, 0.0], scale : [1.0; 3] };
+
+    // where a point in the untransformed object ends up, center is the pos of the object
+    pub fn apply(&self, center : [f32; 3], pos : [f32; 3]) -> [f32; 3] {
+        let local = vec_add(pos, center, -1.0);
+        let scaled = [local[0] * self.scale[0], local[1] * self.scale[1], local[2] * self.scale[2]];
+        vec_add(rotate_pos(scaled, self.rotation), center, 1.0)
+    }
+
+    // the opposite of apply, the distance function of the untransformed object can be used at the returned point
+    pub fn unapply(&self, center : [f32; 3], pos : [f32; 3]) -> [f32; 3] {
+        let q = self.rotation;
+        let local = rotate_pos(vec_add(pos, center, -1.0), [q[0], -q[1], -q[2], -q[3]]);
+        let unscaled = [local[0] / self.scale[0], local[1] / self.scale[1], local[2] / self.scale[2]];
+        vec_add(unscaled, center, 1.0)
+    }
+
+    // distances at the unapplied point are multiplied with this, with a non uniform scale the result is
+    // less than the real distance but never more, so marching can not step through the object
+    pub fn dist_scale(&self) -> f32 {
+        f32::min(self.scale[0].abs(), f32::min(self.scale[1].abs(), self.scale[2].abs()))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+// the numbers are the ones used in the shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operation {
+    Union = 0,
+    Subtraction = 1,
+    Intersection = 2,
+    SmoothUnion = 3,
+    SmoothSubtraction = 4,
+    SmoothIntersection = 5,
+}
+
+impl Operation {
+    pub const ALL : [Operation; 6] = [
+        Operation::Union, Operation::Subtraction, Operation::Intersection,
+        Operation::SmoothUnion, Operation::SmoothSubtraction, Operation::SmoothIntersection,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Union => "Union",
+            Operation::Subtraction => "Subtraction",
+            Operation::Intersection => "Intersection",
+            Operation::SmoothUnion => "Smooth union",
+            Operation::SmoothSubtraction => "Smooth subtraction",
+            Operation::SmoothIntersection => "Smooth intersection",
+        }
+    }
+
+    pub fn is_smooth(&self) -> bool {
+        matches!(self, Operation::SmoothUnion | Operation::SmoothSubtraction | Operation::SmoothIntersection)
+    }
+}
+
+// how an object is combined with the objects before it. The objects are combined in list order,
+// which is set in the side panel, see ObjectHandeler::object_ids
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Combine {
+    pub operation : Operation,
+    // how far the smooth operations blend the surfaces, not used by the others
+    pub blend_radius : f32
+}
+
+impl Combine {
+    pub const UNION : Combine = Combine { operation : Operation::Union, blend_radius : DEFAULT_BLEND_RADIUS };
+
+    pub fn new(operation : Operation, blend_radius : f32) -> Self {
+        Combine { operation, blend_radius }
+    }
+
+    // unions give the same result in any order, and smooth unions almost the same, so these objects can be looked up
+    // through the bvh instead of one at a time in list order
+    pub fn can_reorder(&self) -> bool {
+        matches!(self.operation, Operation::Union | Operation::SmoothUnion)
+    }
+}
+
+impl Default for Combine {
+    fn default() -> Self {
+        Combine::UNION
+    }
+}
+
+pub const DEFAULT_BLEND_RADIUS : f32 = 0.5;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Sphere {
     pub pos : [f32;3],
     pub color : [f32;3],
-    pub radius : f32
+    pub radius : f32,
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Triangle {
     pub v1 : [f32;3],
     pub v2 : [f32;3],
     pub v3 : [f32;3],
-    pub color : [f32;3]
+    pub color : [f32;3],
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Cube{
     pub pos : [f32;3],
     pub dim : [f32;3],
-    pub color : [f32;3]
+    pub color : [f32;3],
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MengerSponge{
     pub pos : [f32;3],
     pub iterations : f32,
-    pub color : [f32;3]
+    pub color : [f32;3],
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
+}
+
+// a ring around the y axis, radius is the distance from pos to the middle of the ring
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Torus{
+    pub pos : [f32;3],
+    pub radius : f32,
+    pub thickness : f32,
+    pub color : [f32;3],
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
+}
+
+// the heights are from pos to the ends along the y axis, like dim in Cube is half the size
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Capsule{
+    pub pos : [f32;3],
+    pub half_height : f32,
+    pub radius : f32,
+    pub color : [f32;3],
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cylinder{
+    pub pos : [f32;3],
+    pub half_height : f32,
+    pub radius : f32,
+    pub color : [f32;3],
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cone{
+    pub pos : [f32;3],
+    pub half_height : f32,
+    pub bottom_radius : f32,
+    pub top_radius : f32,
+    pub color : [f32;3],
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture
+}
+
+// triangles that are moved, rotated, scaled and colored as one object. The triangles are stored in the object handeler,
+// first_triangle and num_triangles is the part of ObjectHandeler::get_mesh_triangles that belongs to this mesh
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Mesh{
+    pub pos : [f32;3],
+    pub color : [f32;3],
+    pub first_triangle : usize,
+    pub num_triangles : usize,
+    #[serde(default)]
+    pub transform : Transform,
+    #[serde(default)]
+    pub combine : Combine,
+    #[serde(default)]
+    pub material : usize,
+    #[serde(default)]
+    pub texture : Texture,
+    // the box around the triangles relative to pos, the object handeler sets it when the mesh is added
+    #[serde(skip)]
+    pub bounds_min : [f32;3],
+    #[serde(skip)]
+    pub bounds_max : [f32;3]
 }
 
 impl Sphere {
     pub fn new(pos : [f32; 3], color : [f32; 3], radius : f32) -> Self {
-        Sphere { pos, color, radius}
+        Sphere { pos, color, radius, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE }
     }
 
 }
 
 impl Triangle {
     pub fn new(v1 : [f32; 3], v2 : [f32; 3], v3 : [f32; 3], color : [f32; 3]) -> Self {
-        Triangle { v1, v2, v3, color}
+        Triangle { v1, v2, v3, color, combine : Combine::UNION, material : 0, texture : Texture::NONE }
     }
 }
 
 impl Cube {
     pub fn new(pos : [f32; 3], dim : [f32; 3],  color : [f32; 3]) -> Self {
-        Cube { pos, dim, color}
+        Cube { pos, dim, color, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE }
     }
 }
 
 impl MengerSponge {
     pub fn new(pos : [f32; 3], iterations : f32,  color : [f32; 3]) -> Self {
-        MengerSponge { pos, iterations, color}
+        MengerSponge { pos, iterations, color, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE }
+    }
+}
+
+impl Torus {
+    pub fn new(pos : [f32; 3], radius : f32, thickness : f32, color : [f32; 3]) -> Self {
+        Torus { pos, radius, thickness, color, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE }
+    }
+}
+
+impl Capsule {
+    pub fn new(pos : [f32; 3], half_height : f32, radius : f32, color : [f32; 3]) -> Self {
+        Capsule { pos, half_height, radius, color, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE }
+    }
+}
+
+impl Cylinder {
+    pub fn new(pos : [f32; 3], half_height : f32, radius : f32, color : [f32; 3]) -> Self {
+        Cylinder { pos, half_height, radius, color, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE }
+    }
+}
+
+impl Cone {
+    pub fn new(pos : [f32; 3], half_height : f32, bottom_radius : f32, top_radius : f32, color : [f32; 3]) -> Self {
+        Cone { pos, half_height, bottom_radius, top_radius, color, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE }
+    }
+}
+
+impl Mesh {
+    pub fn new(pos : [f32; 3], color : [f32; 3], first_triangle : usize, num_triangles : usize) -> Self {
+        Mesh { pos, color, first_triangle, num_triangles, transform : Transform::IDENTITY, combine : Combine::UNION, material : 0, texture : Texture::NONE, bounds_min : [0.0; 3], bounds_max : [0.0; 3] }
+    }
+
+    // the range of the mesh in ObjectHandeler::get_mesh_triangles
+    pub fn triangles(&self) -> std::ops::Range<usize> {
+        self.first_triangle..self.first_triangle + self.num_triangles
     }
 }
@@ -0,0 +1,328 @@
+// what is seen where rays leave the scene, the cpu version of shaders/sky.glsl
+// the sky is also seen in reflections, and lights the surfaces from every direction with the ambient light
+
+use std::f32::consts::PI;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::texture::TextureError;
+use crate::vec_util::{normalize, vec_add, vec_scale};
+
+// what the background was before there was a sky
+pub const DEFAULT_SKY_CLR : [f32; 3] = [0.6196, 0.6118, 0.6549];
+
+// bigger images are made smaller when they are loaded, so that the sky textures stay small and the ambient light is quick to compute
+pub const MAX_PANORAMA_SIZE : u32 = 2048;
+pub const MAX_CUBEMAP_SIZE : u32 = 512;
+
+// the grid of directions that the gradient is sampled at for the ambient light
+const GRADIENT_SAMPLES : (u32, u32) = (64, 32);
+
+// the numbers are the ones used in the shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkyKind {
+    Gradient = 0,
+    Cubemap = 1,
+    Equirectangular = 2,
+}
+
+impl SkyKind {
+    pub const ALL : [SkyKind; 3] = [SkyKind::Gradient, SkyKind::Cubemap, SkyKind::Equirectangular];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkyKind::Gradient => "Gradient",
+            SkyKind::Cubemap => "Cubemap",
+            SkyKind::Equirectangular => "Equirectangular",
+        }
+    }
+}
+
+// how the sky looks and how much it lights the scene, the images are in SkyImages
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sky {
+    pub kind : SkyKind,
+    // the colors of the gradient straight up, at the horizon and straight down
+    pub zenith : [f32; 3],
+    pub horizon : [f32; 3],
+    pub ground : [f32; 3],
+    // every kind of sky is multiplied with this, hdr images often need less than 1
+    pub intensity : f32,
+    // how much the sky lights the surfaces, on top of the lights
+    pub ambient : f32,
+}
+
+impl Sky {
+    // looks like the background before there was a sky
+    pub const DEFAULT : Sky = Sky {
+        kind : SkyKind::Gradient,
+        zenith : DEFAULT_SKY_CLR, horizon : DEFAULT_SKY_CLR, ground : DEFAULT_SKY_CLR,
+        intensity : 1.0, ambient : 0.0,
+    };
+
+    // the ambient light of the sky, see sky_light
+    pub fn irradiance(&self, images : &SkyImages) -> SkyIrradiance {
+        let irradiance = match self.kind {
+            SkyKind::Gradient => {
+                let (width, height) = GRADIENT_SAMPLES;
+                let gradient = Sky { intensity : 1.0, ..*self };
+                project_samples((0..width * height).map(|i| {
+                    let (dir, solid_angle) = panorama_dir(i % width, i / width, width, height);
+                    (sky_color(&gradient, images, dir), dir, solid_angle)
+                }))
+            },
+            SkyKind::Cubemap => images.cubemap_irradiance,
+            SkyKind::Equirectangular => images.panorama_irradiance,
+        };
+        irradiance.map(|c| vec_scale(c, self.intensity))
+    }
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Sky::DEFAULT
+    }
+}
+
+// an image of the sky, scene files only have the path and the pixels are read again when it is opened
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkyImage {
+    pub path : String,
+    #[serde(skip)]
+    pub width : u32,
+    #[serde(skip)]
+    pub height : u32,
+    // linear rgb, row by row from the top
+    #[serde(skip)]
+    pub pixels : Vec<[f32; 3]>,
+}
+
+impl SkyImage {
+    // reads a png or hdr image, images with a side longer than max_size are made smaller
+    pub fn load(path : &str, max_size : u32) -> Result<SkyImage, TextureError> {
+        let image = image::open(Path::new(path))?.to_rgb32f();
+        if image.width() == 0 || image.height() == 0 {
+            return Err(TextureError::Empty);
+        }
+
+        // the average of every factor x factor square, the resize of the image crate clamps hdr colors to 1
+        let factor = image.width().max(image.height()).div_ceil(max_size);
+        let (width, height) = ((image.width() / factor).max(1), (image.height() / factor).max(1));
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 3];
+                for i in 0..factor * factor {
+                    let pixel = image.get_pixel((x * factor + i % factor).min(image.width() - 1), (y * factor + i / factor).min(image.height() - 1));
+                    sum = vec_add(sum, pixel.0, 1.0);
+                }
+                pixels.push(vec_scale(sum, 1.0 / (factor * factor) as f32));
+            }
+        }
+
+        Ok(SkyImage { path : path.to_string(), width, height, pixels })
+    }
+
+    fn texel(&self, x : u32, y : u32) -> [f32; 3] {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    // bilinear between the four closest pixels like the linear filtering of the sky textures. st goes from 0 to 1 over the image,
+    // x goes around for panoramas and stops at the edge for the faces of cubemaps, y always stops at the edge
+    fn color(&self, st : [f32; 2], wrap_x : bool) -> [f32; 3] {
+        let s = st[0] * self.width as f32 - 0.5;
+        let t = st[1] * self.height as f32 - 0.5;
+        let (x, y) = (s.floor(), t.floor());
+        let (fx, fy) = (s - x, t - y);
+        let (x, y) = (x as i32, y as i32);
+
+        let column = |x : i32| match wrap_x {
+            true => x.rem_euclid(self.width as i32) as u32,
+            false => x.clamp(0, self.width as i32 - 1) as u32,
+        };
+        let row = |y : i32| y.clamp(0, self.height as i32 - 1) as u32;
+
+        let top = mix3(self.texel(column(x), row(y)), self.texel(column(x + 1), row(y)), fx);
+        let bottom = mix3(self.texel(column(x), row(y + 1)), self.texel(column(x + 1), row(y + 1)), fx);
+        mix3(top, bottom, fy)
+    }
+}
+
+// the images that the sky can show, a cubemap and a panorama can both be loaded and the kind of the sky picks one
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkyImages {
+    // the six faces in the order +x, -x, +y, -y, +z, -z, or nothing
+    pub cubemap : Vec<SkyImage>,
+    pub panorama : Option<SkyImage>,
+    // the ambient light of the images, projecting every pixel is too slow to do whenever the sky changes
+    #[serde(skip)]
+    cubemap_irradiance : SkyIrradiance,
+    #[serde(skip)]
+    panorama_irradiance : SkyIrradiance,
+}
+
+impl SkyImages {
+    pub fn new(cubemap : Vec<SkyImage>, panorama : Option<SkyImage>) -> Result<SkyImages, TextureError> {
+        if !cubemap.is_empty() {
+            let size = cubemap[0].width;
+            if cubemap.len() != 6 || cubemap.iter().any(|face| face.width != size || face.height != size) {
+                return Err(TextureError::CubemapFaces);
+            }
+        }
+
+        let cubemap_irradiance = project_samples(cubemap.iter().enumerate().flat_map(|(face, image)| {
+            (0..image.width * image.height).map(move |i| {
+                let (x, y) = (i % image.width, i / image.width);
+                let (dir, solid_angle) = cubemap_dir(face, x, y, image.width);
+                (image.texel(x, y), dir, solid_angle)
+            })
+        }));
+        let panorama_irradiance = project_samples(panorama.iter().flat_map(|image| {
+            (0..image.width * image.height).map(move |i| {
+                let (x, y) = (i % image.width, i / image.width);
+                let (dir, solid_angle) = panorama_dir(x, y, image.width, image.height);
+                (image.texel(x, y), dir, solid_angle)
+            })
+        }));
+
+        Ok(SkyImages { cubemap, panorama, cubemap_irradiance, panorama_irradiance })
+    }
+
+    // reads the images again from their paths, scene files only have the paths
+    pub fn reload(&self) -> Result<SkyImages, (String, TextureError)> {
+        let load = |image : &SkyImage, max_size| SkyImage::load(&image.path, max_size).map_err(|err| (image.path.clone(), err));
+
+        let cubemap = self.cubemap.iter().map(|face| load(face, MAX_CUBEMAP_SIZE)).collect::<Result<Vec<_>, _>>()?;
+        let panorama = self.panorama.as_ref().map(|panorama| load(panorama, MAX_PANORAMA_SIZE)).transpose()?;
+
+        let path = self.cubemap.first().map(|face| face.path.clone()).unwrap_or_default();
+        SkyImages::new(cubemap, panorama).map_err(|err| (path, err))
+    }
+
+    pub fn has(&self, kind : SkyKind) -> bool {
+        match kind {
+            SkyKind::Gradient => true,
+            SkyKind::Cubemap => !self.cubemap.is_empty(),
+            SkyKind::Equirectangular => self.panorama.is_some(),
+        }
+    }
+}
+
+// the coefficients of the spherical harmonics up to the second band, already convolved with the cosine
+// and divided by pi. from "An Efficient Representation for Irradiance Environment Maps" by Ramamoorthi and Hanrahan
+pub type SkyIrradiance = [[f32; 3]; 9];
+
+// the spherical harmonics without their constant factors, in the order of the coefficients
+fn sh_basis(dir : [f32; 3]) -> [f32; 9] {
+    let [x, y, z] = dir;
+    [1.0, y, z, x, x * y, y * z, 3.0 * z * z - 1.0, x * z, x * x - y * y]
+}
+
+// the square of the constant factor of every harmonic times how much the cosine keeps of its band
+const SH_WEIGHTS : [f32; 9] = [
+    0.282095 * 0.282095,
+    0.488603 * 0.488603 * 2.0 / 3.0, 0.488603 * 0.488603 * 2.0 / 3.0, 0.488603 * 0.488603 * 2.0 / 3.0,
+    1.092548 * 1.092548 / 4.0, 1.092548 * 1.092548 / 4.0, 0.315392 * 0.315392 / 4.0, 1.092548 * 1.092548 / 4.0, 0.546274 * 0.546274 / 4.0,
+];
+
+// samples are (color, direction, solid angle)
+fn project_samples(samples : impl Iterator<Item = ([f32; 3], [f32; 3], f32)>) -> SkyIrradiance {
+    let mut sums = [[0.0f64; 3]; 9];
+
+    for (clr, dir, solid_angle) in samples {
+        for (sum, basis) in sums.iter_mut().zip(sh_basis(dir)) {
+            for (sum, c) in sum.iter_mut().zip(clr) {
+                *sum += (c * basis * solid_angle) as f64;
+            }
+        }
+    }
+
+    std::array::from_fn(|i| sums[i].map(|sum| sum as f32 * SH_WEIGHTS[i]))
+}
+
+// the direction through the center of a pixel of a panorama and the solid angle it covers
+fn panorama_dir(x : u32, y : u32, width : u32, height : u32) -> ([f32; 3], f32) {
+    let phi = ((x as f32 + 0.5) / width as f32 - 0.5) * 2.0 * PI;
+    let theta = (y as f32 + 0.5) / height as f32 * PI;
+    let dir = [theta.sin() * phi.sin(), theta.cos(), theta.sin() * phi.cos()];
+
+    (dir, 2.0 * PI / width as f32 * PI / height as f32 * theta.sin())
+}
+
+// the direction through the center of a pixel on a face of a cubemap and the solid angle it covers, the inverse of cubemap_face
+fn cubemap_dir(face : usize, x : u32, y : u32, size : u32) -> ([f32; 3], f32) {
+    let sc = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let tc = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let dir = match face {
+        0 => [1.0, -tc, -sc],
+        1 => [-1.0, -tc, sc],
+        2 => [sc, 1.0, tc],
+        3 => [sc, -1.0, -tc],
+        4 => [sc, -tc, 1.0],
+        _ => [-sc, -tc, -1.0],
+    };
+
+    let pixel = 2.0 / size as f32;
+    (normalize(dir), pixel * pixel / f32::powf(1.0 + sc * sc + tc * tc, 1.5))
+}
+
+// "cubemapFace" in the shader, the face that dir points at and where on it, the same as in opengl
+fn cubemap_face(dir : [f32; 3]) -> (usize, [f32; 2]) {
+    let [x, y, z] = dir;
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if x > 0.0 { (0, -z, -y, ax) } else { (1, z, -y, ax) }
+    } else if ay >= az {
+        if y > 0.0 { (2, x, z, ay) } else { (3, x, -z, ay) }
+    } else if z > 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+
+    (face, [(sc / ma + 1.0) / 2.0, (tc / ma + 1.0) / 2.0])
+}
+
+// the longitude goes right from +z towards +x, the latitude goes down from +y
+fn panorama_st(dir : [f32; 3]) -> [f32; 2] {
+    [0.5 + f32::atan2(dir[0], dir[2]) / (2.0 * PI), dir[1].clamp(-1.0, 1.0).acos() / PI]
+}
+
+// "skyColor" in the shader, the color of the sky in the direction dir
+pub fn sky_color(sky : &Sky, images : &SkyImages, dir : [f32; 3]) -> [f32; 3] {
+    let clr = match sky.kind {
+        SkyKind::Cubemap => {
+            let (face, st) = cubemap_face(dir);
+            images.cubemap[face].color(st, false)
+        },
+        SkyKind::Equirectangular => images.panorama.as_ref().unwrap().color(panorama_st(dir), true),
+        SkyKind::Gradient if dir[1] >= 0.0 => mix3(sky.horizon, sky.zenith, dir[1]),
+        SkyKind::Gradient => mix3(sky.horizon, sky.ground, -dir[1]),
+    };
+    vec_scale(clr, sky.intensity)
+}
+
+// "skyLight" in the shader, the light that a surface facing norm gets from the whole sky.
+// it is divided by pi so that a sky of one color gives that color
+pub fn sky_light(irradiance : &SkyIrradiance, norm : [f32; 3]) -> [f32; 3] {
+    // summed in groups of three like the matrices in the shader
+    let basis = sh_basis(norm);
+    let mut light = [0.0; 3];
+    for (coefficients, basis) in irradiance.chunks(3).zip(basis.chunks(3)) {
+        let mut sum = [0.0; 3];
+        for (coefficient, basis) in coefficients.iter().zip(basis) {
+            sum = vec_add(sum, *coefficient, *basis);
+        }
+        light = vec_add(light, sum, 1.0);
+    }
+    light.map(|x| x.max(0.0))
+}
+
+// glsl mix
+fn mix3(a : [f32; 3], b : [f32; 3], h : f32) -> [f32; 3] {
+    vec_add(vec_scale(a, 1.0 - h), b, h)
+}
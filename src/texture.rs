@@ -0,0 +1,233 @@
+// textures of the objects, the cpu version of shaders/texture.glsl
+// distance fields have no uv coordinates, so the texture is projected along the three axes of the object
+// and the projections are blended by the normal of the surface (triplanar mapping)
+
+use std::{fmt, path::Path};
+use serde::{Deserialize, Serialize};
+
+use crate::shapes::Transform;
+use crate::vec_util::{normalize, rotate_pos, vec_add};
+
+// every image is scaled to this width and height when it is loaded, the layers of a texture array on the gpu all have the same size.
+// the uv coordinates go from 0 to 1 over the image, so scaling it does not change where it is drawn
+pub const TEXTURE_SIZE : u32 = 1024;
+// one layer of the texture array each, at 4 MiB per layer
+pub const MAX_TEXTURE_IMAGES : usize = 32;
+
+// the patterns go from this much of the color of the object to all of it
+const PATTERN_DARK : f32 = 0.3;
+const NOISE_OCTAVES : i32 = 4;
+// how much the noise bends the stripes of marble
+const MARBLE_TURBULENCE : f32 = 2.0;
+
+// the numbers are the ones used in the shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pattern {
+    None = 0,
+    Checker = 1,
+    Noise = 2,
+    Stripes = 3,
+    Marble = 4,
+    Image = 5,
+}
+
+impl Pattern {
+    pub const ALL : [Pattern; 6] = [Pattern::None, Pattern::Checker, Pattern::Noise, Pattern::Stripes, Pattern::Marble, Pattern::Image];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pattern::None => "None",
+            Pattern::Checker => "Checker",
+            Pattern::Noise => "Noise",
+            Pattern::Stripes => "Stripes",
+            Pattern::Marble => "Marble",
+            Pattern::Image => "Image",
+        }
+    }
+}
+
+// the texture of an object, it is multiplied with the color of the object
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Texture {
+    pub pattern : Pattern,
+    // the index of the image in the object handeler, only used by Pattern::Image
+    pub image : usize,
+    // how many times the texture repeats over one unit of the object
+    pub scale : f32,
+    // how sharp the edges between the three projections are, higher values blend less
+    pub sharpness : f32,
+}
+
+impl Texture {
+    pub const NONE : Texture = Texture { pattern : Pattern::None, image : 0, scale : 1.0, sharpness : 4.0 };
+}
+
+impl Default for Texture {
+    fn default() -> Self {
+        Texture::NONE
+    }
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Image(image::ImageError),
+    Empty,
+    // a cubemap needs six square faces of the same size
+    CubemapFaces,
+    TooManyImages,
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Image(err) => write!(f, "could not read image: {}", err),
+            TextureError::Empty => write!(f, "the image has no pixels"),
+            TextureError::CubemapFaces => write!(f, "the six faces of a cubemap have to be square and the same size"),
+            TextureError::TooManyImages => write!(f, "at most {} texture images can be loaded", MAX_TEXTURE_IMAGES),
+        }
+    }
+}
+
+impl From<image::ImageError> for TextureError {
+    fn from(err : image::ImageError) -> Self {
+        TextureError::Image(err)
+    }
+}
+
+// an image that objects can use as their texture, scene files only have the path and the pixels are read again when it is opened
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TextureImage {
+    pub path : String,
+    #[serde(skip)]
+    pub width : u32,
+    #[serde(skip)]
+    pub height : u32,
+    // rgba with r in the lowest byte, row by row from the top
+    #[serde(skip)]
+    pub pixels : Vec<u32>,
+}
+
+impl TextureImage {
+    // reads a png image and scales it to TEXTURE_SIZE by TEXTURE_SIZE pixels
+    pub fn load(path : &str) -> Result<TextureImage, TextureError> {
+        let image = image::open(Path::new(path))?.to_rgba8();
+        if image.width() == 0 || image.height() == 0 {
+            return Err(TextureError::Empty);
+        }
+        let image = image::imageops::resize(&image, TEXTURE_SIZE, TEXTURE_SIZE, image::imageops::FilterType::Triangle);
+
+        let pixels = image.pixels().map(|pixel| u32::from_le_bytes(pixel.0)).collect();
+        Ok(TextureImage { path : path.to_string(), width : image.width(), height : image.height(), pixels })
+    }
+
+    // the pixel at (x, y), the image repeats in both directions
+    fn texel(&self, x : i32, y : i32) -> [f32; 3] {
+        let x = x.rem_euclid(self.width as i32) as usize;
+        let y = y.rem_euclid(self.height as i32) as usize;
+        let [r, g, b, _] = self.pixels[y * self.width as usize + x].to_le_bytes();
+        [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0]
+    }
+
+    // "imageColor" in the shader, bilinear between the four closest pixels. v goes up and the rows of the image go down
+    pub fn color(&self, uv : [f32; 2]) -> [f32; 3] {
+        let s = uv[0] * self.width as f32 - 0.5;
+        let t = -uv[1] * self.height as f32 - 0.5;
+        let (x, y) = (s.floor(), t.floor());
+        let (fx, fy) = (s - x, t - y);
+        let (x, y) = (x as i32, y as i32);
+
+        let top = mix3(self.texel(x, y), self.texel(x + 1, y), fx);
+        let bottom = mix3(self.texel(x, y + 1), self.texel(x + 1, y + 1), fx);
+        mix3(top, bottom, fy)
+    }
+}
+
+// glsl mix
+fn mix(a : f32, b : f32, h : f32) -> f32 {
+    a * (1.0 - h) + b * h
+}
+
+fn mix3(a : [f32; 3], b : [f32; 3], h : f32) -> [f32; 3] {
+    [mix(a[0], b[0], h), mix(a[1], b[1], h), mix(a[2], b[2], h)]
+}
+
+// "hash" in the shader, integer math so that the cpu and the gpu get the same noise
+fn hash(x : i32, y : i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(1597334677) ^ (y as u32).wrapping_mul(3812015801);
+    h ^= h >> 16;
+    h = h.wrapping_mul(2246822519);
+    h ^= h >> 13;
+    // 24 bits fit in a float exactly
+    (h >> 8) as f32 / 16777216.0
+}
+
+// "valueNoise" in the shader, smoothly interpolated random values at the integer points
+fn value_noise(uv : [f32; 2]) -> f32 {
+    let (x, y) = (uv[0].floor(), uv[1].floor());
+    let (fx, fy) = (uv[0] - x, uv[1] - y);
+    let (ux, uy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+    let (x, y) = (x as i32, y as i32);
+
+    mix(mix(hash(x, y), hash(x + 1, y), ux), mix(hash(x, y + 1), hash(x + 1, y + 1), ux), uy)
+}
+
+// "fbm" in the shader, octaves of noise that get smaller and weaker, from 0 to 1
+fn fbm(uv : [f32; 2]) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 0.5;
+    let mut total = 0.0;
+    let mut uv = uv;
+
+    for _ in 0..NOISE_OCTAVES {
+        value += amplitude * value_noise(uv);
+        total += amplitude;
+        uv = [uv[0] * 2.0, uv[1] * 2.0];
+        amplitude *= 0.5;
+    }
+    value / total
+}
+
+// "patternValue" in the shader, from 0 to 1
+pub fn pattern_value(pattern : Pattern, uv : [f32; 2]) -> f32 {
+    match pattern {
+        Pattern::Checker => (uv[0].floor() + uv[1].floor()).rem_euclid(2.0),
+        Pattern::Noise => fbm(uv),
+        Pattern::Stripes => if uv[0] - uv[0].floor() >= 0.5 { 1.0 } else { 0.0 },
+        Pattern::Marble => 0.5 + 0.5 * f32::sin((uv[0] + MARBLE_TURBULENCE * fbm(uv)) * std::f32::consts::PI),
+        Pattern::None | Pattern::Image => 1.0,
+    }
+}
+
+// "textureSample" in the shader
+fn texture_sample(texture : &Texture, images : &[TextureImage], uv : [f32; 2]) -> [f32; 3] {
+    if texture.pattern == Pattern::Image {
+        return images[texture.image].color(uv);
+    }
+    [mix(PATTERN_DARK, 1.0, pattern_value(texture.pattern, uv)); 3]
+}
+
+// "applyTexture" in the shader, the color of an object with its texture at pos. norm is the normal of the surface,
+// center and transform are the ones of the object so that the texture moves, rotates and scales with it
+pub fn apply_texture(clr : [f32; 3], texture : &Texture, images : &[TextureImage], center : [f32; 3], transform : &Transform, pos : [f32; 3], norm : [f32; 3]) -> [f32; 3] {
+    if texture.pattern == Pattern::None {
+        return clr;
+    }
+
+    let local = vec_add(transform.unapply(center, pos), center, -1.0);
+    let p = [local[0] * texture.scale, local[1] * texture.scale, local[2] * texture.scale];
+
+    // normals are scaled the opposite way of positions
+    let q = transform.rotation;
+    let n = rotate_pos(norm, [q[0], -q[1], -q[2], -q[3]]);
+    let n = normalize([n[0] * transform.scale[0], n[1] * transform.scale[1], n[2] * transform.scale[2]]);
+
+    let w = n.map(|x| x.abs().powf(texture.sharpness));
+    let total = w[0] + w[1] + w[2];
+
+    let x = texture_sample(texture, images, [p[2], p[1]]);
+    let y = texture_sample(texture, images, [p[0], p[2]]);
+    let z = texture_sample(texture, images, [p[0], p[1]]);
+
+    std::array::from_fn(|i| clr[i] * (w[0] * x[i] + w[1] * y[i] + w[2] * z[i]) / total)
+}
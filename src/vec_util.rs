@@ -26,6 +26,27 @@ pub fn get_rotation_quaternion(axis : [f32;3], angle : f32) -> [f32; 4] {
     ]
 }
 
+// rotation around x, then y, then z, all in radians
+pub fn quaternion_from_euler(angles : [f32; 3]) -> [f32; 4] {
+    let qx = get_rotation_quaternion([1.0, 0.0, 0.0], angles[0]);
+    let qy = get_rotation_quaternion([0.0, 1.0, 0.0], angles[1]);
+    let qz = get_rotation_quaternion([0.0, 0.0, 1.0], angles[2]);
+
+    q_mul(qz, q_mul(qy, qx))
+}
+
+// the angles that quaternion_from_euler needs to give q, the y angle is between -pi/2 and pi/2
+// from https://en.wikipedia.org/wiki/Conversion_between_quaternions_and_Euler_angles
+pub fn quaternion_to_euler(q : [f32; 4]) -> [f32; 3] {
+    let [w, x, y, z] = q;
+
+    let angle_x = f32::atan2(2.0 * (w * x + y * z), 1.0 - 2.0 * (x * x + y * y));
+    let angle_y = f32::asin((2.0 * (w * y - z * x)).clamp(-1.0, 1.0));
+    let angle_z = f32::atan2(2.0 * (w * z + x * y), 1.0 - 2.0 * (y * y + z * z));
+
+    [angle_x, angle_y, angle_z]
+}
+
 /// x + ky
 pub fn vec_add(x : [f32;3], y : [f32;3], k : f32) -> [f32; 3] {
     [
@@ -67,3 +88,27 @@ pub fn rotate_vec2(v : [f32; 2], angle : f32) -> [f32; 2] {
 
     [new_x, new_y]
 }
+
+pub fn dot(x : [f32; 3], y : [f32; 3]) -> f32 {
+    x[0] * y[0] + x[1] * y[1] + x[2] * y[2]
+}
+
+pub fn cross(x : [f32; 3], y : [f32; 3]) -> [f32; 3] {
+    [
+        x[1] * y[2] - x[2] * y[1],
+        x[2] * y[0] - x[0] * y[2],
+        x[0] * y[1] - x[1] * y[0]
+    ]
+}
+
+pub fn length(v : [f32; 3]) -> f32 {
+    f32::sqrt(dot(v, v))
+}
+
+pub fn vec_scale(v : [f32; 3], k : f32) -> [f32; 3] {
+    [
+        v[0] * k,
+        v[1] * k,
+        v[2] * k
+    ]
+}